@@ -1,63 +1,105 @@
-use reqwest::{Client, ClientBuilder, header, RequestBuilder};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+use reqwest::{Certificate, Client, ClientBuilder, header, Identity, Method, Proxy, Request, RequestBuilder, Response,
+              StatusCode};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::redirect;
+use serde::Serialize;
+
+use crate::admin::brokers::PulsarAdminBrokers;
 use crate::admin::clusters::PulsarAdminClusters;
 use crate::admin::error::Error;
 use crate::admin::functions::PulsarAdminFunctions;
 use crate::admin::namespaces::PulsarAdminNamespaces;
+use crate::admin::retry::RetryPolicy;
+use crate::admin::schemas::PulsarAdminSchemas;
 use crate::admin::sinks::PulsarAdminSinks;
 use crate::admin::tenants::PulsarAdminTenants;
 use crate::admin::topics::PulsarAdminTopics;
+use crate::auth::auth::Authn;
 
-#[derive(Debug, Clone)]
+/// Client of the Pulsar admin REST API. It holds a single `reqwest::Client`, so connections
+/// are pooled across all requests made through it.
+#[derive(Clone)]
 pub struct PulsarAdmin {
     service_url: String,
-    auth_name: Option<String>,
-    auth_params: Option<String>,
+    client: Client,
+    auth_provider: Option<Arc<dyn Authn>>,
+    retry_policy: RetryPolicy,
 }
 
+/// Brokers redirect admin requests to the broker owning the bundle, usually with a 307.
+const MAX_REDIRECTS: usize = 10;
+
 impl PulsarAdmin {
-    pub fn new(service_url: String, auth_name: Option<String>, auth_params: Option<String>) -> PulsarAdmin {
-        PulsarAdmin {
+    pub fn builder(service_url: String) -> PulsarAdminBuilder {
+        PulsarAdminBuilder {
             service_url,
-            auth_name,
-            auth_params,
+            auth_provider: None,
+            allow_insecure_connection: false,
+            tls_hostname_verification_enabled: true,
+            trust_certs_file_path: None,
+            client_cert_file_path: None,
+            client_key_file_path: None,
+            proxy_url: None,
+            timeout: None,
+            connect_timeout: None,
+            retry_policy: RetryPolicy::default(),
         }
     }
 
-    fn client_builder(&self) -> Result<ClientBuilder, Error> {
-        let mut builder = Client::builder();
-        if self.auth_name.is_some() && self.auth_params.is_some() {
-            if self.auth_name.as_ref().unwrap() == "token" {
-                let mut headers = header::HeaderMap::new();
-                headers.insert("Authorization", header::HeaderValue::from_str(format!("Bearer {}", self.auth_params.as_ref().unwrap()).as_str()).unwrap());
-                builder = builder.default_headers(headers);
-            }
-        }
-        Ok(builder)
+    pub(crate) fn put(&self, p: &str) -> Result<AdminRequest<'_>, Error> {
+        Ok(AdminRequest::new(self, self.client.put(self.service_url.clone() + p)))
     }
 
-    pub(crate) fn put(&self, p: &str) -> Result<RequestBuilder, Error> {
-        Ok(self.client_builder()?
-            .build()?
-            .put(self.service_url.clone() + p))
+    pub(crate) fn get(&self, p: &str) -> Result<AdminRequest<'_>, Error> {
+        Ok(AdminRequest::new(self, self.client.get(self.service_url.clone() + p)))
     }
 
-    pub(crate) fn get(&self, p: &str) -> Result<RequestBuilder, Error> {
-        Ok(self.client_builder()?
-            .build()?
-            .get(self.service_url.clone() + p))
+    pub(crate) fn post(&self, p: &str) -> Result<AdminRequest<'_>, Error> {
+        Ok(AdminRequest::new(self, self.client.post(self.service_url.clone() + p)))
     }
 
-    pub(crate) fn post(&self, p: &str) -> Result<RequestBuilder, Error> {
-        Ok(self.client_builder()?
-            .build()?
-            .post(self.service_url.clone() + p))
+    pub(crate) fn delete(&self, p: &str) -> Result<AdminRequest<'_>, Error> {
+        Ok(AdminRequest::new(self, self.client.delete(self.service_url.clone() + p)))
     }
 
-    pub(crate) fn delete(&self, p: &str) -> Result<RequestBuilder, Error> {
-        Ok(self.client_builder()?
-            .build()?
-            .delete(self.service_url.clone() + p))
+    /// Executes the request, following redirects. Unlike reqwest's redirect policy, this keeps
+    /// the `Authorization` header when redirected to another broker, and keeps the method and
+    /// body for every redirect except `303 See Other`.
+    async fn execute(&self, request: &Request) -> Result<Response, reqwest::Error> {
+        let mut current = clone_request(request);
+        for _ in 0..MAX_REDIRECTS {
+            let url = current.url().clone();
+            let res = self.client.execute(clone_request(&current)).await?;
+            debug!("{} {} {}", current.method(), url, res.status());
+            let location = match res.headers().get(header::LOCATION).and_then(|l| l.to_str().ok()) {
+                Some(location) if res.status().is_redirection() => location.to_string(),
+                _ => return Ok(res),
+            };
+            let target = match url.join(location.as_str()) {
+                Ok(target) => target,
+                Err(_) => return Ok(res),
+            };
+            debug!("redirected to {}", target);
+            if res.status() == StatusCode::SEE_OTHER {
+                let mut next = Request::new(Method::GET, target);
+                *next.headers_mut() = current.headers().clone();
+                next.headers_mut().remove(header::CONTENT_TYPE);
+                next.headers_mut().remove(header::CONTENT_LENGTH);
+                current = next;
+            } else {
+                *current.url_mut() = target;
+            }
+        }
+        // Too many redirects, the last redirect response is reported as the error.
+        self.client.execute(current).await
+    }
+
+    pub fn service_url(&self) -> &str {
+        self.service_url.as_str()
     }
 
     pub fn clusters(&self) -> PulsarAdminClusters {
@@ -95,4 +137,394 @@ impl PulsarAdmin {
             admin: self,
         }
     }
+
+    pub fn brokers(&self) -> PulsarAdminBrokers<'_> {
+        PulsarAdminBrokers {
+            admin: self,
+        }
+    }
+
+    pub fn schemas(&self) -> PulsarAdminSchemas<'_> {
+        PulsarAdminSchemas {
+            admin: self,
+        }
+    }
+}
+
+/// A request to the admin API. `send` retries it according to the admin's `RetryPolicy`,
+/// follows redirects and fails with a typed error unless the broker answers with a success status.
+pub(crate) struct AdminRequest<'a> {
+    admin: &'a PulsarAdmin,
+    request: RequestBuilder,
+}
+
+impl<'a> AdminRequest<'a> {
+    fn new(admin: &'a PulsarAdmin, request: RequestBuilder) -> AdminRequest<'a> {
+        AdminRequest { admin, request }
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> AdminRequest<'a> {
+        AdminRequest::new(self.admin, self.request.query(query))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> AdminRequest<'a> {
+        AdminRequest::new(self.admin, self.request.json(json))
+    }
+
+    pub fn header(self, key: HeaderName, value: HeaderValue) -> AdminRequest<'a> {
+        AdminRequest::new(self.admin, self.request.header(key, value))
+    }
+
+    pub fn body<T: Into<reqwest::Body>>(self, body: T) -> AdminRequest<'a> {
+        AdminRequest::new(self.admin, self.request.body(body))
+    }
+
+    pub async fn send(self) -> Result<Response, Error> {
+        let mut request = self.request;
+        if let Some(auth) = &self.admin.auth_provider {
+            for (name, value) in auth.http_headers().await? {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| Error::Custom(format!("invalid auth header [{}]: {}", name, e)))?;
+                let value = HeaderValue::from_str(value.as_str())
+                    .map_err(|e| Error::Custom(format!("invalid value of auth header [{}]: {}", name, e)))?;
+                request = request.header(name, value);
+            }
+        }
+        let request = request.build()?;
+        let policy = &self.admin.retry_policy;
+        let mut attempt = 1;
+        loop {
+            let retry = attempt < policy.max_attempts;
+            let res = match self.admin.execute(&request).await {
+                Ok(res) => res,
+                Err(e) if retry && policy.should_retry_error(request.method(), &e) => {
+                    let backoff = policy.backoff(attempt);
+                    warn!("{} {} failed: {}, retrying in {:?}", request.method(), request.url(), e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let status = res.status();
+            if status.is_success() {
+                return Ok(res);
+            }
+            if retry && policy.should_retry_status(request.method(), status) {
+                let backoff = retry_after(&res).unwrap_or_else(|| policy.backoff(attempt)).min(policy.max_backoff);
+                warn!("{} {} returned {}, retrying in {:?}", request.method(), request.url(), status, backoff);
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+                continue;
+            }
+            let body = res.text().await?;
+            return Err(Error::from_response(status, body.as_str()));
+        }
+    }
+}
+
+/// Copies a request. Admin requests are always built from in-memory bodies, so the copy
+/// cannot fail.
+fn clone_request(request: &Request) -> Request {
+    request.try_clone().expect("admin requests have buffered bodies")
+}
+
+/// Delay requested by the broker through a `Retry-After` header in seconds.
+fn retry_after(res: &Response) -> Option<Duration> {
+    res.headers().get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[derive(Clone)]
+pub struct PulsarAdminBuilder {
+    service_url: String,
+    auth_provider: Option<Arc<dyn Authn>>,
+    allow_insecure_connection: bool,
+    tls_hostname_verification_enabled: bool,
+    trust_certs_file_path: Option<PathBuf>,
+    client_cert_file_path: Option<PathBuf>,
+    client_key_file_path: Option<PathBuf>,
+    proxy_url: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+}
+
+impl PulsarAdminBuilder {
+    /// Authenticates every request with the provider's headers, so refreshed tokens are
+    /// picked up without rebuilding the admin.
+    pub fn with_auth_provider(mut self, auth_provider: Arc<dyn Authn>) -> Self {
+        self.auth_provider = Some(auth_provider);
+        self
+    }
+
+    pub fn with_allow_insecure_connection(mut self, allow: bool) -> Self {
+        self.allow_insecure_connection = allow;
+        self
+    }
+
+    pub fn with_tls_hostname_verification_enabled(mut self, enabled: bool) -> Self {
+        self.tls_hostname_verification_enabled = enabled;
+        self
+    }
+
+    /// Trusts the PEM encoded CA certificates in the given file, in addition to the system roots.
+    pub fn with_trust_certs_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.trust_certs_file_path = Some(path.into());
+        self
+    }
+
+    /// Presents the PEM encoded certificate and PKCS#8 private key to the server.
+    pub fn with_client_certificate<P: Into<PathBuf>>(mut self, cert_file_path: P, key_file_path: P) -> Self {
+        self.client_cert_file_path = Some(cert_file_path.into());
+        self.client_key_file_path = Some(key_file_path.into());
+        self
+    }
+
+    /// Sends all requests through the given HTTP(S) proxy.
+    pub fn with_proxy(mut self, proxy_url: String) -> Self {
+        self.proxy_url = Some(proxy_url);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// A client with the TLS trust, proxy and timeouts of the admin but without its client
+    /// certificate, for other HTTP services of the cluster such as OAuth2 issuers.
+    pub fn http_client(&self) -> Result<Client, Error> {
+        Ok(self.client_builder()?.build()?)
+    }
+
+    fn client_builder(&self) -> Result<ClientBuilder, Error> {
+        let mut builder = Client::builder()
+            .redirect(redirect::Policy::none())
+            .danger_accept_invalid_certs(self.allow_insecure_connection)
+            .danger_accept_invalid_hostnames(!self.tls_hostname_verification_enabled);
+        if let Some(path) = &self.trust_certs_file_path {
+            for cert in pem_certificates(read_file(path)?.as_slice())? {
+                builder = builder.add_root_certificate(Certificate::from_pem(cert.as_slice())?);
+            }
+        }
+        if let Some(proxy_url) = &self.proxy_url {
+            builder = builder.proxy(Proxy::all(proxy_url.as_str())?);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        Ok(builder)
+    }
+
+    pub fn build(self) -> Result<PulsarAdmin, Error> {
+        let mut builder = self.client_builder()?;
+        match (&self.client_cert_file_path, &self.client_key_file_path) {
+            (Some(cert_path), Some(key_path)) => {
+                builder = builder.identity(identity(read_file(cert_path)?.as_slice(),
+                                                    read_file(key_path)?.as_slice())?);
+            }
+            (None, None) => {}
+            _ => return Err(Error::Custom("both client certificate and key are required".to_string())),
+        }
+        Ok(PulsarAdmin {
+            service_url: self.service_url,
+            client: builder.build()?,
+            auth_provider: self.auth_provider,
+            retry_policy: self.retry_policy,
+        })
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<Vec<u8>, Error> {
+    std::fs::read(path).map_err(|e| Error::Custom(format!("failed to read {}: {}", path.display(), e)))
+}
+
+/// Splits a PEM bundle into its certificates, since `Certificate::from_pem` only reads the first.
+fn pem_certificates(pem: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let certs = openssl::x509::X509::stack_from_pem(pem)
+        .map_err(|e| Error::Custom(format!("invalid CA certificates: {}", e)))?;
+    certs.iter()
+        .map(|cert| cert.to_pem().map_err(|e| Error::Custom(e.to_string())))
+        .collect()
+}
+
+/// The native TLS backend only accepts PKCS#12 identities, so the PEM certificate and key
+/// are packed into an unencrypted PKCS#12 archive first.
+fn identity(cert_pem: &[u8], key_pem: &[u8]) -> Result<Identity, Error> {
+    let to_error = |e: openssl::error::ErrorStack| Error::Custom(format!("invalid client certificate: {}", e));
+    let mut chain = openssl::x509::X509::stack_from_pem(cert_pem).map_err(to_error)?.into_iter();
+    let cert = chain.next().ok_or_else(|| Error::Custom("no client certificate found".to_string()))?;
+    let key = openssl::pkey::PKey::private_key_from_pem(key_pem).map_err(to_error)?;
+    let mut ca = openssl::stack::Stack::new().map_err(to_error)?;
+    for c in chain {
+        ca.push(c).map_err(to_error)?;
+    }
+    let pkcs12 = openssl::pkcs12::Pkcs12::builder()
+        .name("pulsar-smith")
+        .pkey(&key)
+        .cert(&cert)
+        .ca(ca)
+        .build2("")
+        .map_err(to_error)?;
+    Ok(Identity::from_pkcs12_der(pkcs12.to_der().map_err(to_error)?.as_slice(), "")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::x509::{X509, X509NameBuilder};
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::admin::admin::{identity, pem_certificates, PulsarAdmin};
+    use crate::admin::error::Error;
+    use crate::admin::retry::RetryPolicy;
+    use crate::admin::tenants::TenantInfo;
+    use crate::auth::auth::Authn;
+    use crate::testing::{MockResponse, MockServer};
+
+    fn admin(url: &str) -> PulsarAdmin {
+        PulsarAdmin::builder(url.to_string())
+            .with_auth_provider(Arc::from(crate::auth::auth::create("token".to_string(), "secret".to_string(), reqwest::Client::new()).unwrap()))
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(5),
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_unavailable() {
+        let server = MockServer::start(|_, i| match i {
+            0 => MockResponse::new(503, r#"{"reason":"bundle unloading"}"#),
+            _ => MockResponse::new(200, r#"["public"]"#),
+        }).await;
+        assert_eq!(admin(server.url()).tenants().list().await.unwrap(), vec!["public".to_string()]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let server = MockServer::start(|_, _| MockResponse::new(503, "")).await;
+        let e = admin(server.url()).tenants().list().await.unwrap_err();
+        assert!(matches!(e, Error::ServerError { status: 503, .. }));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_unsafe_requests() {
+        let server = MockServer::start(|_, _| MockResponse::new(500, r#"{"reason":"boom"}"#)).await;
+        let namespace = "public/default".parse().unwrap();
+        let e = admin(server.url()).namespaces().grant_permission(&namespace, "role", &[]).await.unwrap_err();
+        assert!(matches!(e, Error::ServerError { status: 500, .. }));
+        assert_eq!(server.requests().len(), 1);
+
+        let server = MockServer::start(|_, _| MockResponse::new(504, "")).await;
+        let e = admin(server.url()).topics().create_non_partitioned_topic(&"public/default/orders".parse().unwrap()).await.unwrap_err();
+        assert!(matches!(e, Error::ServerError { status: 504, .. }));
+        assert_eq!(server.requests().len(), 1);
+
+        let server = MockServer::start(|_, _| MockResponse::new(404, r#"{"reason":"Tenant does not exist"}"#)).await;
+        let e = admin(server.url()).tenants().get("missing").await.unwrap_err();
+        assert!(matches!(e, Error::NotFound(reason) if reason == "Tenant does not exist"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn redirect_keeps_method_body_and_auth() {
+        let owner = MockServer::start(|_, _| MockResponse::new(204, "")).await;
+        let location = format!("{}/admin/v2/tenants/t", owner.url());
+        let broker = MockServer::start(move |_, _| {
+            MockResponse::new(307, "").with_header("Location", location.as_str())
+        }).await;
+        let info = TenantInfo {
+            admin_roles: vec!["admin".to_string()],
+            allowed_clusters: vec!["standalone".to_string()],
+        };
+        admin(broker.url()).tenants().create("t", info).await.unwrap();
+
+        let requests = owner.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].path, "/admin/v2/tenants/t");
+        assert_eq!(requests[0].headers.get("authorization").map(|s| s.as_str()), Some("Bearer secret"));
+        assert!(requests[0].body.contains("standalone"));
+    }
+
+    struct HeaderAuthn;
+
+    #[async_trait::async_trait]
+    impl Authn for HeaderAuthn {
+        fn auth_method_name(&self) -> String {
+            "custom".to_string()
+        }
+
+        async fn initialize(&mut self) -> Result<(), crate::auth::error::Error> {
+            Ok(())
+        }
+
+        async fn http_headers(&self) -> Result<Vec<(String, String)>, crate::auth::error::Error> {
+            Ok(vec![("X-Custom-Auth".to_string(), "credentials".to_string())])
+        }
+    }
+
+    #[tokio::test]
+    async fn auth_provider_headers() {
+        let server = MockServer::start(|_, _| MockResponse::new(200, "[]")).await;
+        let admin = PulsarAdmin::builder(server.url().to_string())
+            .with_auth_provider(Arc::new(HeaderAuthn))
+            .build()
+            .unwrap();
+        admin.clusters().list().await.unwrap();
+        let requests = server.requests();
+        assert_eq!(requests[0].headers.get("x-custom-auth").map(|s| s.as_str()), Some("credentials"));
+        assert!(!requests[0].headers.contains_key("authorization"));
+    }
+
+    fn self_signed(cn: &str) -> (Vec<u8>, Vec<u8>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", cn).unwrap();
+        let name = name.build();
+        let mut cert = X509::builder().unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        (cert.build().to_pem().unwrap(), key.private_key_to_pem_pkcs8().unwrap())
+    }
+
+    #[test]
+    fn certificates_from_pem() {
+        let (ca1, _) = self_signed("ca1");
+        let (ca2, key) = self_signed("ca2");
+        let bundle = [ca1.as_slice(), ca2.as_slice()].concat();
+        assert_eq!(pem_certificates(bundle.as_slice()).unwrap().len(), 2);
+        assert!(identity(ca2.as_slice(), key.as_slice()).is_ok());
+        assert!(identity(ca1.as_slice(), b"not a key").is_err());
+    }
 }
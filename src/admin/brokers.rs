@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::error::Error;
+
+pub struct PulsarAdminBrokers<'a> {
+    pub(crate) admin: &'a PulsarAdmin,
+}
+
+/// How a broker owns a bundle, with the isolation policies deciding the assignment.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NamespaceOwnershipStatus {
+    /// `primary`, `secondary` or `shared`
+    pub broker_assignment: String,
+
+    pub is_controlled: bool,
+
+    pub is_active: bool,
+}
+
+impl<'a> PulsarAdminBrokers<'a> {
+    /// The brokers of a cluster, as `host:port` of their web service.
+    pub async fn active_brokers(&self, cluster: &str) -> Result<Vec<String>, Error> {
+        Ok(self.admin.get(format!("/admin/v2/brokers/{}", cluster).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    /// The bundles a broker owns, keyed by `tenant/namespace/bundle`.
+    pub async fn owned_namespaces(&self, cluster: &str, broker: &str)
+                                  -> Result<BTreeMap<String, NamespaceOwnershipStatus>, Error> {
+        Ok(self.admin.get(format!("/admin/v2/brokers/{}/{}/ownedNamespaces", cluster, broker).as_str())?
+            .send().await?
+            .json().await?)
+    }
+}
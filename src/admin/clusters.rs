@@ -1,3 +1,9 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
 use crate::admin::admin::PulsarAdmin;
 use crate::admin::error::Error;
 
@@ -5,10 +11,259 @@ pub struct PulsarAdminClusters<'a> {
     pub(crate) admin: &'a PulsarAdmin,
 }
 
+/// Service URLs and settings of a cluster, as used for geo-replication and redirects.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_url_tls: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broker_service_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broker_service_url_tls: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_service_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_protocol: Option<ProxyProtocol>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub peer_cluster_names: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authentication_plugin: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authentication_parameters: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broker_client_tls_enabled: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_allow_insecure_connection: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broker_client_trust_certs_file_path: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listener_name: Option<String>,
+
+    /// Settings without a field here, e.g. key stores, kept so updates do not drop them.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyProtocol {
+    #[serde(rename = "SNI")]
+    #[value(name = "SNI")]
+    Sni,
+}
+
+/// A group of brokers, so bundles of a namespace with anti-affinity go to different domains.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FailureDomain {
+    #[serde(default)]
+    pub brokers: Vec<String>,
+}
+
+/// Which brokers may serve which namespaces, as regular expressions.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceIsolationData {
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+
+    #[serde(default)]
+    pub primary: Vec<String>,
+
+    #[serde(default)]
+    pub secondary: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_failover_policy: Option<AutoFailoverPolicy>,
+}
+
+/// When to fall back from the primary to the secondary brokers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AutoFailoverPolicy {
+    pub policy_type: AutoFailoverPolicyType,
+
+    /// `min_limit` and `usage_threshold` for `min_available`.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoFailoverPolicyType {
+    #[serde(rename = "min_available")]
+    #[value(name = "min_available")]
+    MinAvailable,
+}
+
 impl<'a> PulsarAdminClusters<'a> {
+    fn path(cluster: &str) -> String {
+        format!("/admin/v2/clusters/{}", cluster)
+    }
+
     pub async fn list(&self) -> Result<Vec<String>, Error> {
         Ok(self.admin.get("/admin/v2/clusters")?
             .send().await?
             .json::<Vec<String>>().await?)
     }
+
+    pub async fn get(&self, cluster: &str) -> Result<ClusterData, Error> {
+        Ok(self.admin.get(Self::path(cluster).as_str())?
+            .send().await?
+            .json::<ClusterData>().await?)
+    }
+
+    pub async fn create(&self, cluster: &str, data: &ClusterData) -> Result<(), Error> {
+        self.admin.put(Self::path(cluster).as_str())?
+            .json(data)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn update(&self, cluster: &str, data: &ClusterData) -> Result<(), Error> {
+        self.admin.post(Self::path(cluster).as_str())?
+            .json(data)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, cluster: &str) -> Result<(), Error> {
+        self.admin.delete(Self::path(cluster).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn peer_clusters(&self, cluster: &str) -> Result<Vec<String>, Error> {
+        let peers = self.admin.get(format!("{}/peers", Self::path(cluster)).as_str())?
+            .send().await?
+            .json::<Option<Vec<String>>>().await?;
+        Ok(peers.unwrap_or_default())
+    }
+
+    pub async fn update_peer_clusters(&self, cluster: &str, peers: &[String]) -> Result<(), Error> {
+        self.admin.post(format!("{}/peers", Self::path(cluster)).as_str())?
+            .json(peers)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn failure_domains(&self, cluster: &str) -> Result<BTreeMap<String, FailureDomain>, Error> {
+        Ok(self.admin.get(format!("{}/failureDomains", Self::path(cluster)).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    pub async fn failure_domain(&self, cluster: &str, domain: &str) -> Result<FailureDomain, Error> {
+        Ok(self.admin.get(format!("{}/failureDomains/{}", Self::path(cluster), domain).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    /// Creates or replaces a failure domain.
+    pub async fn set_failure_domain(&self, cluster: &str, domain: &str, data: &FailureDomain) -> Result<(), Error> {
+        self.admin.post(format!("{}/failureDomains/{}", Self::path(cluster), domain).as_str())?
+            .json(data)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn delete_failure_domain(&self, cluster: &str, domain: &str) -> Result<(), Error> {
+        self.admin.delete(format!("{}/failureDomains/{}", Self::path(cluster), domain).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn namespace_isolation_policies(&self, cluster: &str)
+                                              -> Result<BTreeMap<String, NamespaceIsolationData>, Error> {
+        Ok(self.admin.get(format!("{}/namespaceIsolationPolicies", Self::path(cluster)).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    pub async fn namespace_isolation_policy(&self, cluster: &str, policy: &str) -> Result<NamespaceIsolationData, Error> {
+        Ok(self.admin.get(format!("{}/namespaceIsolationPolicies/{}", Self::path(cluster), policy).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    /// Creates or replaces a namespace isolation policy.
+    pub async fn set_namespace_isolation_policy(&self, cluster: &str, policy: &str,
+                                                data: &NamespaceIsolationData) -> Result<(), Error> {
+        self.admin.post(format!("{}/namespaceIsolationPolicies/{}", Self::path(cluster), policy).as_str())?
+            .json(data)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn delete_namespace_isolation_policy(&self, cluster: &str, policy: &str) -> Result<(), Error> {
+        self.admin.delete(format!("{}/namespaceIsolationPolicies/{}", Self::path(cluster), policy).as_str())?
+            .send().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::admin::admin::PulsarAdmin;
+    use crate::admin::clusters::{AutoFailoverPolicyType, ClusterData, FailureDomain, ProxyProtocol};
+    use crate::testing::{MockResponse, MockServer};
+
+    #[test]
+    fn cluster_data() {
+        let value = json!({
+            "serviceUrl": "http://broker:8080",
+            "brokerServiceUrl": "pulsar://broker:6650",
+            "proxyProtocol": "SNI",
+            "peerClusterNames": ["us-east"],
+            "brokerClientTlsEnabled": false,
+            "brokerClientTlsTrustStoreType": "JKS"
+        });
+        let data: ClusterData = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(data.broker_service_url.as_deref(), Some("pulsar://broker:6650"));
+        assert_eq!(data.proxy_protocol, Some(ProxyProtocol::Sni));
+        assert_eq!(data.extra["brokerClientTlsTrustStoreType"], "JKS");
+        assert_eq!(serde_json::to_value(&data).unwrap(), value);
+    }
+
+    #[tokio::test]
+    async fn failure_domains_and_isolation_policies() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/clusters/standalone/failureDomains") =>
+                MockResponse::new(200, r#"{"rack-1":{"brokers":["b1:8080"]}}"#),
+            ("GET", "/admin/v2/clusters/standalone/namespaceIsolationPolicies/p1") =>
+                MockResponse::new(200, r#"{"namespaces":["acme/.*"],"primary":["b1.*"],"secondary":[],
+                    "auto_failover_policy":{"policy_type":"min_available","parameters":{"min_limit":"1"}}}"#),
+            ("POST", _) => MockResponse::new(204, ""),
+            _ => MockResponse::new(404, "{}"),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let clusters = admin.clusters();
+
+        let domains = clusters.failure_domains("standalone").await.unwrap();
+        assert_eq!(domains["rack-1"].brokers, vec!["b1:8080"]);
+        clusters.set_failure_domain("standalone", "rack-2", &FailureDomain { brokers: vec!["b2:8080".to_string()] })
+            .await.unwrap();
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "/admin/v2/clusters/standalone/failureDomains/rack-2");
+        assert_eq!(request.body, r#"{"brokers":["b2:8080"]}"#);
+
+        let policy = clusters.namespace_isolation_policy("standalone", "p1").await.unwrap();
+        assert_eq!(policy.namespaces, vec!["acme/.*"]);
+        let failover = policy.auto_failover_policy.unwrap();
+        assert_eq!(failover.policy_type, AutoFailoverPolicyType::MinAvailable);
+        assert_eq!(failover.parameters["min_limit"], "1");
+        assert!(clusters.failure_domain("standalone", "missing").await.is_err());
+    }
 }
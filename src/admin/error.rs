@@ -1,9 +1,62 @@
 use std::fmt::{Display, Formatter};
 
+use reqwest::StatusCode;
+use serde::Deserialize;
+
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     SerDe(String),
-    Custom(String)
+    NotFound(String),
+    Conflict(String),
+    Unauthorized(String),
+    Forbidden(String),
+    PreconditionFailed(String),
+    /// Any other 4xx: the broker rejected the request, e.g. as invalid or not allowed.
+    ClientError { status: u16, reason: String },
+    /// A 5xx: the broker failed to handle the request.
+    ServerError { status: u16, reason: String },
+    Custom(String),
+}
+
+/// Body of the broker's error responses.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    reason: Option<String>,
+}
+
+impl Error {
+    /// Maps a failed response to an error, taking the reason from the broker's `{"reason": ...}`
+    /// body, or the raw body if it is something else.
+    pub fn from_response(status: StatusCode, body: &str) -> Error {
+        let reason = match serde_json::from_str::<ErrorResponse>(body) {
+            Ok(ErrorResponse { reason: Some(reason) }) => reason,
+            _ if !body.trim().is_empty() => body.trim().to_string(),
+            _ => status.canonical_reason().unwrap_or("unknown error").to_string(),
+        };
+        match status {
+            StatusCode::NOT_FOUND => Error::NotFound(reason),
+            StatusCode::CONFLICT => Error::Conflict(reason),
+            StatusCode::UNAUTHORIZED => Error::Unauthorized(reason),
+            StatusCode::FORBIDDEN => Error::Forbidden(reason),
+            StatusCode::PRECONDITION_FAILED => Error::PreconditionFailed(reason),
+            _ if status.is_client_error() => Error::ClientError { status: status.as_u16(), reason },
+            _ => Error::ServerError { status: status.as_u16(), reason },
+        }
+    }
+
+    /// The HTTP status the broker answered with, if the error comes from a response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::NotFound(_) => Some(404),
+            Error::Conflict(_) => Some(409),
+            Error::Unauthorized(_) => Some(401),
+            Error::Forbidden(_) => Some(403),
+            Error::PreconditionFailed(_) => Some(412),
+            Error::ClientError { status, .. } | Error::ServerError { status, .. } => Some(*status),
+            Error::SerDe(_) | Error::Custom(_) => None,
+        }
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -18,6 +71,12 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<crate::auth::error::Error> for Error {
+    fn from(e: crate::auth::error::Error) -> Self {
+        Error::Custom(format!("failed to authenticate: {}", e))
+    }
+}
+
 impl From<String> for Error {
     fn from(s: String) -> Self {
         Error::Custom(s)
@@ -28,6 +87,13 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::SerDe(str) => write!(f, "serde {}", str),
+            Error::NotFound(reason) => write!(f, "not found: {}", reason),
+            Error::Conflict(reason) => write!(f, "conflict: {}", reason),
+            Error::Unauthorized(reason) => write!(f, "unauthorized: {}", reason),
+            Error::Forbidden(reason) => write!(f, "forbidden: {}", reason),
+            Error::PreconditionFailed(reason) => write!(f, "precondition failed: {}", reason),
+            Error::ClientError { status, reason } => write!(f, "request rejected with status {}: {}", status, reason),
+            Error::ServerError { status, reason } => write!(f, "request failed with status {}: {}", status, reason),
             Error::Custom(str) => write!(f, "{}", str),
         }
     }
@@ -36,3 +102,27 @@ impl Display for Error {
 impl std::error::Error for Error {
 
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use crate::admin::error::Error;
+
+    #[test]
+    fn from_response() {
+        let e = Error::from_response(StatusCode::NOT_FOUND, r#"{"reason":"Topic not found"}"#);
+        assert!(matches!(&e, Error::NotFound(reason) if reason == "Topic not found"));
+        assert_eq!(e.status(), Some(404));
+
+        let e = Error::from_response(StatusCode::CONFLICT, "This topic already exists\n");
+        assert!(matches!(&e, Error::Conflict(reason) if reason == "This topic already exists"));
+
+        let e = Error::from_response(StatusCode::METHOD_NOT_ALLOWED, r#"{"reason":"Topic level policies are disabled"}"#);
+        assert!(matches!(&e, Error::ClientError { status: 405, .. }));
+        assert_eq!(e.status(), Some(405));
+
+        let e = Error::from_response(StatusCode::SERVICE_UNAVAILABLE, "");
+        assert!(matches!(&e, Error::ServerError { status: 503, reason } if reason == "Service Unavailable"));
+    }
+}
@@ -1,14 +1,32 @@
 use crate::admin::admin::PulsarAdmin;
 use crate::admin::error::Error;
+use crate::admin::naming::NamespaceName;
 
 pub struct PulsarAdminFunctions<'a> {
     pub(crate) admin: &'a PulsarAdmin,
 }
 
 impl<'a> PulsarAdminFunctions<'a> {
-    pub async fn list(&self, namespace: &str) -> Result<Vec<String>, Error> {
+    pub async fn list(&self, namespace: &NamespaceName) -> Result<Vec<String>, Error> {
         Ok(self.admin.get(format!("/admin/v3/functions/{}", namespace).as_str())?
             .send().await?
             .json::<Vec<String>>().await?)
     }
+
+    pub async fn delete(&self, namespace: &NamespaceName, function: &str) -> Result<(), Error> {
+        self.admin.delete(format!("/admin/v3/functions/{}/{}", namespace, function).as_str())?
+            .send().await?;
+        Ok(())
+    }
+}
+
+/// Functions and sinks of a namespace, or none when the cluster runs no functions worker.
+pub(crate) fn workers_list(r: Result<Vec<String>, Error>) -> Result<Vec<String>, Error> {
+    match r {
+        Err(Error::NotFound(reason)) | Err(Error::Conflict(reason)) => {
+            debug!("no functions worker: {}", reason);
+            Ok(Vec::new())
+        }
+        r => r,
+    }
 }
@@ -6,3 +6,7 @@ pub mod topics;
 pub mod error;
 pub mod functions;
 pub mod sinks;
+pub mod brokers;
+pub mod schemas;
+pub mod naming;
+pub mod retry;
@@ -1,34 +1,573 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::admin::admin::PulsarAdmin;
 use crate::admin::error::Error;
+use crate::admin::naming::NamespaceName;
 
 pub struct PulsarAdminNamespaces<'a> {
     pub(crate) admin: &'a PulsarAdmin,
 }
 
+/// The policies document of a namespace. Rates are keyed by cluster, backlog quotas by
+/// `BacklogQuotaType`; settings without a field here are kept in `extra`.
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct NamespacePolicies {
-    pub bundles: Option<BundlesData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_policies: Option<AuthPolicies>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub replication_clusters: Option<Vec<String>>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundles: Option<BundlesData>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backlog_quota_map: Option<BTreeMap<String, BacklogQuota>>,
+
+    #[serde(rename = "topicDispatchRate", default, skip_serializing_if = "Option::is_none")]
+    pub topic_dispatch_rate: Option<BTreeMap<String, DispatchRate>>,
+
+    #[serde(rename = "subscriptionDispatchRate", default, skip_serializing_if = "Option::is_none")]
+    pub subscription_dispatch_rate: Option<BTreeMap<String, DispatchRate>>,
+
+    #[serde(rename = "replicatorDispatchRate", default, skip_serializing_if = "Option::is_none")]
+    pub replicator_dispatch_rate: Option<BTreeMap<String, DispatchRate>>,
+
+    #[serde(rename = "clusterSubscribeRate", default, skip_serializing_if = "Option::is_none")]
+    pub cluster_subscribe_rate: Option<BTreeMap<String, SubscribeRate>>,
+
+    #[serde(rename = "publishMaxMessageRate", default, skip_serializing_if = "Option::is_none")]
+    pub publish_max_message_rate: Option<BTreeMap<String, PublishRate>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub persistence: Option<PersistencePolicies>,
+
+    #[serde(rename = "deduplicationEnabled", default, skip_serializing_if = "Option::is_none")]
+    pub deduplication_enabled: Option<bool>,
+
+    #[serde(rename = "autoTopicCreationOverride", default, skip_serializing_if = "Option::is_none")]
+    pub auto_topic_creation_override: Option<AutoTopicCreationOverride>,
+
+    #[serde(rename = "autoSubscriptionCreationOverride", default, skip_serializing_if = "Option::is_none")]
+    pub auto_subscription_creation_override: Option<AutoSubscriptionCreationOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_ttl_in_seconds: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_expiration_time_minutes: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_policies: Option<RetentionPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_required: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delayed_delivery_policies: Option<DelayedDeliveryPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_topic_policies: Option<InactiveTopicPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_producers_per_topic: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_consumers_per_topic: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_consumers_per_subscription: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_subscriptions_per_topic: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_unacked_messages_per_consumer: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_unacked_messages_per_subscription: Option<i32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction_threshold: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offload_threshold: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offload_deletion_lag_ms: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_compatibility_strategy: Option<SchemaCompatibilityStrategy>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_allow_auto_update_schema: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_validation_enforced: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<BTreeMap<String, String>>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuthPolicies {
+    #[serde(default)]
+    pub namespace_auth: BTreeMap<String, Vec<String>>,
+
+    #[serde(default)]
+    pub destination_auth: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+
+    #[serde(default)]
+    pub subscription_auth_roles: BTreeMap<String, Vec<String>>,
+}
+
+/// A namespace policy with its own endpoint below the namespace, so it can be read, set and
+/// removed on its own instead of through the whole policies document. Topic level policies
+/// use the same endpoints below the topic.
+pub trait NamespacePolicy: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// The endpoint below the namespace, e.g. `retention`.
+    const PATH: &'static str;
+
+    /// For messages, e.g. `retention`.
+    const NAME: &'static str;
+
+    /// Whether the broker takes it with PUT rather than POST.
+    const PUT: bool = false;
+
+    /// What `remove` sets for policies the broker cannot delete, e.g. `false` for flags.
+    fn unset() -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicies {
+    /// `-1` for infinite retention.
+    #[serde(rename = "retentionTimeInMinutes")]
+    pub retention_time_in_minutes: i32,
+
+    /// `-1` for infinite retention.
+    #[serde(rename = "retentionSizeInMB")]
+    pub retention_size_in_mb: i64,
+}
+
+impl NamespacePolicy for RetentionPolicies {
+    const PATH: &'static str = "retention";
+    const NAME: &'static str = "retention";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct MessageTtl(pub i32);
+
+impl NamespacePolicy for MessageTtl {
+    const PATH: &'static str = "messageTTL";
+    const NAME: &'static str = "message TTL";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogQuota {
+    /// Bytes, `-1` for no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_size: Option<i64>,
+
+    /// Seconds, `-1` for no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_time: Option<i32>,
+
+    pub policy: BacklogQuotaPolicy,
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum BacklogQuotaType {
+    /// Limit the size of the backlog
+    DestinationStorage,
+    /// Limit the age of the oldest unacknowledged message
+    MessageAge,
+}
+
+impl BacklogQuotaType {
+    pub fn name(self) -> &'static str {
+        match self {
+            BacklogQuotaType::DestinationStorage => "destination_storage",
+            BacklogQuotaType::MessageAge => "message_age",
+        }
+    }
+}
+
+/// What the broker does once a backlog quota is exceeded.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum BacklogQuotaPolicy {
+    /// Hold publish requests until the backlog shrinks
+    ProducerRequestHold,
+    /// Fail publish requests
+    ProducerException,
+    /// Drop the oldest messages of the slowest subscriptions
+    ConsumerBacklogEviction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchRate {
+    /// Messages per period, `-1` for no limit.
+    pub dispatch_throttling_rate_in_msg: i32,
+
+    /// Bytes per period, `-1` for no limit.
+    pub dispatch_throttling_rate_in_byte: i64,
+
+    #[serde(default)]
+    pub relative_to_publish_rate: bool,
+
+    pub rate_period_in_second: i32,
+}
+
+impl NamespacePolicy for DispatchRate {
+    const PATH: &'static str = "dispatchRate";
+    const NAME: &'static str = "dispatch rate";
+}
+
+/// The dispatch rate of each subscription, rather than of each topic.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SubscriptionDispatchRate(pub DispatchRate);
+
+impl NamespacePolicy for SubscriptionDispatchRate {
+    const PATH: &'static str = "subscriptionDispatchRate";
+    const NAME: &'static str = "subscription dispatch rate";
+}
+
+impl From<DispatchRate> for SubscriptionDispatchRate {
+    fn from(rate: DispatchRate) -> Self {
+        SubscriptionDispatchRate(rate)
+    }
+}
+
+/// The dispatch rate of geo-replication to other clusters.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ReplicatorDispatchRate(pub DispatchRate);
+
+impl NamespacePolicy for ReplicatorDispatchRate {
+    const PATH: &'static str = "replicatorDispatchRate";
+    const NAME: &'static str = "replicator dispatch rate";
+}
+
+impl From<DispatchRate> for ReplicatorDispatchRate {
+    fn from(rate: DispatchRate) -> Self {
+        ReplicatorDispatchRate(rate)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeRate {
+    /// Subscribes per consumer and period, `-1` for no limit.
+    pub subscribe_throttling_rate_per_consumer: i32,
+
+    pub rate_period_in_second: i32,
+}
+
+impl NamespacePolicy for SubscribeRate {
+    const PATH: &'static str = "subscribeRate";
+    const NAME: &'static str = "subscribe rate";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishRate {
+    /// Messages per second, `-1` for no limit.
+    pub publish_throttling_rate_in_msg: i32,
+
+    /// Bytes per second, `-1` for no limit.
+    pub publish_throttling_rate_in_byte: i64,
+}
+
+impl NamespacePolicy for PublishRate {
+    const PATH: &'static str = "publishRate";
+    const NAME: &'static str = "publish rate";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Deduplication(pub bool);
+
+impl NamespacePolicy for Deduplication {
+    const PATH: &'static str = "deduplication";
+    const NAME: &'static str = "deduplication";
+}
+
+impl From<bool> for Deduplication {
+    fn from(enabled: bool) -> Self {
+        Deduplication(enabled)
+    }
+}
+
+/// Minutes after which inactive subscriptions are deleted, `0` to never delete them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SubscriptionExpirationTime(pub i32);
+
+impl NamespacePolicy for SubscriptionExpirationTime {
+    const PATH: &'static str = "subscriptionExpirationTime";
+    const NAME: &'static str = "subscription expiration time";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct MaxProducersPerTopic(pub i32);
+
+impl NamespacePolicy for MaxProducersPerTopic {
+    const PATH: &'static str = "maxProducersPerTopic";
+    const NAME: &'static str = "max producers per topic";
+}
+
+impl From<i32> for MaxProducersPerTopic {
+    fn from(max: i32) -> Self {
+        MaxProducersPerTopic(max)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct MaxConsumersPerTopic(pub i32);
+
+impl NamespacePolicy for MaxConsumersPerTopic {
+    const PATH: &'static str = "maxConsumersPerTopic";
+    const NAME: &'static str = "max consumers per topic";
+}
+
+impl From<i32> for MaxConsumersPerTopic {
+    fn from(max: i32) -> Self {
+        MaxConsumersPerTopic(max)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct MaxSubscriptionsPerTopic(pub i32);
+
+impl NamespacePolicy for MaxSubscriptionsPerTopic {
+    const PATH: &'static str = "maxSubscriptionsPerTopic";
+    const NAME: &'static str = "max subscriptions per topic";
+}
+
+impl From<i32> for MaxSubscriptionsPerTopic {
+    fn from(max: i32) -> Self {
+        MaxSubscriptionsPerTopic(max)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTopicCreationOverride {
+    pub allow_auto_topic_creation: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic_type: Option<TopicType>,
+
+    /// Only for partitioned topics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_num_partitions: Option<i32>,
+}
+
+impl NamespacePolicy for AutoTopicCreationOverride {
+    const PATH: &'static str = "autoTopicCreation";
+    const NAME: &'static str = "auto topic creation";
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TopicType {
+    Partitioned,
+    NonPartitioned,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSubscriptionCreationOverride {
+    pub allow_auto_subscription_creation: bool,
+}
+
+impl NamespacePolicy for AutoSubscriptionCreationOverride {
+    const PATH: &'static str = "autoSubscriptionCreation";
+    const NAME: &'static str = "auto subscription creation";
+}
+
+impl From<bool> for AutoSubscriptionCreationOverride {
+    fn from(allow_auto_subscription_creation: bool) -> Self {
+        AutoSubscriptionCreationOverride { allow_auto_subscription_creation }
+    }
+}
+
+/// Which schema changes producers and consumers may make, compared to the existing schemas.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[value(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SchemaCompatibilityStrategy {
+    /// Use the broker's default
+    Undefined,
+    AlwaysIncompatible,
+    AlwaysCompatible,
+    Backward,
+    Forward,
+    Full,
+    BackwardTransitive,
+    ForwardTransitive,
+    FullTransitive,
+}
+
+impl NamespacePolicy for SchemaCompatibilityStrategy {
+    const PATH: &'static str = "schemaCompatibilityStrategy";
+    const NAME: &'static str = "schema compatibility strategy";
+    const PUT: bool = true;
+
+    fn unset() -> Option<Self> {
+        Some(SchemaCompatibilityStrategy::Undefined)
+    }
+}
+
+/// Whether producers without a schema are rejected on topics that have one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SchemaValidationEnforced(pub bool);
+
+impl NamespacePolicy for SchemaValidationEnforced {
+    const PATH: &'static str = "schemaValidationEnforced";
+    const NAME: &'static str = "schema validation enforcement";
+
+    fn unset() -> Option<Self> {
+        Some(SchemaValidationEnforced(false))
+    }
+}
+
+impl From<bool> for SchemaValidationEnforced {
+    fn from(enforced: bool) -> Self {
+        SchemaValidationEnforced(enforced)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Whether unencrypted messages are rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct EncryptionRequired(pub bool);
+
+impl NamespacePolicy for EncryptionRequired {
+    const PATH: &'static str = "encryptionRequired";
+    const NAME: &'static str = "encryption requirement";
+
+    fn unset() -> Option<Self> {
+        Some(EncryptionRequired(false))
+    }
+}
+
+impl From<bool> for EncryptionRequired {
+    fn from(required: bool) -> Self {
+        EncryptionRequired(required)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DelayedDeliveryPolicies {
+    pub active: bool,
+
+    /// Milliseconds between checks for messages due.
+    pub tick_time: i64,
+}
+
+impl NamespacePolicy for DelayedDeliveryPolicies {
+    const PATH: &'static str = "delayedDelivery";
+    const NAME: &'static str = "delayed delivery";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InactiveTopicPolicies {
+    pub inactive_topic_delete_mode: InactiveTopicDeleteMode,
+
+    pub max_inactive_duration_seconds: i32,
+
+    pub delete_while_inactive: bool,
+}
+
+impl NamespacePolicy for InactiveTopicPolicies {
+    const PATH: &'static str = "inactiveTopicPolicies";
+    const NAME: &'static str = "inactive topic policies";
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum InactiveTopicDeleteMode {
+    /// Once the topic has no subscriptions left
+    DeleteWhenNoSubscriptions,
+    /// Once all subscriptions caught up
+    DeleteWhenSubscriptionsCaughtUp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BundlesData {
     #[serde(rename = "numBundles")]
     pub num_bundles: u64,
 
-    #[serde(rename = "boundaries")]
+    /// Left empty on create for the broker to divide the hash range evenly.
+    #[serde(rename = "boundaries", default, skip_serializing_if = "Vec::is_empty")]
     pub boundaries: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl BundlesData {
+    /// The bundles as the broker names them, e.g. `0x00000000_0x40000000`.
+    pub fn ranges(&self) -> Vec<String> {
+        self.boundaries.windows(2)
+            .map(|w| format!("{}_{}", w[0], w[1]))
+            .collect()
+    }
+}
+
+/// How the broker picks the boundary when splitting a bundle.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitAlgorithm {
+    /// In the middle of the hash range
+    #[serde(rename = "range_equally_divide")]
+    #[value(name = "range_equally_divide")]
+    Range,
+    /// So both halves get about as many topics
+    #[serde(rename = "topic_count_equally_divide")]
+    #[value(name = "topic_count_equally_divide")]
+    TopicCount,
+    /// So both halves get about as much traffic
+    #[serde(rename = "flow_or_qps_equally_divide")]
+    #[value(name = "flow_or_qps_equally_divide")]
+    FlowOrQps,
+}
+
+impl SplitAlgorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            SplitAlgorithm::Range => "range_equally_divide",
+            SplitAlgorithm::TopicCount => "topic_count_equally_divide",
+            SplitAlgorithm::FlowOrQps => "flow_or_qps_equally_divide",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PersistencePolicies {
     #[serde(rename = "bookkeeperEnsemble")]
     pub bookkeeper_ensemble: i32,
@@ -43,34 +582,44 @@ pub struct PersistencePolicies {
     pub managed_ledger_max_mark_delete_rate: f64,
 }
 
+impl NamespacePolicy for PersistencePolicies {
+    const PATH: &'static str = "persistence";
+    const NAME: &'static str = "persistence";
+}
+
 impl<'a> PulsarAdminNamespaces<'a> {
-    pub async fn list(&self, namespace: &str) -> Result<Vec<String>, Error> {
-        Ok(self.admin.get(format!("/admin/v2/namespaces/{}", namespace).as_str())?
+    pub async fn list(&self, tenant: &str) -> Result<Vec<String>, Error> {
+        Ok(self.admin.get(format!("/admin/v2/namespaces/{}", tenant).as_str())?
             .send().await?
             .json::<Vec<String>>().await?)
     }
 
-    pub async fn create(&self, namespace: &str, policies: &NamespacePolicies) -> Result<(), Error> {
-        let res = self.admin.put(format!("/admin/v2/namespaces/{}", namespace).as_str())?
+    pub async fn create(&self, namespace: &NamespaceName, policies: &NamespacePolicies) -> Result<(), Error> {
+        self.admin.put(namespace.admin_path().as_str())?
             .json(policies)
             .send().await?;
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            Err(res.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn policies(&self, namespace: &str) -> Result<NamespacePolicies, Error> {
-        let body = self.admin.get(format!("/admin/v2/namespaces/{}", namespace).as_str())?
+    /// Deletes a namespace, which must be empty unless `force` is set and the broker allows
+    /// forced deletes.
+    pub async fn delete(&self, namespace: &NamespaceName, force: bool) -> Result<(), Error> {
+        self.admin.delete(namespace.admin_path().as_str())?
+            .query(&[("force", force.to_string())])
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn policies(&self, namespace: &NamespaceName) -> Result<NamespacePolicies, Error> {
+        let body = self.admin.get(namespace.admin_path().as_str())?
             .send().await?
             .text().await?;
         debug!("{}", body.as_str());
         Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub async fn permissions(&self, namespace: &str) -> Result<HashMap<String, Vec<String>>, Error> {
-        let res = self.admin.get(format!("/admin/v2/namespaces/{}/permissions", namespace).as_str())?
+    pub async fn permissions(&self, namespace: &NamespaceName) -> Result<BTreeMap<String, Vec<String>>, Error> {
+        let res = self.admin.get(format!("{}/permissions", namespace.admin_path()).as_str())?
             .send().await?;
         let body = res.text().await?;
         debug!("{}", body.as_str());
@@ -86,45 +635,219 @@ impl<'a> PulsarAdminNamespaces<'a> {
             .collect())
     }
 
-    pub async fn grant_permission(&self, namespace: &str, role: &str, permissions: &[String]) -> Result<(), Error> {
-        let res = self.admin.post(format!("/admin/v2/namespaces/{}/permissions/{}", namespace, role).as_str())?
+    pub async fn grant_permission(&self, namespace: &NamespaceName, role: &str, permissions: &[String]) -> Result<(), Error> {
+        self.admin.post(format!("{}/permissions/{}", namespace.admin_path(), role).as_str())?
             .json(permissions)
             .send().await?;
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            Err(res.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn revoke_permission(&self, namespace: &str, role: &str) -> Result<(), Error> {
-        let res = self.admin.delete(format!("/admin/v2/namespaces/{}/permissions/{}", namespace, role).as_str())?
+    pub async fn revoke_permission(&self, namespace: &NamespaceName, role: &str) -> Result<(), Error> {
+        self.admin.delete(format!("{}/permissions/{}", namespace.admin_path(), role).as_str())?
             .send().await?;
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            Err(res.text().await?.into())
-        }
+        Ok(())
+    }
+
+    pub async fn set_replication_clusters(&self, namespace: &NamespaceName, clusters: &[String]) -> Result<(), Error> {
+        self.admin.post(format!("{}/replication", namespace.admin_path()).as_str())?
+            .json(clusters)
+            .send().await?;
+        Ok(())
     }
 
-    pub async fn update_persistence(&self, namespace: &str, persistence: &PersistencePolicies) -> Result<(), Error> {
-        let res = self.admin.post(format!("/admin/v2/namespaces/{}/persistence", namespace).as_str())?
+    pub async fn update_persistence(&self, namespace: &NamespaceName, persistence: &PersistencePolicies) -> Result<(), Error> {
+        self.admin.post(format!("{}/persistence", namespace.admin_path()).as_str())?
             .json(persistence)
             .send().await?;
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            Err(res.text().await?.into())
+        Ok(())
+    }
+
+    pub async fn remove_persistence(&self, namespace: &NamespaceName) -> Result<(), Error> {
+        self.admin.delete(format!("{}/persistence", namespace.admin_path()).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    /// A policy set on the namespace, or `None` when the broker default applies.
+    pub async fn policy<P: NamespacePolicy>(&self, namespace: &NamespaceName) -> Result<Option<P>, Error> {
+        let body = self.admin.get(format!("{}/{}", namespace.admin_path(), P::PATH).as_str())?
+            .send().await?
+            .text().await?;
+        debug!("{}", body.as_str());
+        if body.trim().is_empty() {
+            return Ok(None);
         }
+        Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub async fn remove_persistence(&self, namespace: &str) -> Result<(), Error> {
-        let res = self.admin.delete(format!("/admin/v2/namespaces/{}/persistence", namespace).as_str())?
+    pub async fn set_policy<P: NamespacePolicy>(&self, namespace: &NamespaceName, policy: &P) -> Result<(), Error> {
+        let path = format!("{}/{}", namespace.admin_path(), P::PATH);
+        let request = if P::PUT { self.admin.put(path.as_str())? } else { self.admin.post(path.as_str())? };
+        request.json(policy)
             .send().await?;
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            Err(res.text().await?.into())
+        Ok(())
+    }
+
+    /// Removes a policy from the namespace, so the broker default applies again.
+    pub async fn remove_policy<P: NamespacePolicy>(&self, namespace: &NamespaceName) -> Result<(), Error> {
+        match P::unset() {
+            Some(policy) => self.set_policy(namespace, &policy).await,
+            None => {
+                self.admin.delete(format!("{}/{}", namespace.admin_path(), P::PATH).as_str())?
+                    .send().await?;
+                Ok(())
+            }
         }
     }
+
+    /// The backlog quotas of the namespace, keyed by `BacklogQuotaType`.
+    pub async fn backlog_quotas(&self, namespace: &NamespaceName) -> Result<BTreeMap<String, BacklogQuota>, Error> {
+        Ok(self.admin.get(format!("{}/backlogQuotaMap", namespace.admin_path()).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    pub async fn set_backlog_quota(&self, namespace: &NamespaceName, quota_type: BacklogQuotaType,
+                                   quota: &BacklogQuota) -> Result<(), Error> {
+        self.admin.post(format!("{}/backlogQuota", namespace.admin_path()).as_str())?
+            .query(&[("backlogQuotaType", quota_type.name())])
+            .json(quota)
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn remove_backlog_quota(&self, namespace: &NamespaceName, quota_type: BacklogQuotaType) -> Result<(), Error> {
+        self.admin.delete(format!("{}/backlogQuota", namespace.admin_path()).as_str())?
+            .query(&[("backlogQuotaType", quota_type.name())])
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn bundles(&self, namespace: &NamespaceName) -> Result<BundlesData, Error> {
+        Ok(self.admin.get(format!("{}/bundles", namespace.admin_path()).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    /// Unloads all bundles of a namespace, so other brokers can pick them up.
+    pub async fn unload(&self, namespace: &NamespaceName) -> Result<(), Error> {
+        self.admin.put(format!("{}/unload", namespace.admin_path()).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn unload_bundle(&self, namespace: &NamespaceName, bundle: &str) -> Result<(), Error> {
+        self.admin.put(format!("{}/{}/unload", namespace.admin_path(), bundle).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    pub async fn split_bundle(&self, namespace: &NamespaceName, bundle: &str, algorithm: SplitAlgorithm,
+                              unload: bool) -> Result<(), Error> {
+        self.admin.put(format!("{}/{}/split", namespace.admin_path(), bundle).as_str())?
+            .query(&[("unload", unload.to_string()), ("splitAlgorithmName", algorithm.name().to_string())])
+            .send().await?;
+        Ok(())
+    }
+
+    /// Skips all messages of the subscriptions in a namespace, or in one of its bundles,
+    /// optionally only of one subscription.
+    pub async fn clear_backlog(&self, namespace: &NamespaceName, bundle: Option<&str>,
+                               subscription: Option<&str>) -> Result<(), Error> {
+        let mut path = namespace.admin_path();
+        if let Some(bundle) = bundle {
+            path = format!("{}/{}", path, bundle);
+        }
+        path = format!("{}/clearBacklog", path);
+        if let Some(subscription) = subscription {
+            path = format!("{}/{}", path, urlencoding::encode(subscription));
+        }
+        self.admin.post(path.as_str())?
+            .send().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::admin::PulsarAdmin;
+    use crate::admin::namespaces::{EncryptionRequired, MessageTtl, NamespacePolicies, RetentionPolicies,
+                                   SchemaCompatibilityStrategy, SplitAlgorithm};
+    use crate::admin::naming::NamespaceName;
+    use crate::testing::{MockResponse, MockServer};
+
+    #[test]
+    fn policies_document() {
+        let policies: NamespacePolicies = serde_json::from_str(r#"{
+            "auth_policies": {"namespace_auth": {"app": ["produce"]}, "destination_auth": {}, "subscription_auth_roles": {}},
+            "replication_clusters": ["standalone"],
+            "bundles": {"boundaries": ["0x00000000", "0xffffffff"], "numBundles": 1},
+            "backlog_quota_map": {"destination_storage": {"limit": 1024, "limitSize": 1024, "limitTime": -1, "policy": "producer_exception"}},
+            "topicDispatchRate": {"standalone": {"dispatchThrottlingRateInMsg": 100, "dispatchThrottlingRateInByte": -1,
+                                                 "relativeToPublishRate": false, "ratePeriodInSecond": 1}},
+            "deduplicationEnabled": true,
+            "message_ttl_in_seconds": 3600,
+            "retention_policies": {"retentionTimeInMinutes": 60, "retentionSizeInMB": -1},
+            "schema_compatibility_strategy": "FULL",
+            "latency_stats_sample_rate": {}
+        }"#).unwrap();
+        assert_eq!(policies.auth_policies.unwrap().namespace_auth["app"], vec!["produce"]);
+        assert_eq!(policies.backlog_quota_map.unwrap()["destination_storage"].limit_size, Some(1024));
+        assert_eq!(policies.topic_dispatch_rate.unwrap()["standalone"].dispatch_throttling_rate_in_msg, 100);
+        assert_eq!(policies.message_ttl_in_seconds, Some(3600));
+        assert_eq!(policies.retention_policies.unwrap().retention_size_in_mb, -1);
+        assert_eq!(policies.schema_compatibility_strategy, Some(SchemaCompatibilityStrategy::Full));
+        assert!(policies.extra.contains_key("latency_stats_sample_rate"));
+    }
+
+    #[tokio::test]
+    async fn policies() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/namespaces/acme/orders/retention") =>
+                MockResponse::new(200, r#"{"retentionTimeInMinutes":60,"retentionSizeInMB":1024}"#),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let namespaces = admin.namespaces();
+        let namespace = NamespaceName::parse("acme/orders").unwrap();
+
+        let retention = namespaces.policy::<RetentionPolicies>(&namespace).await.unwrap();
+        assert_eq!(retention.unwrap().retention_time_in_minutes, 60);
+        assert_eq!(namespaces.policy::<MessageTtl>(&namespace).await.unwrap(), None);
+        namespaces.set_policy(&namespace, &MessageTtl(3600)).await.unwrap();
+        namespaces.remove_policy::<MessageTtl>(&namespace).await.unwrap();
+        namespaces.set_policy(&namespace, &SchemaCompatibilityStrategy::Backward).await.unwrap();
+        namespaces.remove_policy::<EncryptionRequired>(&namespace).await.unwrap();
+        let requests: Vec<String> = server.requests().into_iter().skip(2)
+            .map(|r| format!("{} {} {}", r.method, r.path, r.body))
+            .collect();
+        assert_eq!(requests, vec![
+            "POST /admin/v2/namespaces/acme/orders/messageTTL 3600",
+            "DELETE /admin/v2/namespaces/acme/orders/messageTTL ",
+            "PUT /admin/v2/namespaces/acme/orders/schemaCompatibilityStrategy \"BACKWARD\"",
+            "POST /admin/v2/namespaces/acme/orders/encryptionRequired false",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn bundles() {
+        let server = MockServer::start(|req, _| match req.method.as_str() {
+            "GET" => MockResponse::new(200, r#"{"boundaries":["0x00000000","0x80000000","0xffffffff"],"numBundles":2}"#),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let namespace = NamespaceName::parse("acme/orders").unwrap();
+
+        let bundles = admin.namespaces().bundles(&namespace).await.unwrap();
+        assert_eq!(bundles.ranges(), vec!["0x00000000_0x80000000", "0x80000000_0xffffffff"]);
+        admin.namespaces()
+            .split_bundle(&namespace, "0x00000000_0x80000000", SplitAlgorithm::TopicCount, true)
+            .await.unwrap();
+        admin.namespaces().clear_backlog(&namespace, Some("0x80000000_0xffffffff"), Some("sub/1")).await.unwrap();
+        let paths: Vec<String> = server.requests().into_iter().skip(1).map(|r| r.path).collect();
+        assert_eq!(paths, vec![
+            "/admin/v2/namespaces/acme/orders/0x00000000_0x80000000/split?unload=true&splitAlgorithmName=topic_count_equally_divide",
+            "/admin/v2/namespaces/acme/orders/0x80000000_0xffffffff/clearBacklog/sub%2F1",
+        ]);
+    }
 }
@@ -0,0 +1,362 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::admin::error::Error;
+
+const PARTITIONED_TOPIC_SUFFIX: &str = "-partition-";
+
+const DEFAULT_NAMESPACE: &str = "public/default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TopicDomain {
+    Persistent,
+    NonPersistent,
+}
+
+impl Display for TopicDomain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopicDomain::Persistent => write!(f, "persistent"),
+            TopicDomain::NonPersistent => write!(f, "non-persistent"),
+        }
+    }
+}
+
+impl TopicDomain {
+    pub fn parse(name: &str) -> Result<TopicDomain, Error> {
+        match name.to_uppercase().replace('-', "_").as_str() {
+            "PERSISTENT" => Ok(TopicDomain::Persistent),
+            "NON_PERSISTENT" => Ok(TopicDomain::NonPersistent),
+            &_ => Err(format!("invalid domain name [{}]", name).into())
+        }
+    }
+}
+
+/// A namespace name, either `tenant/namespace` or the legacy `tenant/cluster/namespace` form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamespaceName {
+    tenant: String,
+    cluster: Option<String>,
+    local_name: String,
+}
+
+impl NamespaceName {
+    pub fn new(tenant: &str, local_name: &str) -> Result<NamespaceName, Error> {
+        let namespace = NamespaceName {
+            tenant: tenant.to_string(),
+            cluster: None,
+            local_name: local_name.to_string(),
+        };
+        namespace.validate()?;
+        Ok(namespace)
+    }
+
+    pub fn parse(name: &str) -> Result<NamespaceName, Error> {
+        let parts: Vec<&str> = name.split('/').collect();
+        let namespace = match parts.len() {
+            2 => NamespaceName {
+                tenant: parts[0].to_string(),
+                cluster: None,
+                local_name: parts[1].to_string(),
+            },
+            3 => NamespaceName {
+                tenant: parts[0].to_string(),
+                cluster: Some(parts[1].to_string()),
+                local_name: parts[2].to_string(),
+            },
+            _ => return Err(format!("invalid namespace [{}], it should be in the format of <tenant>/<namespace>", name).into()),
+        };
+        namespace.validate()?;
+        Ok(namespace)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        check_name("tenant", self.tenant.as_str(), self)?;
+        if let Some(cluster) = &self.cluster {
+            check_name("cluster", cluster.as_str(), self)?;
+        }
+        check_name("namespace", self.local_name.as_str(), self)
+    }
+
+    pub fn tenant(&self) -> &str {
+        self.tenant.as_str()
+    }
+
+    pub fn is_v2(&self) -> bool {
+        self.cluster.is_none()
+    }
+
+    /// The namespace as it appears in admin REST paths.
+    pub fn rest_path(&self) -> String {
+        self.to_string()
+    }
+
+    /// The prefix of admin REST paths for resources in this namespace, which differs between
+    /// the v2 API and the legacy v1 API.
+    pub fn admin_path(&self) -> String {
+        if self.is_v2() {
+            format!("/admin/v2/namespaces/{}", self.rest_path())
+        } else {
+            format!("/admin/namespaces/{}", self.rest_path())
+        }
+    }
+}
+
+impl Display for NamespaceName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.cluster {
+            Some(cluster) => write!(f, "{}/{}/{}", self.tenant, cluster, self.local_name),
+            None => write!(f, "{}/{}", self.tenant, self.local_name),
+        }
+    }
+}
+
+impl FromStr for NamespaceName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NamespaceName::parse(s)
+    }
+}
+
+/// Names may only contain alphanumerics, `_`, `-`, `=`, `:` and `.`, as enforced by the broker.
+fn check_name(kind: &str, name: &str, namespace: &NamespaceName) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(format!("invalid namespace [{}], {} is empty", namespace, kind).into());
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_alphanumeric() || "_-=:.".contains(*c))) {
+        return Err(format!("invalid namespace [{}], {} contains illegal character [{}]", namespace, kind, c).into());
+    }
+    Ok(())
+}
+
+/// A fully qualified topic name: `{domain}://{tenant}/{namespace}/{local name}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TopicName {
+    domain: TopicDomain,
+    namespace: NamespaceName,
+    local_name: String,
+    partition_index: Option<u32>,
+}
+
+impl TopicName {
+    /// Parses a topic name, expanding short names into the `public/default` namespace.
+    pub fn parse(name: &str) -> Result<TopicName, Error> {
+        Self::parse_in(name, &NamespaceName::parse(DEFAULT_NAMESPACE)?)
+    }
+
+    /// Parses a topic name, expanding a bare `topic` into `persistent://{namespace}/topic` and
+    /// `tenant/namespace/topic` into `persistent://tenant/namespace/topic`.
+    pub fn parse_in(name: &str, default_namespace: &NamespaceName) -> Result<TopicName, Error> {
+        let full_name = if name.contains("://") {
+            name.to_string()
+        } else {
+            let parts: Vec<&str> = name.split('/').collect();
+            match parts.len() {
+                1 => format!("{}://{}/{}", TopicDomain::Persistent, default_namespace, name),
+                3 => format!("{}://{}", TopicDomain::Persistent, name),
+                _ => return Err(format!("invalid short topic name [{}], it should be in the format of \
+                    <tenant>/<namespace>/<topic> or <topic>", name).into()),
+            }
+        };
+
+        let (domain, rest) = full_name.split_once("://").unwrap();
+        let domain = TopicDomain::parse(domain)
+            .map_err(|_| Error::from(format!("invalid topic domain [{}] in [{}]", domain, name)))?;
+
+        // like the java client, four parts are read as the v1 `tenant/cluster/namespace/topic`
+        // form, so v2 local names cannot contain '/'
+        let parts: Vec<&str> = rest.splitn(4, '/').collect();
+        let (namespace, local_name) = match parts.len() {
+            3 => (NamespaceName::parse(format!("{}/{}", parts[0], parts[1]).as_str()), parts[2]),
+            4 => (NamespaceName::parse(format!("{}/{}/{}", parts[0], parts[1], parts[2]).as_str()), parts[3]),
+            _ => return Err(format!("invalid topic name [{}]", name).into()),
+        };
+        let namespace = namespace
+            .map_err(|e| Error::from(format!("invalid topic name [{}]: {}", name, e)))?;
+        if local_name.is_empty() {
+            return Err(format!("invalid topic name [{}], the local name is empty", name).into());
+        }
+
+        Ok(TopicName {
+            domain,
+            namespace,
+            local_name: local_name.to_string(),
+            partition_index: parse_partition_index(local_name),
+        })
+    }
+
+    pub fn namespace(&self) -> &NamespaceName {
+        &self.namespace
+    }
+
+    pub fn local_name(&self) -> &str {
+        self.local_name.as_str()
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.domain == TopicDomain::Persistent
+    }
+
+    pub fn is_partitioned(&self) -> bool {
+        self.partition_index.is_some()
+    }
+
+    pub fn partition_index(&self) -> Option<u32> {
+        self.partition_index
+    }
+
+    /// Returns the partitioned topic this partition belongs to, or itself if it is not a partition.
+    pub fn partitioned_topic_name(&self) -> TopicName {
+        match self.partition_index {
+            Some(_) => {
+                let idx = self.local_name.rfind(PARTITIONED_TOPIC_SUFFIX).unwrap();
+                TopicName {
+                    domain: self.domain,
+                    namespace: self.namespace.clone(),
+                    local_name: self.local_name[..idx].to_string(),
+                    partition_index: None,
+                }
+            }
+            None => self.clone(),
+        }
+    }
+
+    /// The topic as it appears in REST paths, e.g. `persistent/tenant/namespace/topic`.
+    pub fn rest_path(&self) -> String {
+        format!("{}/{}/{}", self.domain, self.namespace.rest_path(), urlencoding::encode(self.local_name.as_str()))
+    }
+
+    /// The prefix of admin REST paths for this topic, which differs between the v2 API and the
+    /// legacy v1 API.
+    pub fn admin_path(&self) -> String {
+        if self.namespace.is_v2() {
+            format!("/admin/v2/{}", self.rest_path())
+        } else {
+            format!("/admin/{}", self.rest_path())
+        }
+    }
+
+    pub fn lookup_path(&self) -> String {
+        if self.namespace.is_v2() {
+            format!("/lookup/v2/topic/{}", self.rest_path())
+        } else {
+            format!("/lookup/v2/destination/{}", self.rest_path())
+        }
+    }
+}
+
+impl Display for TopicName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}/{}", self.domain, self.namespace, self.local_name)
+    }
+}
+
+impl FromStr for TopicName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TopicName::parse(s)
+    }
+}
+
+/// Parses the `N` in `topic-partition-N`; anything that is not a canonical non-negative
+/// integer means the topic is not a partition.
+fn parse_partition_index(local_name: &str) -> Option<u32> {
+    let idx = local_name.rfind(PARTITIONED_TOPIC_SUFFIX)?;
+    let suffix = &local_name[idx + PARTITIONED_TOPIC_SUFFIX.len()..];
+    let index = suffix.parse::<u32>().ok()?;
+    if index.to_string() == suffix {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::naming::{NamespaceName, TopicDomain, TopicName};
+
+    #[test]
+    fn short_names() {
+        let topic = TopicName::parse("my-topic").unwrap();
+        assert_eq!(topic.to_string(), "persistent://public/default/my-topic");
+        assert_eq!(topic.domain, TopicDomain::Persistent);
+        assert_eq!(topic.namespace().tenant(), "public");
+        assert_eq!(topic.namespace().local_name, "default");
+
+        let topic = TopicName::parse("acme/orders/my-topic").unwrap();
+        assert_eq!(topic.to_string(), "persistent://acme/orders/my-topic");
+
+        let ns = NamespaceName::parse("acme/billing").unwrap();
+        let topic = TopicName::parse_in("my-topic", &ns).unwrap();
+        assert_eq!(topic.to_string(), "persistent://acme/billing/my-topic");
+
+        assert!(TopicName::parse("orders/my-topic").is_err());
+        assert!(TopicName::parse("a/b/c/d").is_err());
+    }
+
+    #[test]
+    fn full_names() {
+        let topic = TopicName::parse("non-persistent://acme/orders/my-topic").unwrap();
+        assert_eq!(topic.domain, TopicDomain::NonPersistent);
+        assert!(!topic.is_persistent());
+        assert_eq!(topic.rest_path(), "non-persistent/acme/orders/my-topic");
+        assert_eq!(topic.admin_path(), "/admin/v2/non-persistent/acme/orders/my-topic");
+        assert_eq!(topic.lookup_path(), "/lookup/v2/topic/non-persistent/acme/orders/my-topic");
+
+        assert!(TopicName::parse("other://acme/orders/my-topic").is_err());
+        assert!(TopicName::parse("persistent://acme/orders").is_err());
+        assert!(TopicName::parse("persistent://acme/orders/").is_err());
+        assert!(TopicName::parse("persistent:///orders/my-topic").is_err());
+        assert!(TopicName::parse("persistent://acme/ord ers/my-topic").is_err());
+    }
+
+    #[test]
+    fn v1_names() {
+        let topic = TopicName::parse("persistent://acme/us-west/orders/my-topic").unwrap();
+        assert!(!topic.namespace().is_v2());
+        assert_eq!(topic.namespace().cluster.as_deref(), Some("us-west"));
+        assert_eq!(topic.local_name(), "my-topic");
+        assert_eq!(topic.admin_path(), "/admin/persistent/acme/us-west/orders/my-topic");
+
+        // the java client reads anything after the fourth '/' as part of the local name
+        let topic = TopicName::parse("persistent://acme/us-west/orders/a/b").unwrap();
+        assert_eq!(topic.local_name(), "a/b");
+        assert_eq!(topic.rest_path(), "persistent/acme/us-west/orders/a%2Fb");
+    }
+
+    #[test]
+    fn partitions() {
+        let topic = TopicName::parse("persistent://acme/orders/my-topic").unwrap();
+        assert!(!topic.is_partitioned());
+        let partition = TopicName::parse("persistent://acme/orders/my-topic-partition-3").unwrap();
+        assert!(partition.is_partitioned());
+        assert_eq!(partition.partition_index(), Some(3));
+        assert_eq!(partition.partitioned_topic_name(), topic);
+
+        let nested = TopicName::parse("a-partition-1-partition-2").unwrap();
+        assert_eq!(nested.partition_index(), Some(2));
+        assert_eq!(nested.partitioned_topic_name().local_name(), "a-partition-1");
+
+        assert_eq!(TopicName::parse("t-partition-").unwrap().partition_index(), None);
+        assert_eq!(TopicName::parse("t-partition-01").unwrap().partition_index(), None);
+        assert_eq!(TopicName::parse("t-partition--1").unwrap().partition_index(), None);
+        assert_eq!(TopicName::parse("t-partition-x").unwrap().partition_index(), None);
+    }
+
+    #[test]
+    fn namespaces() {
+        let ns = NamespaceName::parse("acme/orders").unwrap();
+        assert!(ns.is_v2());
+        assert_eq!(ns.admin_path(), "/admin/v2/namespaces/acme/orders");
+        let ns = NamespaceName::parse("acme/us-west/orders").unwrap();
+        assert_eq!(ns.admin_path(), "/admin/namespaces/acme/us-west/orders");
+
+        assert!(NamespaceName::parse("acme").is_err());
+        assert!(NamespaceName::parse("acme/").is_err());
+        assert!(NamespaceName::parse("a/b/c/d").is_err());
+        assert!(NamespaceName::parse("acme/ord#ers").is_err());
+        assert!(NamespaceName::new("acme", "orders=1:a.b").is_ok());
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{Method, StatusCode};
+
+/// How failed admin requests are retried. Requests are only retried when repeating them is
+/// harmless: connection failures, which never reached the broker, `429` and `503`, which
+/// the broker answers without acting on the request, and other `5xx` and timeouts for reads.
+/// Pulsar creates resources with `PUT`, so a retried create that had succeeded would fail
+/// with `409`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff before the retry following the given attempt (starting at 1),
+    /// with jitter between half and the full delay.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.initial_backoff.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        let delay = exp.min(self.max_backoff);
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+
+    pub fn should_retry_status(&self, method: &Method, status: StatusCode) -> bool {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+            StatusCode::INTERNAL_SERVER_ERROR | StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT =>
+                is_read(method),
+            _ => false,
+        }
+    }
+
+    pub fn should_retry_error(&self, method: &Method, error: &reqwest::Error) -> bool {
+        error.is_connect() || (error.is_timeout() && is_read(method))
+    }
+}
+
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::{Method, StatusCode};
+
+    use crate::admin::retry::RetryPolicy;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        };
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let second = policy.backoff(2);
+            assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.should_retry_status(&Method::GET, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!policy.should_retry_status(&Method::POST, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!policy.should_retry_status(&Method::PUT, StatusCode::BAD_GATEWAY));
+        assert!(!policy.should_retry_status(&Method::DELETE, StatusCode::GATEWAY_TIMEOUT));
+        assert!(policy.should_retry_status(&Method::PUT, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.should_retry_status(&Method::GET, StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::error::Error;
+use crate::admin::naming::TopicName;
+
+pub struct PulsarAdminSchemas<'a> {
+    pub(crate) admin: &'a PulsarAdmin,
+}
+
+/// The latest schema of a topic, as the registry returns it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SchemaInfo {
+    pub version: i64,
+
+    /// e.g. `AVRO`, `JSON`, `STRING`.
+    #[serde(rename = "type")]
+    pub schema_type: String,
+
+    pub timestamp: i64,
+
+    /// The definition, e.g. an Avro schema as JSON; empty for primitive types.
+    #[serde(default)]
+    pub data: String,
+
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// A schema to upload; it becomes a new version if the compatibility strategy allows it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PostSchemaPayload {
+    #[serde(rename = "type")]
+    pub schema_type: String,
+
+    #[serde(default)]
+    pub schema: String,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+impl From<SchemaInfo> for PostSchemaPayload {
+    fn from(info: SchemaInfo) -> Self {
+        PostSchemaPayload {
+            schema_type: info.schema_type,
+            schema: info.data,
+            properties: info.properties,
+        }
+    }
+}
+
+impl<'a> PulsarAdminSchemas<'a> {
+    /// Schemas belong to the partitioned topic, not to its partitions.
+    fn path(topic: &TopicName) -> String {
+        let topic = topic.partitioned_topic_name();
+        format!("/admin/v2/schemas/{}/{}/schema", topic.namespace(), urlencoding::encode(topic.local_name()))
+    }
+
+    pub async fn get(&self, topic: &TopicName) -> Result<SchemaInfo, Error> {
+        Ok(self.admin.get(Self::path(topic).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    pub async fn create(&self, topic: &TopicName, payload: &PostSchemaPayload) -> Result<(), Error> {
+        self.admin.post(Self::path(topic).as_str())?
+            .json(payload)
+            .send().await?;
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::admin::admin::PulsarAdmin;
 use crate::admin::error::Error;
+use crate::admin::naming::NamespaceName;
 
 pub struct PulsarAdminSinks<'a> {
     pub(crate) admin: &'a PulsarAdmin,
@@ -9,10 +10,10 @@ pub struct PulsarAdminSinks<'a> {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SinkDef {
-    name: String,
-    description: String,
-    id: String,
-    version: String,
+    pub name: String,
+    pub description: String,
+    pub id: String,
+    pub version: String,
     #[serde(rename = "imageRepository")]
     image_repository: Option<String>,
     #[serde(rename = "imageTag")]
@@ -22,7 +23,7 @@ pub struct SinkDef {
     #[serde(rename = "sourceClass")]
     source_class: Option<String>,
     #[serde(rename = "sinkClass")]
-    sink_class: Option<String>,
+    pub sink_class: Option<String>,
     #[serde(rename = "sourceConfigClass")]
     source_config_class: Option<String>,
     #[serde(rename = "sinkConfigClass")]
@@ -37,12 +38,18 @@ pub struct SinkDef {
 }
 
 impl<'a> PulsarAdminSinks<'a> {
-    pub async fn list(&self, namespace: &str) -> Result<Vec<String>, Error> {
+    pub async fn list(&self, namespace: &NamespaceName) -> Result<Vec<String>, Error> {
         Ok(self.admin.get(format!("/admin/v3/sinks/{}", namespace).as_str())?
             .send().await?
             .json::<Vec<String>>().await?)
     }
 
+    pub async fn delete(&self, namespace: &NamespaceName, sink: &str) -> Result<(), Error> {
+        self.admin.delete(format!("/admin/v3/sinks/{}/{}", namespace, sink).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
     pub async fn builtin_sinks(&self) -> Result<Vec<SinkDef>, Error> {
         let res = self.admin.get("/admin/v3/sinks/builtinsinks")?
             .send().await?;
@@ -7,7 +7,7 @@ pub struct PulsarAdminTenants<'a> {
     pub(crate) admin: &'a PulsarAdmin,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TenantInfo {
     #[serde(rename = "adminRoles")]
     pub admin_roles: Vec<String>,
@@ -28,21 +28,13 @@ impl<'a> PulsarAdminTenants<'a> {
             .json(&info)
             .send().await?;
         debug!("Got {:?}", &r);
-        if r.status().is_success() {
-            Ok(())
-        } else {
-            Err(r.text().await?.into())
-        }
+        Ok(())
     }
 
     pub async fn list(&self) -> Result<Vec<String>, Error> {
-        let r = self.admin.get("/admin/v2/tenants")?
-            .send().await?;
-        if r.status().is_success() {
-            Ok(r.json::<Vec<String>>().await?)
-        } else {
-            Err(r.text().await?.into())
-        }
+        Ok(self.admin.get("/admin/v2/tenants")?
+            .send().await?
+            .json::<Vec<String>>().await?)
     }
 
     pub async fn get(&self, tenant: &str) -> Result<TenantInfo, Error> {
@@ -50,4 +42,21 @@ impl<'a> PulsarAdminTenants<'a> {
             .send().await?
             .json::<TenantInfo>().await?)
     }
+
+    /// Replaces the admin roles and allowed clusters of a tenant.
+    pub async fn update(&self, tenant: &str, info: &TenantInfo) -> Result<(), Error> {
+        self.admin.post(format!("/admin/v2/tenants/{}", tenant).as_str())?
+            .json(info)
+            .send().await?;
+        Ok(())
+    }
+
+    /// Deletes a tenant, which must have no namespaces left unless `force` is set and the
+    /// broker allows forced deletes.
+    pub async fn delete(&self, tenant: &str, force: bool) -> Result<(), Error> {
+        self.admin.delete(format!("/admin/v2/tenants/{}", tenant).as_str())?
+            .query(&[("force", force.to_string())])
+            .send().await?;
+        Ok(())
+    }
 }
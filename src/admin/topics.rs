@@ -1,40 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use reqwest::header::{CONTENT_TYPE, HeaderValue};
 use serde::{Deserialize, Serialize};
 
 use crate::admin::admin::PulsarAdmin;
 use crate::admin::error::Error;
+use crate::admin::namespaces::NamespacePolicy;
+use crate::admin::naming::{NamespaceName, TopicDomain, TopicName};
 
 pub struct PulsarAdminTopics<'a> {
     pub(crate) admin: &'a PulsarAdmin,
 }
 
-pub enum TopicDomain {
-    Persistent,
-    NonPersistent,
-}
-
-impl ToString for TopicDomain {
-    #[inline]
-    fn to_string(&self) -> String {
-        match self {
-            TopicDomain::Persistent => "persistent",
-            TopicDomain::NonPersistent => "non-persistent",
-        }.to_string()
-    }
-}
-
-impl TopicDomain {
-    pub fn parse(name: &str) -> Result<TopicDomain, Error> {
-        match name.to_uppercase().replace("-", "_").as_str() {
-            "PERSISTENT" => Ok(TopicDomain::Persistent),
-            "NON_PERSISTENT" => Ok(TopicDomain::NonPersistent),
-            &_ => Err(format!("invalid domain name [{}]", name).into())
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LookupResponse {
     #[serde(rename = "brokerUrl")]
@@ -84,33 +61,45 @@ pub struct TopicStats {
     waiting_publishers: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionedTopicMetadata {
+    /// `0` for non-partitioned topics.
+    pub partitions: u32,
+}
+
 impl<'a> PulsarAdminTopics<'a> {
-    pub async fn list(&self, namespace: &str, domain: TopicDomain) -> Result<Vec<String>, Error> {
-        Ok(self.admin.get(format!("/admin/v2/{}/{}", domain.to_string(), namespace).as_str())?
+    fn namespace_path(namespace: &NamespaceName, domain: TopicDomain) -> String {
+        if namespace.is_v2() {
+            format!("/admin/v2/{}/{}", domain, namespace)
+        } else {
+            format!("/admin/{}/{}", domain, namespace)
+        }
+    }
+
+    pub async fn list(&self, namespace: &NamespaceName, domain: TopicDomain) -> Result<Vec<String>, Error> {
+        Ok(self.admin.get(Self::namespace_path(namespace, domain).as_str())?
             .send().await?
             .json::<Vec<String>>().await?)
     }
 
-    pub async fn list_partitioned(&self, namespace: &str, domain: TopicDomain) -> Result<Vec<String>, Error> {
-        Ok(self.admin.get(format!("/admin/v2/{}/{}/partitioned", domain.to_string(), namespace).as_str())?
+    pub async fn list_partitioned(&self, namespace: &NamespaceName, domain: TopicDomain) -> Result<Vec<String>, Error> {
+        Ok(self.admin.get(format!("{}/partitioned", Self::namespace_path(namespace, domain)).as_str())?
             .send().await?
             .json::<Vec<String>>().await?)
     }
 
-    pub async fn lookup(&self, topic: &str) -> Result<LookupResponse, Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let body = self.admin.get(format!("/lookup/v2/topic/{}", canonical_topic).as_str())?
+    pub async fn lookup(&self, topic: &TopicName) -> Result<LookupResponse, Error> {
+        let body = self.admin.get(topic.lookup_path().as_str())?
             .send().await?
             .text().await?;
         debug!("Lookup response: {}", body);
         Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub async fn stats(&self, topic: &str,
+    pub async fn stats(&self, topic: &TopicName,
                        get_precise_backlog: bool,
                        subscription_backlog_size: bool) -> Result<serde_json::Value, Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let body = self.admin.get(format!("/admin/v2/{}/stats", canonical_topic).as_str())?
+        let body = self.admin.get(format!("{}/stats", topic.admin_path()).as_str())?
             .query(&[
                 ("getPreciseBacklog", get_precise_backlog),
                 ("subscriptionBacklogSize", subscription_backlog_size)
@@ -121,110 +110,126 @@ impl<'a> PulsarAdminTopics<'a> {
         Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub async fn permissions(&self, topic: &str) -> Result<HashMap<String, Vec<Option<String>>>, Error> {
-        let canonical_topic = topic.replace("://", "/");
-        Ok(self.admin.get(format!("/admin/v2/{}/permissions", canonical_topic).as_str())?
+    pub async fn permissions(&self, topic: &TopicName) -> Result<BTreeMap<String, Vec<String>>, Error> {
+        Ok(self.admin.get(format!("{}/permissions", topic.admin_path()).as_str())?
             .send().await?
-            .json().await?)
+            .json::<HashMap<String, Vec<Option<String>>>>().await?
+            .into_iter()
+            .map(|(role, actions)| (role, actions.into_iter().flatten().collect()))
+            .collect())
     }
 
-    pub async fn grant_permissions(&self, topic: &str, role: &str, permissions: &[String]) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin.post(format!("/admin/v2/{}/permissions/{}", canonical_topic, role).as_str())?
+    pub async fn grant_permissions(&self, topic: &TopicName, role: &str, permissions: &[String]) -> Result<(), Error> {
+        self.admin.post(format!("{}/permissions/{}", topic.admin_path(), role).as_str())?
             .json(&permissions)
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            resp.error_for_status()?;
-            Ok(())
-        }
+        Ok(())
     }
 
-    pub async fn revoke_permissions(&self, topic: &str, role: &str) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin.delete(format!("/admin/v2/{}/permissions/{}", canonical_topic, role).as_str())?
+    pub async fn revoke_permissions(&self, topic: &TopicName, role: &str) -> Result<(), Error> {
+        self.admin.delete(format!("{}/permissions/{}", topic.admin_path(), role).as_str())?
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            resp.error_for_status()?;
-            Ok(())
-        }
+        Ok(())
     }
 
-    pub async fn create_non_partitioned_topic(&self, topic: &str) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin
-            .put(format!("/admin/v2/{}", canonical_topic).as_str())?
+    pub async fn create_non_partitioned_topic(&self, topic: &TopicName) -> Result<(), Error> {
+        self.admin
+            .put(topic.admin_path().as_str())?
             .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(resp.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn create_partitioned_topic(&self, topic: &str, num_partitions: i32) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin
-            .put(format!("/admin/v2/{}/partitions", canonical_topic).as_str())?
+    pub async fn create_partitioned_topic(&self, topic: &TopicName, num_partitions: i32) -> Result<(), Error> {
+        self.admin
+            .put(format!("{}/partitions", topic.admin_path()).as_str())?
             .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
             .body(num_partitions.to_string())
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(resp.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn delete_topic(&self, topic: &str, force: bool, delete_schema: bool) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin
-            .delete(format!("/admin/v2/{}", canonical_topic).as_str())?
-            .query(&[("force", force.to_string()), ("", delete_schema.to_string())])
+    pub async fn delete_topic(&self, topic: &TopicName, force: bool, delete_schema: bool) -> Result<(), Error> {
+        self.admin
+            .delete(topic.admin_path().as_str())?
+            .query(&[("force", force.to_string()), ("deleteSchema", delete_schema.to_string())])
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(resp.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn delete_partitioned_topic(&self, topic: &str, force: bool, delete_schema: bool) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-        let resp = self.admin
-            .delete(format!("/admin/v2/{}/partitions", canonical_topic).as_str())?
-            .query(&[("force", force.to_string()), ("", delete_schema.to_string())])
+    pub async fn delete_partitioned_topic(&self, topic: &TopicName, force: bool, delete_schema: bool) -> Result<(), Error> {
+        self.admin
+            .delete(format!("{}/partitions", topic.admin_path()).as_str())?
+            .query(&[("force", force.to_string()), ("deleteSchema", delete_schema.to_string())])
             .send().await?;
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(resp.text().await?.into())
-        }
+        Ok(())
     }
 
-    pub async fn subscriptions(&self, topic: &str) -> Result<Vec<String>, Error> {
-        let canonical_topic = topic.replace("://", "/");
-        Ok(self.admin.get(format!("/admin/v2/{}/subscriptions", canonical_topic).as_str())?
+    pub async fn subscriptions(&self, topic: &TopicName) -> Result<Vec<String>, Error> {
+        Ok(self.admin.get(format!("{}/subscriptions", topic.admin_path()).as_str())?
             .send().await?
             .json().await?)
     }
 
-    pub async fn unsubscribe(&self, topic: &str, subscription: &str, force: bool) -> Result<(), Error> {
-        let canonical_topic = topic.replace("://", "/");
-
-        let resp = self.admin.delete(format!("/admin/v2/{}/subscription/{}", canonical_topic,
+    pub async fn unsubscribe(&self, topic: &TopicName, subscription: &str, force: bool) -> Result<(), Error> {
+        self.admin.delete(format!("{}/subscription/{}", topic.admin_path(),
                                              urlencoding::encode(subscription)).as_str())?
             .query(&[("force", force.to_string())])
             .send().await?;
+        Ok(())
+    }
 
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(resp.text().await?.into())
+    pub async fn partitioned_metadata(&self, topic: &TopicName) -> Result<PartitionedTopicMetadata, Error> {
+        Ok(self.admin.get(format!("{}/partitions", topic.admin_path()).as_str())?
+            .send().await?
+            .json().await?)
+    }
+
+    /// Increases the partitions of a partitioned topic; they cannot be decreased.
+    ///
+    /// With `update_local_topic_only` a geo-replicated topic is only updated in this cluster, and
+    /// `force` creates the new partitions even when some of them already exist.
+    pub async fn update_partitions(&self, topic: &TopicName, num_partitions: u32,
+                                   update_local_topic_only: bool, force: bool) -> Result<(), Error> {
+        self.admin.post(format!("{}/partitions", topic.admin_path()).as_str())?
+            .query(&[("updateLocalTopicOnly", update_local_topic_only.to_string()), ("force", force.to_string())])
+            .json(&num_partitions)
+            .send().await?;
+        Ok(())
+    }
+
+    /// Creates the partitions of a partitioned topic that are missing, e.g. after they were deleted
+    /// while the metadata was kept.
+    pub async fn create_missed_partitions(&self, topic: &TopicName) -> Result<(), Error> {
+        self.admin.post(format!("{}/createMissedPartitions", topic.admin_path()).as_str())?
+            .send().await?;
+        Ok(())
+    }
+
+    /// Creates a subscription starting at the latest message.
+    pub async fn create_subscription(&self, topic: &TopicName, subscription: &str) -> Result<(), Error> {
+        self.admin.put(format!("{}/subscription/{}", topic.admin_path(),
+                               urlencoding::encode(subscription)).as_str())?
+            .json(&serde_json::json!({"ledgerId": i64::MAX, "entryId": i64::MAX}))
+            .send().await?;
+        Ok(())
+    }
+
+    /// A topic level policy, or `None` when the namespace policy applies.
+    pub async fn policy<P: NamespacePolicy>(&self, topic: &TopicName) -> Result<Option<P>, Error> {
+        let body = self.admin.get(format!("{}/{}", topic.admin_path(), P::PATH).as_str())?
+            .send().await?
+            .text().await?;
+        if body.trim().is_empty() {
+            return Ok(None);
         }
+        Ok(serde_json::from_str(body.as_str())?)
+    }
+
+    pub async fn set_policy<P: NamespacePolicy>(&self, topic: &TopicName, policy: &P) -> Result<(), Error> {
+        self.admin.post(format!("{}/{}", topic.admin_path(), P::PATH).as_str())?
+            .json(policy)
+            .send().await?;
+        Ok(())
     }
 }
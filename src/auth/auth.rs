@@ -1,42 +1,99 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use pulsar::error::AuthenticationError;
 
+use crate::auth::basic::BasicAuthn;
 use crate::auth::error::Error;
 use crate::auth::oauth2::OAuth2Authn;
+use crate::auth::tls::TlsAuthn;
+use crate::auth::token::TokenAuthn;
+
+/// Shows the verification URI and the code to enter there during an interactive login.
+pub type LoginPrompt = dyn Fn(&str, &str) + Send + Sync;
 
+/// An authentication method, supplying credentials for both the binary protocol and the
+/// admin REST API. Token based methods only need `get_token`.
 #[async_trait]
 pub trait Authn: Send + Sync + 'static {
+    /// Name of the method, as the broker's authentication provider knows it.
     fn auth_method_name(&self) -> String;
 
     async fn initialize(&mut self) -> Result<(), Error>;
 
-    async fn get_token(&self) -> Result<String, Error>;
+    async fn get_token(&self) -> Result<String, Error> {
+        Err(Error::Custom(format!("auth method [{}] does not use tokens", self.auth_method_name())))
+    }
+
+    /// Auth data sent with the `CONNECT` command of the binary protocol.
+    async fn auth_data(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.get_token().await?.into_bytes())
+    }
+
+    /// Headers added to every admin request.
+    async fn http_headers(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(vec![("Authorization".to_string(), format!("Bearer {}", self.get_token().await?))])
+    }
+
+    /// The role the broker most likely derives from the credentials, for diagnostics: the
+    /// `sub` claim of JWTs by default.
+    async fn role(&self) -> Result<Option<String>, Error> {
+        let token = self.get_token().await?;
+        Ok(crate::auth::jwt::decode(token.as_str()).ok()
+            .and_then(|(_, claims)| claims.get("sub").and_then(|sub| sub.as_str()).map(String::from)))
+    }
+
+    /// Interactive login of a human.
+    async fn login(&self, _prompt: &LoginPrompt) -> Result<(), Error> {
+        Err(Error::Custom(format!("auth method [{}] does not support login", self.auth_method_name())))
+    }
+
+    /// PEM certificate and key files presented during the TLS handshake.
+    fn tls_identity(&self) -> Option<(PathBuf, PathBuf)> {
+        None
+    }
 }
 
-pub fn create(auth_name: String, auth_params: String) -> Result<Box<dyn Authn>, Error> {
+/// `http_client` is used by methods calling other services, like OAuth2 issuers.
+pub fn create(auth_name: String, auth_params: String, http_client: reqwest::Client) -> Result<Box<dyn Authn>, Error> {
     match auth_name.to_ascii_lowercase().as_str() {
-        "token" => Ok(Box::new(TokenAuthn {
-            params: auth_params
-        })),
-        "oauth2" => Ok(OAuth2Authn::create(auth_params.as_str())?),
+        "token" | "org.apache.pulsar.client.impl.auth.authenticationtoken" =>
+            Ok(TokenAuthn::create(auth_params.as_str())?),
+        "oauth2" | "org.apache.pulsar.client.impl.auth.oauth2.authenticationoauth2" =>
+            Ok(OAuth2Authn::create(auth_params.as_str(), http_client)?),
+        "tls" | "org.apache.pulsar.client.impl.auth.authenticationtls" =>
+            Ok(TlsAuthn::create(auth_params.as_str())?),
+        "basic" | "org.apache.pulsar.client.impl.auth.authenticationbasic" =>
+            Ok(BasicAuthn::create(auth_params.as_str())?),
         _ => Err(format!("invalid auth [{}], [{}]", auth_name, auth_params).into()),
     }
 }
 
-struct TokenAuthn {
-    params: String,
+/// Adapts an `Authn` to the pulsar client. The client asks for auth data on every new
+/// connection, so reconnections pick up refreshed tokens.
+pub struct PulsarAuthentication {
+    authn: Arc<dyn Authn>,
+}
+
+impl PulsarAuthentication {
+    pub fn new(authn: Arc<dyn Authn>) -> PulsarAuthentication {
+        PulsarAuthentication { authn }
+    }
 }
 
 #[async_trait]
-impl Authn for TokenAuthn {
+impl pulsar::authentication::Authentication for PulsarAuthentication {
     fn auth_method_name(&self) -> String {
-        "auth".to_string()
+        self.authn.auth_method_name()
     }
 
-    async fn initialize(&mut self) -> Result<(), Error> {
+    async fn initialize(&mut self) -> Result<(), AuthenticationError> {
         Ok(())
     }
 
-    async fn get_token(&self) -> Result<String, Error> {
-        Ok(self.params.clone())
+    async fn auth_data(&mut self) -> Result<Vec<u8>, AuthenticationError> {
+        self.authn.auth_data().await
+            .map_err(|e| AuthenticationError::Custom(e.to_string()))
     }
 }
@@ -0,0 +1,95 @@
+use std::fs;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::auth::auth::Authn;
+use crate::auth::error::Error;
+
+/// Authentication against the broker's `basic` provider.
+pub(crate) struct BasicAuthn {
+    params: BasicParams,
+}
+
+/// `{"userId": "...", "password": "..."}`, or `passwordFile` instead of `password` to keep
+/// the password out of the config.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct BasicParams {
+    user_id: String,
+    password: Option<String>,
+    password_file: Option<String>,
+}
+
+impl BasicAuthn {
+    pub(crate) fn create(auth_params: &str) -> Result<Box<dyn Authn>, Error> {
+        let params: BasicParams = serde_json::from_str(auth_params)?;
+        if params.password.is_none() && params.password_file.is_none() {
+            return Err(Error::Custom("password or passwordFile is required for basic auth".to_string()));
+        }
+        Ok(Box::new(BasicAuthn { params }))
+    }
+
+    /// `userId:password`, with the password file read on every use so it can be rotated.
+    fn credentials(&self) -> Result<String, Error> {
+        let password = match (&self.params.password, &self.params.password_file) {
+            (Some(password), _) => password.clone(),
+            (None, Some(path)) => {
+                let path = path.strip_prefix("file://").unwrap_or(path);
+                fs::read_to_string(path)
+                    .map_err(|e| Error::Custom(format!("failed to read password file {}: {}", path, e)))?
+                    .trim()
+                    .to_string()
+            }
+            (None, None) => unreachable!(),
+        };
+        Ok(format!("{}:{}", self.params.user_id, password))
+    }
+}
+
+#[async_trait]
+impl Authn for BasicAuthn {
+    fn auth_method_name(&self) -> String {
+        "basic".to_string()
+    }
+
+    async fn initialize(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn auth_data(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.credentials()?.into_bytes())
+    }
+
+    async fn http_headers(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(vec![("Authorization".to_string(), format!("Basic {}", base64::encode(self.credentials()?)))])
+    }
+
+    async fn role(&self) -> Result<Option<String>, Error> {
+        Ok(Some(self.params.user_id.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::basic::BasicAuthn;
+
+    #[tokio::test]
+    async fn credentials() {
+        let authn = BasicAuthn::create(r#"{"userId":"superuser","password":"admin"}"#).unwrap();
+        assert_eq!(authn.auth_method_name(), "basic");
+        assert_eq!(authn.auth_data().await.unwrap(), b"superuser:admin".to_vec());
+        assert_eq!(authn.http_headers().await.unwrap(),
+                   vec![("Authorization".to_string(), "Basic c3VwZXJ1c2VyOmFkbWlu".to_string())]);
+        assert_eq!(authn.role().await.unwrap(), Some("superuser".to_string()));
+
+        let path = std::env::temp_dir().join(format!("pulsar-smith-password-{}", std::process::id()));
+        std::fs::write(&path, "secret\n").unwrap();
+        let params = format!(r#"{{"userId":"superuser","passwordFile":"{}"}}"#, path.display());
+        let authn = BasicAuthn::create(params.as_str()).unwrap();
+        assert_eq!(authn.auth_data().await.unwrap(), b"superuser:secret".to_vec());
+        std::fs::remove_file(path).unwrap();
+
+        assert!(BasicAuthn::create(r#"{"userId":"superuser"}"#).is_err());
+    }
+}
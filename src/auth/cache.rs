@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth::error::Error;
+
+const CACHE_FILE: &str = "cache/tokens.json";
+
+/// Tokens are refreshed when less than this many seconds are left before they expire.
+const REFRESH_AHEAD_SECS: u64 = 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedToken {
+    pub access_token: String,
+    /// Expiration as seconds since the epoch, `None` if the issuer did not tell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Kept for interactive logins, so later commands refresh the token without the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    pub fn new(access_token: String, expires_in: Option<u64>) -> CachedToken {
+        CachedToken {
+            access_token,
+            expires_at: expires_in.map(|secs| now() + secs),
+            refresh_token: None,
+        }
+    }
+
+    /// Whether the token is still good for a while. Tokens without an expiration are only
+    /// kept in memory, and always considered fresh there.
+    pub fn is_fresh(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now() + REFRESH_AHEAD_SECS < expires_at,
+            None => true,
+        }
+    }
+}
+
+/// Access tokens persisted across runs, in a file under the config directory, so every
+/// command does not go through a new token exchange.
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    path: Option<PathBuf>,
+}
+
+impl TokenCache {
+    /// A cache in the given file, or one persisting nothing without a file.
+    pub fn new(path: Option<PathBuf>) -> TokenCache {
+        TokenCache { path }
+    }
+
+    /// The cache file under the config directory.
+    pub fn default_location() -> TokenCache {
+        Self::new(crate::config::config_dir().map(|dir| dir.join(CACHE_FILE)))
+    }
+
+    pub fn key(issuer: &str, audience: &str, client_id: &str) -> String {
+        format!("{} {} {}", issuer, audience, client_id)
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedToken> {
+        self.load().ok()?.remove(key).filter(|t| t.is_fresh())
+    }
+
+    /// The refresh token stored with the entry, even if its access token expired.
+    pub fn refresh_token(&self, key: &str) -> Option<String> {
+        self.load().ok()?.remove(key).and_then(|t| t.refresh_token)
+    }
+
+    pub fn put(&self, key: &str, token: &CachedToken) -> Result<(), Error> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if token.expires_at.is_none() && token.refresh_token.is_none() {
+            return Ok(());
+        }
+        let mut tokens = self.load().unwrap_or_default();
+        tokens.retain(|_, t| t.is_fresh() || t.refresh_token.is_some());
+        tokens.insert(key.to_string(), token.clone());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(path, serde_json::to_string_pretty(&tokens)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, CachedToken>, Error> {
+        match &self.path {
+            Some(path) if path.exists() => Ok(serde_json::from_str(fs::read_to_string(path)?.as_str())?),
+            _ => Ok(BTreeMap::new()),
+        }
+    }
+}
+
+/// Writes a file readable by the owner only, as it holds credentials.
+pub(crate) fn write_private(path: &PathBuf, content: &[u8]) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+        file.write_all(content)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, content)
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::cache::{CachedToken, now, TokenCache};
+
+    #[test]
+    fn persist_fresh_tokens() {
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-cache-{}", std::process::id()));
+        let cache = TokenCache::new(Some(dir.join("tokens.json")));
+        let key = TokenCache::key("https://issuer", "urn:pulsar", "client");
+        assert_eq!(cache.get(key.as_str()), None);
+
+        let token = CachedToken::new("t1".to_string(), Some(3600));
+        cache.put(key.as_str(), &token).unwrap();
+        assert_eq!(cache.get(key.as_str()), Some(token));
+
+        let expiring = CachedToken { access_token: "t2".to_string(), expires_at: Some(now() + 10), refresh_token: None };
+        assert!(!expiring.is_fresh());
+        cache.put(key.as_str(), &expiring).unwrap();
+        assert_eq!(cache.get(key.as_str()), None);
+
+        // refresh tokens outlive their access token
+        let expiring = CachedToken { refresh_token: Some("r1".to_string()), ..expiring };
+        cache.put(key.as_str(), &expiring).unwrap();
+        assert_eq!(cache.get(key.as_str()), None);
+        assert_eq!(cache.refresh_token(key.as_str()), Some("r1".to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -26,6 +26,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Error::Custom(e.to_string())
+    }
+}
+
 impl From<String> for Error {
     fn from(s: String) -> Self {
         Error::Custom(s)
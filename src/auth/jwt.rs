@@ -0,0 +1,219 @@
+//! JSON Web Tokens as checked by the broker's `token` provider: decoding without
+//! verification for inspection, and signing test tokens like `pulsar tokens` does.
+
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::sign::Signer;
+use serde_json::{json, Map, Value};
+
+use crate::auth::error::Error;
+
+const SECRET_KEY_LEN: usize = 32;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// HMAC with SHA-256, using a secret key
+    #[value(name = "HS256")]
+    Hs256,
+    /// RSA with SHA-256, using a key pair
+    #[value(name = "RS256")]
+    Rs256,
+    /// ECDSA on P-256 with SHA-256, using a key pair
+    #[value(name = "ES256")]
+    Es256,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Hs256 => "HS256",
+            Algorithm::Rs256 => "RS256",
+            Algorithm::Es256 => "ES256",
+        }
+    }
+}
+
+pub enum SigningKey {
+    Secret(Vec<u8>),
+    Private(PKey<Private>),
+}
+
+impl SigningKey {
+    /// A private key, PEM or DER encoded.
+    pub fn private_key(key: &[u8]) -> Result<SigningKey, Error> {
+        let key = if key.starts_with(b"-----BEGIN") {
+            PKey::private_key_from_pem(key)?
+        } else {
+            PKey::private_key_from_der(key)?
+        };
+        Ok(SigningKey::Private(key))
+    }
+
+    /// The algorithm matching the key, for when none is given.
+    fn default_algorithm(&self) -> Result<Algorithm, Error> {
+        match self {
+            SigningKey::Secret(_) => Ok(Algorithm::Hs256),
+            SigningKey::Private(key) if key.id() == Id::RSA => Ok(Algorithm::Rs256),
+            SigningKey::Private(key) if key.id() == Id::EC => Ok(Algorithm::Es256),
+            SigningKey::Private(_) => Err(Error::Custom("unsupported private key type".to_string())),
+        }
+    }
+}
+
+/// Decodes the header and claims of a token, without verifying its signature.
+pub fn decode(token: &str) -> Result<(Value, Value), Error> {
+    let segments: Vec<&str> = token.trim().split('.').collect();
+    if segments.len() != 3 {
+        return Err(Error::SerDe("not a JWT, expected three segments separated by '.'".to_string()));
+    }
+    let segment = |s: &str, name: &str| -> Result<Value, Error> {
+        let bytes = base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| Error::SerDe(format!("invalid JWT {}: {}", name, e)))?;
+        serde_json::from_slice(bytes.as_slice())
+            .map_err(|e| Error::SerDe(format!("invalid JWT {}: {}", name, e)))
+    };
+    Ok((segment(segments[0], "header")?, segment(segments[1], "claims")?))
+}
+
+/// Signs the claims, with the algorithm matching the key unless one is given.
+pub fn sign(claims: &Map<String, Value>, key: &SigningKey, algorithm: Option<Algorithm>) -> Result<String, Error> {
+    let algorithm = match algorithm {
+        Some(algorithm) => algorithm,
+        None => key.default_algorithm()?,
+    };
+    let header = json!({"alg": algorithm.name(), "typ": "JWT"});
+    let input = format!("{}.{}", encode_segment(serde_json::to_vec(&header)?.as_slice()),
+                        encode_segment(serde_json::to_vec(claims)?.as_slice()));
+    let signature = match (algorithm, key) {
+        (Algorithm::Hs256, SigningKey::Secret(secret)) => {
+            let key = PKey::hmac(secret.as_slice())?;
+            Signer::new(MessageDigest::sha256(), &key)?.sign_oneshot_to_vec(input.as_bytes())?
+        }
+        (Algorithm::Rs256, SigningKey::Private(key)) if key.id() == Id::RSA => {
+            Signer::new(MessageDigest::sha256(), key)?.sign_oneshot_to_vec(input.as_bytes())?
+        }
+        (Algorithm::Es256, SigningKey::Private(key)) if key.id() == Id::EC => {
+            // JWS wants the raw r and s values rather than a DER encoded signature
+            let digest = hash(MessageDigest::sha256(), input.as_bytes())?;
+            let signature = EcdsaSig::sign(&digest, key.ec_key()?.as_ref())?;
+            let mut raw = signature.r().to_vec_padded(32)?;
+            raw.extend(signature.s().to_vec_padded(32)?);
+            raw
+        }
+        (algorithm, _) => return Err(Error::Custom(
+            format!("{} needs a {}", algorithm.name(),
+                    if algorithm == Algorithm::Hs256 { "secret key" } else { "matching private key" }))),
+    };
+    Ok(format!("{}.{}", input, encode_segment(signature.as_slice())))
+}
+
+fn encode_segment(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+pub fn create_secret_key() -> Result<Vec<u8>, Error> {
+    let mut key = vec![0; SECRET_KEY_LEN];
+    openssl::rand::rand_bytes(key.as_mut_slice())?;
+    Ok(key)
+}
+
+/// A new key pair, as DER encoded PKCS#8 private key and X.509 public key like the broker
+/// expects for `tokenPublicKey`.
+pub fn create_key_pair(algorithm: Algorithm) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let key = match algorithm {
+        Algorithm::Rs256 => PKey::from_rsa(Rsa::generate(2048)?)?,
+        Algorithm::Es256 => PKey::from_ec_key(EcKey::generate(EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?.as_ref())?)?,
+        Algorithm::Hs256 => return Err(Error::Custom("HS256 uses a secret key, not a key pair".to_string())),
+    };
+    Ok((key.private_key_to_pkcs8()?, key.public_key_to_der()?))
+}
+
+/// Reads a key the way the broker reads its key settings: a `file://` or `data:` URL, a
+/// path, or the base64 encoded key itself.
+pub fn read_key(key: &str) -> Result<Vec<u8>, Error> {
+    if let Some(path) = key.strip_prefix("file://") {
+        Ok(fs::read(path).map_err(|e| Error::Custom(format!("failed to read key {}: {}", path, e)))?)
+    } else if let Some(data) = key.strip_prefix("data:") {
+        let (media_type, data) = data.split_once(',')
+            .ok_or_else(|| Error::Custom("invalid data url for key".to_string()))?;
+        if media_type.ends_with(";base64") {
+            base64::decode(data).map_err(|e| Error::SerDe(format!("invalid base64 key: {}", e)))
+        } else {
+            Ok(data.as_bytes().to_vec())
+        }
+    } else if Path::new(key).exists() {
+        Ok(fs::read(key)?)
+    } else {
+        base64::decode(key).map_err(|e| Error::SerDe(format!("key is neither a file nor base64: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::pkey::PKey;
+    use openssl::sign::Verifier;
+    use openssl::hash::MessageDigest;
+    use serde_json::{json, Map, Value};
+
+    use crate::auth::jwt::{Algorithm, create_key_pair, create_secret_key, decode, sign, SigningKey};
+
+    fn claims() -> Map<String, Value> {
+        json!({"sub": "admin", "exp": 1893456000}).as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn hs256() {
+        // same as `pulsar tokens create --secret-key data:;base64,c2VjcmV0`
+        let key = SigningKey::Secret(b"secret".to_vec());
+        let token = sign(&claims(), &key, None).unwrap();
+        assert_eq!(token, "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJleHAiOjE4OTM0NTYwMDAsInN1YiI6ImFkbWluIn0.\
+                           t_E8qUkf-TZSJb2KDgyRZ4sO2PMPhsVcZ7DNZSiDVRo");
+        let (header, claims) = decode(token.as_str()).unwrap();
+        assert_eq!(header, json!({"alg": "HS256", "typ": "JWT"}));
+        assert_eq!(claims["sub"], "admin");
+        assert_eq!(create_secret_key().unwrap().len(), 32);
+        assert!(sign(&self::claims(), &key, Some(Algorithm::Rs256)).is_err());
+    }
+
+    #[test]
+    fn key_pairs() {
+        for algorithm in [Algorithm::Rs256, Algorithm::Es256] {
+            let (private_key, public_key) = create_key_pair(algorithm).unwrap();
+            let key = SigningKey::private_key(private_key.as_slice()).unwrap();
+            let token = sign(&claims(), &key, None).unwrap();
+            let (header, _) = decode(token.as_str()).unwrap();
+            assert_eq!(header["alg"], algorithm.name());
+
+            let (input, signature) = token.rsplit_once('.').unwrap();
+            let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD).unwrap();
+            let public_key = PKey::public_key_from_der(public_key.as_slice()).unwrap();
+            let valid = if algorithm == Algorithm::Rs256 {
+                Verifier::new(MessageDigest::sha256(), &public_key).unwrap()
+                    .verify_oneshot(signature.as_slice(), input.as_bytes()).unwrap()
+            } else {
+                assert_eq!(signature.len(), 64);
+                let r = openssl::bn::BigNum::from_slice(&signature[..32]).unwrap();
+                let s = openssl::bn::BigNum::from_slice(&signature[32..]).unwrap();
+                let digest = openssl::hash::hash(MessageDigest::sha256(), input.as_bytes()).unwrap();
+                openssl::ecdsa::EcdsaSig::from_private_components(r, s).unwrap()
+                    .verify(&digest, public_key.ec_key().unwrap().as_ref()).unwrap()
+            };
+            assert!(valid);
+        }
+        assert!(create_key_pair(Algorithm::Hs256).is_err());
+    }
+
+    #[test]
+    fn invalid_tokens() {
+        assert!(decode("abc").is_err());
+        assert!(decode("a.b.c").is_err());
+    }
+}
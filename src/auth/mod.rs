@@ -1,3 +1,8 @@
 pub mod auth;
+mod basic;
 mod oauth2;
+mod tls;
+mod token;
 pub(crate) mod error;
+pub(crate) mod cache;
+pub(crate) mod jwt;
@@ -1,48 +1,208 @@
+use std::fmt::Display;
 use std::fs;
 
 use async_trait::async_trait;
-use oauth2::{AuthUrl, ClientId, ClientSecret, TokenResponse, TokenUrl};
+use oauth2::{AuthUrl, ClientId, ClientSecret, DeviceAuthorizationUrl, ErrorResponse, HttpRequest, HttpResponse,
+             RefreshToken, RequestTokenError, Scope, TokenResponse, TokenUrl};
 use oauth2::AuthType::RequestBody;
-use oauth2::basic::BasicClient;
-use oauth2::reqwest::async_http_client;
+use oauth2::basic::{BasicClient, BasicTokenResponse};
+use oauth2::devicecode::StandardDeviceAuthorizationResponse;
 use serde::Deserialize;
+use tokio::sync::Mutex;
 
-use crate::auth::auth::Authn;
+use crate::auth::auth::{Authn, LoginPrompt};
+use crate::auth::cache::{CachedToken, TokenCache};
 use crate::auth::error::Error;
 
+/// Client credentials flow, or the device authorization flow of `auth login` for humans. The
+/// access token is kept in memory and in the `TokenCache`, and only exchanged again shortly
+/// before it expires, with the refresh token of a login when there is one.
 pub(crate) struct OAuth2Authn {
     params: OAuth2Params,
+    cache: TokenCache,
+    token: Mutex<Option<CachedToken>>,
+    endpoints: Mutex<Option<Endpoints>>,
+    http_client: reqwest::Client,
 }
 
+/// Params of the Java client, in snake case or camel case.
 #[derive(Deserialize, Debug)]
 struct OAuth2Params {
-    credentials_url: String,
+    /// Client credentials, as a `file://` or `data:` URL or the JSON itself. Without them
+    /// tokens only come from `auth login`.
+    #[serde(default, alias = "privateKey")]
+    credentials_url: Option<String>,
+    #[serde(alias = "issuerUrl")]
     issuer_url: String,
     audience: String,
+    /// Space separated scopes.
+    #[serde(default)]
     scope: Option<String>,
+    /// The public client used by `auth login`, when there are no client credentials.
+    #[serde(default, alias = "clientId")]
+    client_id: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
 struct OAuth2PrivateParams {
     client_id: String,
     client_secret: String,
-    client_email: String,
-    issuer_url: String,
+    #[allow(dead_code)]
+    #[serde(default)]
+    client_email: Option<String>,
+    #[serde(default)]
+    issuer_url: Option<String>,
+}
+
+/// The issuer's endpoints, from its `.well-known/openid-configuration`.
+#[derive(Deserialize, Debug, Clone)]
+struct Endpoints {
+    token_endpoint: String,
+    #[serde(default)]
+    device_authorization_endpoint: Option<String>,
 }
 
 impl OAuth2Authn {
-    pub(crate) fn create(auth_params: &str) -> Result<Box<dyn Authn>, Error> {
-        Ok(Box::new(OAuth2Authn {
-            params: serde_json::from_str(auth_params)?
-        }))
+    pub(crate) fn create(auth_params: &str, http_client: reqwest::Client) -> Result<Box<dyn Authn>, Error> {
+        Ok(Box::new(Self::new(auth_params, TokenCache::default_location(), http_client)?))
+    }
+
+    fn new(auth_params: &str, cache: TokenCache, http_client: reqwest::Client) -> Result<OAuth2Authn, Error> {
+        Ok(OAuth2Authn {
+            params: serde_json::from_str(auth_params)?,
+            cache,
+            token: Mutex::new(None),
+            endpoints: Mutex::new(None),
+            http_client,
+        })
+    }
+
+    /// Sends the requests of the oauth2 crate with the context's client.
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, reqwest::Error> {
+        let mut builder = self.http_client.request(request.method, request.url.as_str()).body(request.body);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder.send().await?;
+        Ok(HttpResponse {
+            status_code: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?.to_vec(),
+        })
     }
 }
 
 impl OAuth2Authn {
-    fn read_private_params(&self) -> Result<OAuth2PrivateParams, Error> {
-        let fn_name = self.params.credentials_url.strip_prefix("file://")
-            .expect("key file should start with file://");
-        Ok(serde_json::from_str(fs::read_to_string(fn_name)?.as_str())?)
+    fn read_private_params(&self) -> Result<Option<OAuth2PrivateParams>, Error> {
+        let credentials_url = match &self.params.credentials_url {
+            Some(credentials_url) => credentials_url.trim(),
+            None => return Ok(None),
+        };
+        let credentials = read_credentials(credentials_url)?;
+        Ok(Some(serde_json::from_str(credentials.as_str())
+            .map_err(|e| Error::SerDe(format!("invalid oauth2 credentials: {}", e)))?))
+    }
+
+    fn cache_key(&self, private_params: Option<&OAuth2PrivateParams>) -> Result<String, Error> {
+        let issuer_url = private_params.and_then(|p| p.issuer_url.as_deref())
+            .unwrap_or(self.params.issuer_url.as_str());
+        Ok(TokenCache::key(issuer_url, self.params.audience.as_str(), self.client_id(private_params)?))
+    }
+
+    fn client_id<'a>(&'a self, private_params: Option<&'a OAuth2PrivateParams>) -> Result<&'a str, Error> {
+        private_params.map(|p| p.client_id.as_str())
+            .or(self.params.client_id.as_deref())
+            .ok_or_else(|| Error::Custom("oauth2 auth params need a credentials_url or a client_id".to_string()))
+    }
+
+    fn scopes(&self) -> Vec<Scope> {
+        self.params.scope.iter()
+            .flat_map(|scope| scope.split_whitespace())
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect()
+    }
+
+    /// Discovers the endpoints of the issuer, falling back to the `oauth/token` and
+    /// `oauth/device/code` paths of issuers without a discovery document.
+    async fn endpoints(&self) -> Endpoints {
+        let mut endpoints = self.endpoints.lock().await;
+        if let Some(endpoints) = endpoints.as_ref() {
+            return endpoints.clone();
+        }
+        let issuer_url = self.params.issuer_url.trim_end_matches('/');
+        let discovered = match discover(&self.http_client, issuer_url).await {
+            Ok(discovered) => discovered,
+            Err(e) => {
+                debug!("failed to discover the endpoints of {}: {}", issuer_url, e);
+                Endpoints {
+                    token_endpoint: format!("{}/oauth/token", issuer_url),
+                    device_authorization_endpoint: Some(format!("{}/oauth/device/code", issuer_url)),
+                }
+            }
+        };
+        *endpoints = Some(discovered.clone());
+        discovered
+    }
+
+    async fn client(&self, private_params: Option<&OAuth2PrivateParams>) -> Result<BasicClient, Error> {
+        let endpoints = self.endpoints().await;
+        let mut client = BasicClient::new(
+            ClientId::new(self.client_id(private_params)?.to_string()),
+            private_params.map(|p| ClientSecret::new(p.client_secret.clone())),
+            AuthUrl::new(self.params.issuer_url.clone())?,
+            Some(TokenUrl::new(endpoints.token_endpoint)?),
+        ).set_auth_type(RequestBody);
+        if let Some(device_authorization_endpoint) = endpoints.device_authorization_endpoint {
+            client = client.set_device_authorization_url(DeviceAuthorizationUrl::new(device_authorization_endpoint)?);
+        }
+        Ok(client)
+    }
+}
+
+/// Reads client credentials from a `file://` URL, a `data:application/json;base64,` or
+/// `data:application/json,` URL, or inline JSON.
+fn read_credentials(credentials_url: &str) -> Result<String, Error> {
+    if let Some(path) = credentials_url.strip_prefix("file://") {
+        fs::read_to_string(path)
+            .map_err(|e| Error::Custom(format!("failed to read oauth2 credentials {}: {}", path, e)))
+    } else if let Some(data) = credentials_url.strip_prefix("data:") {
+        let (media_type, data) = data.split_once(',')
+            .ok_or_else(|| Error::Custom("invalid data url for oauth2 credentials".to_string()))?;
+        if media_type.ends_with(";base64") {
+            let decoded = base64::decode(data)
+                .map_err(|e| Error::SerDe(format!("invalid base64 oauth2 credentials: {}", e)))?;
+            String::from_utf8(decoded).map_err(|e| Error::SerDe(e.to_string()))
+        } else {
+            Ok(urlencoding::decode(data).map_err(|e| Error::SerDe(e.to_string()))?.into_owned())
+        }
+    } else if credentials_url.starts_with('{') {
+        Ok(credentials_url.to_string())
+    } else {
+        Err(Error::Custom(format!("unsupported oauth2 credentials url [{}], expected file://, data: or JSON",
+                                  credentials_url)))
+    }
+}
+
+async fn discover(http_client: &reqwest::Client, issuer_url: &str) -> Result<Endpoints, Error> {
+    let url = format!("{}/.well-known/openid-configuration", issuer_url);
+    let response = http_client.get(url.as_str()).send().await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    response.json().await.map_err(|e| Error::SerDe(e.to_string()))
+}
+
+fn cached_token(response: &BasicTokenResponse, refresh_token: Option<String>) -> CachedToken {
+    CachedToken {
+        refresh_token: response.refresh_token().map(|t| t.secret().clone()).or(refresh_token),
+        ..CachedToken::new(response.access_token().secret().clone(), response.expires_in().map(|d| d.as_secs()))
+    }
+}
+
+fn request_error<RE, T>(e: RequestTokenError<RE, T>) -> Error
+    where RE: std::error::Error + 'static, T: ErrorResponse + Display + 'static {
+    match e {
+        RequestTokenError::ServerResponse(response) => Error::Custom(format!("oauth2 server error: {}", response)),
+        e => Error::Custom(format!("{:?}", e)),
     }
 }
 
@@ -57,29 +217,245 @@ impl Authn for OAuth2Authn {
     }
 
     async fn get_token(&self) -> Result<String, Error> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
         let private_params = self.read_private_params()?;
-        let token_url = if private_params.issuer_url.ends_with("/") {
-            private_params.issuer_url.clone() + "oauth/token"
-        } else {
-            private_params.issuer_url.clone() + "/oauth/token"
+        let key = self.cache_key(private_params.as_ref())?;
+        let fresh = match self.cache.get(key.as_str()) {
+            Some(cached) => {
+                debug!("using cached oauth2 token for {}", self.params.audience);
+                cached
+            }
+            None => {
+                let refresh_token = token.as_ref().and_then(|t| t.refresh_token.clone())
+                    .or_else(|| self.cache.refresh_token(key.as_str()));
+                let fresh = self.fetch(private_params.as_ref(), refresh_token).await?;
+                if let Err(e) = self.cache.put(key.as_str(), &fresh) {
+                    warn!("failed to cache oauth2 token: {}", e);
+                }
+                fresh
+            }
         };
-        let client =
-            BasicClient::new(
-                ClientId::new(private_params.client_id.clone()),
-                Some(ClientSecret::new(private_params.client_secret.clone())),
-                AuthUrl::new(private_params.issuer_url.clone())?,
-                Some(TokenUrl::new(token_url)?),
-            );
-        match client
-            .set_auth_type(RequestBody)
-            .exchange_client_credentials()
+        *token = Some(fresh.clone());
+        Ok(fresh.access_token)
+    }
+
+    async fn login(&self, prompt: &LoginPrompt) -> Result<(), Error> {
+        let private_params = self.read_private_params()?;
+        let client = self.client(private_params.as_ref()).await?;
+        let details: StandardDeviceAuthorizationResponse = client.exchange_device_code()
+            .map_err(|e| Error::Custom(format!("the issuer does not support device authorization: {}", e)))?
+            .add_scopes(self.scopes())
             .add_extra_param("audience", self.params.audience.clone())
-            .request_async(async_http_client).await {
-            Ok(token) => {
-                info!("got a oauth2 token for {}", self.params.audience);
-                Ok(token.access_token().secret().clone())
+            .request_async(|request| self.send(request)).await
+            .map_err(request_error)?;
+        prompt(details.verification_uri().as_str(), details.user_code().secret());
+        let response = client.exchange_device_access_token(&details)
+            .request_async(|request| self.send(request), tokio::time::sleep, None).await
+            .map_err(request_error)?;
+        info!("logged in to {} for {}", self.params.issuer_url, self.params.audience);
+        let fresh = cached_token(&response, None);
+        self.cache.put(self.cache_key(private_params.as_ref())?.as_str(), &fresh)?;
+        *self.token.lock().await = Some(fresh);
+        Ok(())
+    }
+}
+
+impl OAuth2Authn {
+    /// A new token, from the refresh token of a login if there is one, otherwise from the
+    /// client credentials.
+    async fn fetch(&self, private_params: Option<&OAuth2PrivateParams>, refresh_token: Option<String>)
+                   -> Result<CachedToken, Error> {
+        if let Some(refresh_token) = refresh_token {
+            match self.refresh(private_params, refresh_token).await {
+                Ok(token) => return Ok(token),
+                Err(e) => warn!("failed to refresh the oauth2 token: {}", e),
             }
-            Err(e) => Err(Error::Custom(format!("{:?}", e))),
         }
+        match private_params {
+            Some(private_params) => self.exchange(private_params).await,
+            None => Err(Error::Custom(
+                "no oauth2 credentials or login, run `pulsar-smith auth login` first".to_string())),
+        }
+    }
+
+    async fn exchange(&self, private_params: &OAuth2PrivateParams) -> Result<CachedToken, Error> {
+        let token = self.client(Some(private_params)).await?
+            .exchange_client_credentials()
+            .add_scopes(self.scopes())
+            .add_extra_param("audience", self.params.audience.clone())
+            .request_async(|request| self.send(request)).await
+            .map_err(request_error)?;
+        info!("got a oauth2 token for {}", self.params.audience);
+        Ok(cached_token(&token, None))
+    }
+
+    async fn refresh(&self, private_params: Option<&OAuth2PrivateParams>, refresh_token: String)
+                     -> Result<CachedToken, Error> {
+        let refresh_token = RefreshToken::new(refresh_token);
+        let token = self.client(private_params).await?
+            .exchange_refresh_token(&refresh_token)
+            .request_async(|request| self.send(request)).await
+            .map_err(request_error)?;
+        info!("refreshed the oauth2 token for {}", self.params.audience);
+        Ok(cached_token(&token, Some(refresh_token.secret().clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::admin::admin::PulsarAdmin;
+    use crate::auth::auth::Authn;
+    use crate::auth::cache::{CachedToken, TokenCache};
+    use crate::auth::oauth2::{OAuth2Authn, read_credentials};
+    use crate::testing::{MockResponse, MockServer, RecordedRequest};
+
+    fn token_requests(server: &MockServer) -> Vec<RecordedRequest> {
+        server.requests().into_iter().filter(|r| r.method == "POST").collect()
+    }
+
+    #[tokio::test]
+    async fn caches_tokens() {
+        // no discovery document, tokens come from oauth/token
+        let server = MockServer::start(|req, i| match req.path.as_str() {
+            "/oauth/token" => MockResponse::new(200, format!(
+                r#"{{"access_token":"token-{}","token_type":"bearer","expires_in":3600}}"#, i).as_str()),
+            _ => MockResponse::new(404, "{}"),
+        }).await;
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-oauth2-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key_file = dir.join("key.json");
+        std::fs::write(&key_file, format!(r#"{{"client_id":"c","client_secret":"s","client_email":"e","issuer_url":"{}"}}"#,
+                                          server.url())).unwrap();
+        let params = format!(r#"{{"credentials_url":"file://{}","issuer_url":"{}","audience":"urn:pulsar"}}"#,
+                             key_file.display(), server.url());
+        let cache = TokenCache::new(Some(dir.join("tokens.json")));
+
+        let authn = OAuth2Authn::new(params.as_str(), cache.clone(), reqwest::Client::new()).unwrap();
+        let first = authn.get_token().await.unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), first);
+        let requests = token_requests(&server);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/oauth/token");
+        assert!(requests[0].body.contains("grant_type=client_credentials"));
+
+        // another run reuses the persisted token
+        let authn = OAuth2Authn::new(params.as_str(), cache.clone(), reqwest::Client::new()).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), first);
+        assert_eq!(token_requests(&server).len(), 1);
+
+        // and refreshes it when it is about to expire
+        let key = TokenCache::key(server.url(), "urn:pulsar", "c");
+        cache.put(key.as_str(), &CachedToken::new("expiring".to_string(), Some(30))).unwrap();
+        let authn = OAuth2Authn::new(params.as_str(), cache, reqwest::Client::new()).unwrap();
+        assert_ne!(authn.get_token().await.unwrap(), first);
+        assert_eq!(token_requests(&server).len(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn credentials() {
+        let json = r#"{"client_id":"c","client_secret":"s"}"#;
+        assert_eq!(read_credentials(json).unwrap(), json);
+        let data = format!("data:application/json;base64,{}", base64::encode(json));
+        assert_eq!(read_credentials(data.as_str()).unwrap(), json);
+        let data = format!("data:application/json,{}", urlencoding::encode(json));
+        assert_eq!(read_credentials(data.as_str()).unwrap(), json);
+        assert!(read_credentials("https://example.com/key.json").is_err());
+        assert!(read_credentials("file:///does/not/exist").is_err());
+    }
+
+    /// An issuer with a discovery document and device authorization, on its own paths.
+    async fn issuer() -> MockServer {
+        MockServer::start(|req, _| {
+            let url = format!("http://{}", req.headers["host"]);
+            match req.path.as_str() {
+                "/.well-known/openid-configuration" => MockResponse::new(200, format!(
+                    r#"{{"issuer":"{0}","token_endpoint":"{0}/token","device_authorization_endpoint":"{0}/device"}}"#,
+                    url).as_str()),
+                "/device" => MockResponse::new(200,
+                    r#"{"device_code":"d1","user_code":"ABCD-EFGH","verification_uri":"https://issuer/activate","expires_in":600}"#),
+                "/token" if req.body.contains("grant_type=refresh_token") => MockResponse::new(200,
+                    r#"{"access_token":"refreshed","token_type":"bearer","expires_in":3600}"#),
+                "/token" if req.body.contains("device_code=d1") => MockResponse::new(200,
+                    r#"{"access_token":"logged-in","token_type":"bearer","expires_in":3600,"refresh_token":"r1"}"#),
+                "/token" => MockResponse::new(200,
+                    r#"{"access_token":"client","token_type":"bearer","expires_in":3600}"#),
+                _ => MockResponse::new(404, "{}"),
+            }
+        }).await
+    }
+
+    #[tokio::test]
+    async fn discovery_and_scope() {
+        let server = issuer().await;
+        let credentials = base64::encode(r#"{"client_id":"c","client_secret":"s"}"#);
+        let params = format!(r#"{{"privateKey":"data:application/json;base64,{}","issuerUrl":"{}/","audience":"urn:pulsar","scope":"api://pulsar/.default offline"}}"#,
+                             credentials, server.url());
+        let authn = OAuth2Authn::new(params.as_str(), TokenCache::new(None), reqwest::Client::new()).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), "client");
+        let requests = token_requests(&server);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/token");
+        assert!(requests[0].body.contains("scope=api%3A%2F%2Fpulsar%2F.default+offline"));
+        assert!(requests[0].body.contains("audience=urn%3Apulsar"));
+    }
+
+    #[tokio::test]
+    async fn issuer_behind_proxy() {
+        let proxy = MockServer::start(|req, _| match req.path.as_str() {
+            "http://issuer.invalid/.well-known/openid-configuration" =>
+                MockResponse::new(200, r#"{"token_endpoint":"http://issuer.invalid/token"}"#),
+            "http://issuer.invalid/token" =>
+                MockResponse::new(200, r#"{"access_token":"proxied","token_type":"bearer","expires_in":3600}"#),
+            _ => MockResponse::new(404, "{}"),
+        }).await;
+        let http_client = PulsarAdmin::builder("http://broker.invalid".to_string())
+            .with_proxy(proxy.url().to_string())
+            .http_client().unwrap();
+        let params = serde_json::json!({
+            "credentials_url": r#"{"client_id":"c","client_secret":"s"}"#,
+            "issuer_url": "http://issuer.invalid",
+            "audience": "urn:pulsar",
+        }).to_string();
+        let authn = OAuth2Authn::new(params.as_str(), TokenCache::new(None), http_client).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), "proxied");
+        assert_eq!(proxy.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn device_login() {
+        let server = issuer().await;
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-login-{}", std::process::id()));
+        let cache = TokenCache::new(Some(dir.join("tokens.json")));
+        let params = format!(r#"{{"issuer_url":"{}","audience":"urn:pulsar","client_id":"cli"}}"#, server.url());
+
+        let authn = OAuth2Authn::new(params.as_str(), cache.clone(), reqwest::Client::new()).unwrap();
+        assert!(authn.get_token().await.unwrap_err().to_string().contains("auth login"));
+        let prompted = Arc::new(Mutex::new(None));
+        let shown = prompted.clone();
+        authn.login(&move |uri, code| *shown.lock().unwrap() = Some((uri.to_string(), code.to_string())))
+            .await.unwrap();
+        assert_eq!(*prompted.lock().unwrap(),
+                   Some(("https://issuer/activate".to_string(), "ABCD-EFGH".to_string())));
+        assert_eq!(authn.get_token().await.unwrap(), "logged-in");
+        let requests = server.requests();
+        let device = requests.iter().find(|r| r.path == "/device").unwrap();
+        assert!(device.body.contains("client_id=cli"));
+
+        // a later command refreshes the expired token with the stored refresh token
+        let key = TokenCache::key(server.url(), "urn:pulsar", "cli");
+        assert_eq!(cache.refresh_token(key.as_str()), Some("r1".to_string()));
+        let expired = CachedToken { refresh_token: Some("r1".to_string()), ..CachedToken::new("old".to_string(), Some(0)) };
+        cache.put(key.as_str(), &expired).unwrap();
+        let authn = OAuth2Authn::new(params.as_str(), cache.clone(), reqwest::Client::new()).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), "refreshed");
+        assert!(server.requests().last().unwrap().body.contains("refresh_token=r1"));
+        assert_eq!(cache.refresh_token(key.as_str()), Some("r1".to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use openssl::nid::Nid;
+use openssl::x509::X509;
+
+use crate::auth::auth::Authn;
+use crate::auth::error::Error;
+
+/// Authentication with a client certificate, matching the broker's `tls` provider. The
+/// credentials are presented during the TLS handshake, so there is no auth data or header.
+pub(crate) struct TlsAuthn {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+}
+
+impl TlsAuthn {
+    /// Accepts the Java client's params, either `{"tlsCertFile": "...", "tlsKeyFile": "..."}`
+    /// or `tlsCertFile:...,tlsKeyFile:...`.
+    pub(crate) fn create(auth_params: &str) -> Result<Box<dyn Authn>, Error> {
+        let auth_params = auth_params.trim();
+        let params: HashMap<String, String> = if auth_params.starts_with('{') {
+            serde_json::from_str(auth_params)?
+        } else {
+            auth_params.split(',')
+                .filter_map(|p| p.split_once(':'))
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .collect()
+        };
+        let param = |name: &str| params.get(name)
+            .map(|p| PathBuf::from(p.strip_prefix("file://").unwrap_or(p)))
+            .ok_or_else(|| Error::Custom(format!("{} is required for tls auth", name)));
+        Ok(Box::new(TlsAuthn {
+            cert_file: param("tlsCertFile")?,
+            key_file: param("tlsKeyFile")?,
+        }))
+    }
+}
+
+#[async_trait]
+impl Authn for TlsAuthn {
+    fn auth_method_name(&self) -> String {
+        "tls".to_string()
+    }
+
+    async fn initialize(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn auth_data(&self) -> Result<Vec<u8>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn http_headers(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// The common name of the certificate, which the broker takes as the role.
+    async fn role(&self) -> Result<Option<String>, Error> {
+        let cert = X509::from_pem(std::fs::read(&self.cert_file)?.as_slice())?;
+        Ok(cert.subject_name().entries_by_nid(Nid::COMMONNAME).next()
+            .and_then(|cn| cn.data().as_utf8().ok())
+            .map(|cn| cn.to_string()))
+    }
+
+    fn tls_identity(&self) -> Option<(PathBuf, PathBuf)> {
+        Some((self.cert_file.clone(), self.key_file.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::auth::tls::TlsAuthn;
+
+    #[test]
+    fn params() {
+        let expected = Some((PathBuf::from("/certs/client.pem"), PathBuf::from("/certs/client.key")));
+        let authn = TlsAuthn::create(r#"{"tlsCertFile":"/certs/client.pem","tlsKeyFile":"/certs/client.key"}"#).unwrap();
+        assert_eq!(authn.tls_identity(), expected);
+        let authn = TlsAuthn::create("tlsCertFile:/certs/client.pem,tlsKeyFile:file:///certs/client.key").unwrap();
+        assert_eq!(authn.tls_identity(), expected);
+        assert!(TlsAuthn::create("tlsCertFile:/certs/client.pem").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::auth::auth::Authn;
+use crate::auth::cache::{CachedToken, now};
+use crate::auth::error::Error;
+
+/// Where a token comes from, given by the auth params:
+/// - `file:///path/to/token`, re-read whenever the file changes, e.g. a rotated Kubernetes secret
+/// - `env:VAR`
+/// - `exec:command args`, run with `sh -c` and printing `{"token": "...", "expiry": "<RFC 3339>"}`
+///   or just the token; the token is cached until shortly before it expires, or for
+///   `EXEC_TOKEN_TTL_SECS` without an expiry so that rotated credentials are picked up
+/// - `token:value` or the token itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenSource {
+    Literal(String),
+    File(PathBuf),
+    Env(String),
+    Exec(String),
+}
+
+impl TokenSource {
+    fn parse(params: &str) -> TokenSource {
+        let params = params.trim();
+        if let Some(path) = params.strip_prefix("file://") {
+            TokenSource::File(PathBuf::from(path))
+        } else if let Some(var) = params.strip_prefix("env:") {
+            TokenSource::Env(var.to_string())
+        } else if let Some(command) = params.strip_prefix("exec:") {
+            TokenSource::Exec(command.to_string())
+        } else if let Some(token) = params.strip_prefix("token:") {
+            TokenSource::Literal(token.to_string())
+        } else {
+            TokenSource::Literal(params.to_string())
+        }
+    }
+}
+
+/// How long a token printed without an expiry by an exec credential plugin is used.
+const EXEC_TOKEN_TTL_SECS: u64 = 300;
+
+/// Output of an exec credential plugin.
+#[derive(Deserialize, Debug)]
+struct ExecCredential {
+    #[serde(alias = "access_token")]
+    token: String,
+    #[serde(default, alias = "expirationTimestamp")]
+    expiry: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+pub(crate) struct TokenAuthn {
+    source: TokenSource,
+    /// The last token read from a file or command, with the file's modification time.
+    cached: Mutex<Option<(Option<SystemTime>, CachedToken)>>,
+}
+
+impl TokenAuthn {
+    pub(crate) fn create(auth_params: &str) -> Result<Box<dyn Authn>, Error> {
+        Ok(Box::new(TokenAuthn {
+            source: TokenSource::parse(auth_params),
+            cached: Mutex::new(None),
+        }))
+    }
+
+    async fn read_file(&self, path: &PathBuf) -> Result<String, Error> {
+        let modified = std::fs::metadata(path)
+            .map_err(|e| Error::Custom(format!("failed to read token file {}: {}", path.display(), e)))?
+            .modified().ok();
+        let mut cached = self.cached.lock().await;
+        if let Some((Some(time), token)) = cached.as_ref() {
+            if modified == Some(*time) {
+                return Ok(token.access_token.clone());
+            }
+        }
+        let token = std::fs::read_to_string(path)?.trim().to_string();
+        debug!("read token from {}", path.display());
+        *cached = Some((modified, CachedToken::new(token.clone(), None)));
+        Ok(token)
+    }
+
+    async fn exec(&self, command: &str) -> Result<String, Error> {
+        let mut cached = self.cached.lock().await;
+        if let Some((_, token)) = cached.as_ref().filter(|(_, t)| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output().await
+            .map_err(|e| Error::Custom(format!("failed to run [{}]: {}", command, e)))?;
+        if !output.status.success() {
+            return Err(Error::Custom(format!("[{}] failed with {}: {}", command, output.status,
+                                             String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        let token = parse_exec_output(String::from_utf8_lossy(&output.stdout).trim())?;
+        info!("got a token from [{}]", command);
+        *cached = Some((None, token.clone()));
+        Ok(token.access_token)
+    }
+}
+
+fn parse_exec_output(stdout: &str) -> Result<CachedToken, Error> {
+    if !stdout.starts_with('{') {
+        return Ok(CachedToken::new(stdout.to_string(), Some(EXEC_TOKEN_TTL_SECS)));
+    }
+    let credential: ExecCredential = serde_json::from_str(stdout)?;
+    let expires_at = match (&credential.expiry, credential.expires_in) {
+        (Some(expiry), _) => {
+            let expiry = chrono::DateTime::parse_from_rfc3339(expiry.as_str())
+                .map_err(|e| Error::Custom(format!("invalid expiry [{}]: {}", expiry, e)))?;
+            Some(expiry.timestamp().max(0) as u64)
+        }
+        (None, Some(expires_in)) => Some(now() + expires_in),
+        (None, None) => Some(now() + EXEC_TOKEN_TTL_SECS),
+    };
+    Ok(CachedToken {
+        access_token: credential.token,
+        expires_at,
+        refresh_token: None,
+    })
+}
+
+#[async_trait]
+impl Authn for TokenAuthn {
+    fn auth_method_name(&self) -> String {
+        "token".to_string()
+    }
+
+    async fn initialize(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn get_token(&self) -> Result<String, Error> {
+        match &self.source {
+            TokenSource::Literal(token) => Ok(token.clone()),
+            TokenSource::File(path) => self.read_file(path).await,
+            TokenSource::Env(var) => std::env::var(var)
+                .map(|token| token.trim().to_string())
+                .map_err(|_| Error::Custom(format!("environment variable [{}] is not set", var))),
+            TokenSource::Exec(command) => self.exec(command).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use tokio::sync::Mutex;
+
+    use crate::auth::auth::Authn;
+    use crate::auth::cache::now;
+    use crate::auth::token::{parse_exec_output, TokenAuthn, TokenSource, EXEC_TOKEN_TTL_SECS};
+
+    #[test]
+    fn sources() {
+        assert_eq!(TokenSource::parse("abc"), TokenSource::Literal("abc".to_string()));
+        assert_eq!(TokenSource::parse("token:abc"), TokenSource::Literal("abc".to_string()));
+        assert_eq!(TokenSource::parse("file:///var/run/token"), TokenSource::File("/var/run/token".into()));
+        assert_eq!(TokenSource::parse("env:PULSAR_TOKEN"), TokenSource::Env("PULSAR_TOKEN".to_string()));
+        assert_eq!(TokenSource::parse("exec:vault read -field=token x"),
+                   TokenSource::Exec("vault read -field=token x".to_string()));
+    }
+
+    #[test]
+    fn exec_output() {
+        let token = parse_exec_output(r#"{"token":"abc","expiry":"2030-01-01T00:00:00Z"}"#).unwrap();
+        assert_eq!(token.access_token, "abc");
+        assert_eq!(token.expires_at, Some(1893456000));
+        let expires_at = parse_exec_output("abc").unwrap().expires_at.unwrap();
+        assert!(expires_at >= now() + EXEC_TOKEN_TTL_SECS - 1 && expires_at <= now() + EXEC_TOKEN_TTL_SECS);
+        assert!(parse_exec_output(r#"{"token":"abc"}"#).unwrap().expires_at.is_some());
+        assert!(parse_exec_output(r#"{"expiry":"2030-01-01T00:00:00Z"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn file_and_exec_tokens() {
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-token-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("token");
+        std::fs::write(&path, "t1\n").unwrap();
+        let authn = TokenAuthn::create(format!("file://{}", path.display()).as_str()).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), "t1");
+        std::fs::write(&path, "t2").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        assert_eq!(authn.get_token().await.unwrap(), "t2");
+
+        let counter = dir.join("runs");
+        let command = format!(r#"exec:echo run >> {}; echo '{{"token":"t3","expires_in":3600}}'"#, counter.display());
+        let authn = TokenAuthn {
+            source: TokenSource::parse(command.as_str()),
+            cached: Mutex::new(None),
+        };
+        assert_eq!(authn.get_token().await.unwrap(), "t3");
+        assert_eq!(authn.get_token().await.unwrap(), "t3");
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().lines().count(), 1);
+        authn.cached.lock().await.as_mut().unwrap().1.expires_at = Some(now());
+        assert_eq!(authn.get_token().await.unwrap(), "t3");
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().lines().count(), 2);
+
+        assert!(TokenAuthn::create("exec:exit 3").unwrap().get_token().await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use clap::Parser;
+
+use crate::cmd::cmd::AsyncCmd;
+use crate::context::PulsarContext;
+use crate::error::Error;
+use crate::manifest::Manifest;
+use crate::manifest::plan::Plan;
+
+/// Change tenants, namespaces, topics and subscriptions where they differ from a manifest
+#[derive(Parser, Debug, Clone)]
+pub struct ApplyOpts {
+    /// Manifest of tenants, namespaces, topics and subscriptions, `-` for stdin
+    #[arg(short = 'f', long = "filename", required = true)]
+    pub filenames: Vec<String>,
+
+    /// Delete namespaces, topics and subscriptions the manifest does not declare
+    #[arg(long, default_value = "false")]
+    pub prune: bool,
+
+    /// Only print the changes
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+
+    /// Delete subscriptions and topics even if they have producers or consumers connected
+    #[arg(long, default_value = "false", requires = "prune")]
+    pub force: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for ApplyOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let manifest = Manifest::read(&self.filenames)?;
+        let output = pulsar_ctx.output();
+        let admin = pulsar_ctx.admin().await?;
+        let plan = Plan::compute(admin, &manifest, self.prune).await?;
+        if plan.is_empty() {
+            output.message("No changes");
+            return Ok(());
+        }
+        if self.dry_run {
+            output.render(&plan)?;
+            return Ok(());
+        }
+        let (report, result) = plan.apply(admin, self.force).await;
+        output.render(&report)?;
+        result?;
+        output.message(format!("Applied {} changes", plan.changes.len()).as_str());
+        Ok(())
+    }
+}
+
+/// Show what apply would change
+#[derive(Parser, Debug, Clone)]
+pub struct DiffOpts {
+    /// Manifest of tenants, namespaces, topics and subscriptions, `-` for stdin
+    #[arg(short = 'f', long = "filename", required = true)]
+    pub filenames: Vec<String>,
+
+    /// Include deletes of namespaces, topics and subscriptions the manifest does not declare
+    #[arg(long, default_value = "false")]
+    pub prune: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for DiffOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let manifest = Manifest::read(&self.filenames)?;
+        let output = pulsar_ctx.output();
+        let plan = Plan::compute(pulsar_ctx.admin().await?, &manifest, self.prune).await?;
+        if plan.is_empty() {
+            output.message("No changes");
+            return Ok(());
+        }
+        output.render(&plan)?;
+        Ok(())
+    }
+}
@@ -1,9 +1,15 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
-use clap::Parser;
+use clap::{Args, Parser};
 
+use crate::admin::clusters::{AutoFailoverPolicy, AutoFailoverPolicyType, ClusterData, FailureDomain,
+                             NamespaceIsolationData, ProxyProtocol};
+use crate::admin::error::Error as AdminError;
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
 use crate::error::Error;
+use crate::output::{Render, Table};
 
 #[derive(Parser, Debug, Clone)]
 pub struct ClustersOpts {
@@ -16,6 +22,21 @@ impl AsyncCmd for ClustersOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
         let cmd: &dyn AsyncCmd = match &self.cmd {
             Command::List(opts) => opts,
+            Command::Get(opts) => opts,
+            Command::Create(opts) => opts,
+            Command::Update(opts) => opts,
+            Command::Delete(opts) => opts,
+            Command::GetPeerClusters(opts) => opts,
+            Command::UpdatePeerClusters(opts) => opts,
+            Command::ListFailureDomains(opts) => opts,
+            Command::GetFailureDomain(opts) => opts,
+            Command::CreateFailureDomain(opts) => opts,
+            Command::UpdateFailureDomain(opts) => opts,
+            Command::DeleteFailureDomain(opts) => opts,
+            Command::ListIsolationPolicies(opts) => opts,
+            Command::GetIsolationPolicy(opts) => opts,
+            Command::SetIsolationPolicy(opts) => opts,
+            Command::DeleteIsolationPolicy(opts) => opts,
         };
         cmd.run(pulsar_ctx).await?;
         Ok(())
@@ -25,6 +46,24 @@ impl AsyncCmd for ClustersOpts {
 #[derive(Parser, Debug, Clone)]
 pub enum Command {
     List(ListOpts),
+    Get(GetOpts),
+    Create(CreateOpts),
+    /// Update the given settings of a cluster, keeping the others
+    Update(UpdateOpts),
+    Delete(DeleteOpts),
+    GetPeerClusters(GetPeerClustersOpts),
+    /// Replace the peer clusters of a cluster
+    UpdatePeerClusters(UpdatePeerClustersOpts),
+    ListFailureDomains(ListFailureDomainsOpts),
+    GetFailureDomain(GetFailureDomainOpts),
+    CreateFailureDomain(CreateFailureDomainOpts),
+    UpdateFailureDomain(UpdateFailureDomainOpts),
+    DeleteFailureDomain(DeleteFailureDomainOpts),
+    ListIsolationPolicies(ListIsolationPoliciesOpts),
+    GetIsolationPolicy(GetIsolationPolicyOpts),
+    /// Create or replace a namespace isolation policy
+    SetIsolationPolicy(SetIsolationPolicyOpts),
+    DeleteIsolationPolicy(DeleteIsolationPolicyOpts),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -37,7 +76,465 @@ impl AsyncCmd for ListOpts {
             .clusters()
             .list()
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetOpts {
+    pub cluster: String,
+}
+
+#[async_trait]
+impl AsyncCmd for GetOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .get(self.cluster.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+// The settings of `create` and `update`, named like `pulsar-admin clusters`.
+#[derive(Args, Debug, Clone)]
+pub struct ClusterDataOpts {
+    /// Web service URL, e.g. http://broker:8080
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Web service URL with TLS, e.g. https://broker:8443
+    #[arg(long)]
+    pub url_secure: Option<String>,
+
+    /// Broker service URL, e.g. pulsar://broker:6650
+    #[arg(long)]
+    pub broker_url: Option<String>,
+
+    /// Broker service URL with TLS, e.g. pulsar+ssl://broker:6651
+    #[arg(long)]
+    pub broker_url_secure: Option<String>,
+
+    /// Proxy service URL, to reach the brokers through a proxy
+    #[arg(long)]
+    pub proxy_url: Option<String>,
+
+    #[arg(long)]
+    pub proxy_protocol: Option<ProxyProtocol>,
+
+    /// Authentication plugin the brokers use to connect to this cluster
+    #[arg(long)]
+    pub auth_plugin: Option<String>,
+
+    #[arg(long)]
+    pub auth_parameters: Option<String>,
+
+    #[arg(long)]
+    pub tls_enable: Option<bool>,
+
+    #[arg(long)]
+    pub tls_allow_insecure: Option<bool>,
+
+    #[arg(long)]
+    pub tls_trust_certs_filepath: Option<String>,
+
+    /// Listener the brokers use to connect to this cluster
+    #[arg(long)]
+    pub listener_name: Option<String>,
+}
+
+impl ClusterDataOpts {
+    /// Overwrites the settings given on the command line.
+    fn apply(&self, data: &mut ClusterData) {
+        fn set<T: Clone>(field: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                *field = value.clone();
+            }
+        }
+        set(&mut data.service_url, &self.url);
+        set(&mut data.service_url_tls, &self.url_secure);
+        set(&mut data.broker_service_url, &self.broker_url);
+        set(&mut data.broker_service_url_tls, &self.broker_url_secure);
+        set(&mut data.proxy_service_url, &self.proxy_url);
+        set(&mut data.proxy_protocol, &self.proxy_protocol);
+        set(&mut data.authentication_plugin, &self.auth_plugin);
+        set(&mut data.authentication_parameters, &self.auth_parameters);
+        set(&mut data.broker_client_tls_enabled, &self.tls_enable);
+        set(&mut data.tls_allow_insecure_connection, &self.tls_allow_insecure);
+        set(&mut data.broker_client_trust_certs_file_path, &self.tls_trust_certs_filepath);
+        set(&mut data.listener_name, &self.listener_name);
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct CreateOpts {
+    pub cluster: String,
+
+    #[command(flatten)]
+    pub data: ClusterDataOpts,
+}
+
+#[async_trait]
+impl AsyncCmd for CreateOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let mut data = ClusterData::default();
+        self.data.apply(&mut data);
+        pulsar_ctx.admin().await?
+            .clusters()
+            .create(self.cluster.as_str(), &data)
+            .await?;
+        pulsar_ctx.output().message(format!("Created cluster {}", self.cluster).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UpdateOpts {
+    pub cluster: String,
+
+    #[command(flatten)]
+    pub data: ClusterDataOpts,
+}
+
+#[async_trait]
+impl AsyncCmd for UpdateOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let admin = pulsar_ctx.admin().await?;
+        // the broker replaces the whole cluster data, so start from the current one
+        let mut data = admin.clusters().get(self.cluster.as_str()).await?;
+        self.data.apply(&mut data);
+        admin.clusters().update(self.cluster.as_str(), &data).await?;
+        pulsar_ctx.output().message(format!("Updated cluster {}", self.cluster).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct DeleteOpts {
+    pub cluster: String,
+
+    /// Delete the failure domains and namespace isolation policies of the cluster too
+    #[arg(short = 'a', long)]
+    pub all: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for DeleteOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let admin = pulsar_ctx.admin().await?;
+        let clusters = admin.clusters();
+        let cluster = self.cluster.as_str();
+        if self.all {
+            for policy in clusters.namespace_isolation_policies(cluster).await?.keys() {
+                clusters.delete_namespace_isolation_policy(cluster, policy).await?;
+            }
+            for domain in clusters.failure_domains(cluster).await?.keys() {
+                clusters.delete_failure_domain(cluster, domain).await?;
+            }
+        }
+        clusters.delete(cluster).await?;
+        pulsar_ctx.output().message(format!("Deleted cluster {}", self.cluster).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetPeerClustersOpts {
+    pub cluster: String,
+}
+
+#[async_trait]
+impl AsyncCmd for GetPeerClustersOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .peer_clusters(self.cluster.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UpdatePeerClustersOpts {
+    pub cluster: String,
+
+    /// Peer cluster, repeat for several; none to remove all peers
+    #[arg(long = "peer-cluster")]
+    pub peer_clusters: Vec<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for UpdatePeerClustersOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        pulsar_ctx.admin().await?
+            .clusters()
+            .update_peer_clusters(self.cluster.as_str(), &self.peer_clusters)
+            .await?;
+        pulsar_ctx.output().message(format!("Updated peer clusters of {}", self.cluster).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ListFailureDomainsOpts {
+    pub cluster: String,
+}
+
+#[async_trait]
+impl AsyncCmd for ListFailureDomainsOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .failure_domains(self.cluster.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetFailureDomainOpts {
+    pub cluster: String,
+
+    pub domain: String,
+}
+
+#[async_trait]
+impl AsyncCmd for GetFailureDomainOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .failure_domain(self.cluster.as_str(), self.domain.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct CreateFailureDomainOpts {
+    pub cluster: String,
+
+    pub domain: String,
+
+    /// Broker in the domain, repeat for several
+    #[arg(short = 'b', long = "broker")]
+    pub brokers: Vec<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for CreateFailureDomainOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let admin = pulsar_ctx.admin().await?;
+        // the broker creates and updates domains alike, so check here to not replace one
+        match admin.clusters().failure_domain(self.cluster.as_str(), self.domain.as_str()).await {
+            Ok(_) => return Err(AdminError::Conflict(
+                format!("failure domain {} already exists in cluster {}", self.domain, self.cluster)).into()),
+            Err(AdminError::NotFound(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        admin.clusters()
+            .set_failure_domain(self.cluster.as_str(), self.domain.as_str(),
+                                &FailureDomain { brokers: self.brokers.clone() })
+            .await?;
+        pulsar_ctx.output().message(format!("Created failure domain {}", self.domain).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UpdateFailureDomainOpts {
+    pub cluster: String,
+
+    pub domain: String,
+
+    /// Broker in the domain, repeat for several; replaces the current brokers
+    #[arg(short = 'b', long = "broker")]
+    pub brokers: Vec<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for UpdateFailureDomainOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let admin = pulsar_ctx.admin().await?;
+        admin.clusters().failure_domain(self.cluster.as_str(), self.domain.as_str()).await?;
+        admin.clusters()
+            .set_failure_domain(self.cluster.as_str(), self.domain.as_str(),
+                                &FailureDomain { brokers: self.brokers.clone() })
+            .await?;
+        pulsar_ctx.output().message(format!("Updated failure domain {}", self.domain).as_str());
         Ok(())
     }
 }
+
+#[derive(Parser, Debug, Clone)]
+pub struct DeleteFailureDomainOpts {
+    pub cluster: String,
+
+    pub domain: String,
+}
+
+#[async_trait]
+impl AsyncCmd for DeleteFailureDomainOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        pulsar_ctx.admin().await?
+            .clusters()
+            .delete_failure_domain(self.cluster.as_str(), self.domain.as_str())
+            .await?;
+        pulsar_ctx.output().message(format!("Deleted failure domain {}", self.domain).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ListIsolationPoliciesOpts {
+    pub cluster: String,
+}
+
+#[async_trait]
+impl AsyncCmd for ListIsolationPoliciesOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .namespace_isolation_policies(self.cluster.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetIsolationPolicyOpts {
+    pub cluster: String,
+
+    pub policy: String,
+}
+
+#[async_trait]
+impl AsyncCmd for GetIsolationPolicyOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let r = pulsar_ctx.admin().await?
+            .clusters()
+            .namespace_isolation_policy(self.cluster.as_str(), self.policy.as_str())
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetIsolationPolicyOpts {
+    pub cluster: String,
+
+    pub policy: String,
+
+    /// Namespace regex the policy applies to, repeat for several
+    #[arg(long = "namespace", required = true)]
+    pub namespaces: Vec<String>,
+
+    /// Broker regex of the primary brokers, repeat for several
+    #[arg(long = "primary", required = true)]
+    pub primary: Vec<String>,
+
+    /// Broker regex of the secondary brokers, repeat for several
+    #[arg(long = "secondary")]
+    pub secondary: Vec<String>,
+
+    #[arg(long, requires = "auto_failover_policy_params")]
+    pub auto_failover_policy_type: Option<AutoFailoverPolicyType>,
+
+    /// Policy parameter, e.g. min_limit=1 or usage_threshold=80, repeat for several
+    #[arg(long, value_parser = parse_param)]
+    pub auto_failover_policy_params: Vec<(String, String)>,
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    let (name, value) = param.split_once('=')
+        .ok_or_else(|| format!("invalid parameter [{}], expected name=value", param))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+impl From<&SetIsolationPolicyOpts> for NamespaceIsolationData {
+    fn from(opts: &SetIsolationPolicyOpts) -> Self {
+        NamespaceIsolationData {
+            namespaces: opts.namespaces.clone(),
+            primary: opts.primary.clone(),
+            secondary: opts.secondary.clone(),
+            auto_failover_policy: opts.auto_failover_policy_type.map(|policy_type| AutoFailoverPolicy {
+                policy_type,
+                parameters: opts.auto_failover_policy_params.iter().cloned().collect(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCmd for SetIsolationPolicyOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        pulsar_ctx.admin().await?
+            .clusters()
+            .set_namespace_isolation_policy(self.cluster.as_str(), self.policy.as_str(), &self.into())
+            .await?;
+        pulsar_ctx.output().message(format!("Set namespace isolation policy {}", self.policy).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct DeleteIsolationPolicyOpts {
+    pub cluster: String,
+
+    pub policy: String,
+}
+
+#[async_trait]
+impl AsyncCmd for DeleteIsolationPolicyOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        pulsar_ctx.admin().await?
+            .clusters()
+            .delete_namespace_isolation_policy(self.cluster.as_str(), self.policy.as_str())
+            .await?;
+        pulsar_ctx.output().message(format!("Deleted namespace isolation policy {}", self.policy).as_str());
+        Ok(())
+    }
+}
+
+impl Render for ClusterData {}
+
+impl Render for FailureDomain {}
+
+impl Render for NamespaceIsolationData {}
+
+impl Render for BTreeMap<String, FailureDomain> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["DOMAIN", "BROKERS"]);
+        for (domain, data) in self {
+            table.add_row(vec![domain.clone(), data.brokers.join(",")]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for BTreeMap<String, NamespaceIsolationData> {
+    fn tables(&self, wide: bool) -> Vec<Table> {
+        let mut headers = vec!["POLICY", "NAMESPACES", "PRIMARY", "SECONDARY"];
+        if wide {
+            headers.push("FAILOVER");
+        }
+        let mut table = Table::new(headers.as_slice());
+        for (policy, data) in self {
+            let mut row = vec![policy.clone(), data.namespaces.join(","), data.primary.join(","),
+                               data.secondary.join(",")];
+            if wide {
+                row.push(data.auto_failover_policy.as_ref()
+                    .map(|p| serde_json::to_value(p).unwrap_or_default().to_string())
+                    .unwrap_or_default());
+            }
+            table.add_row(row);
+        }
+        vec![table]
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::PulsarConfig;
 use crate::error::Error;
 use pulsar::{ProducerOptions, TokioExecutor, Pulsar};
 use pulsar::producer::ProducerBuilder;
@@ -23,6 +24,13 @@ pub struct ProducerOpts {
 }
 
 impl ProducerOpts {
+    /// Returns a copy with the topic expanded to its fully qualified name.
+    pub fn resolve(&self, cfg: &PulsarConfig) -> Result<ProducerOpts, Error> {
+        let mut opts = self.clone();
+        opts.topic = cfg.resolve_topic(&self.topic)?.to_string();
+        Ok(opts)
+    }
+
     pub fn parse_batch_size(&self) -> Option<u32> {
         if self.batch_size <= 0 {
             None
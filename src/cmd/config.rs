@@ -0,0 +1,463 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use serde::Serialize;
+
+use crate::config::Configs;
+use crate::error::Error;
+use crate::output::{Output, OutputFormat, Render, Table};
+
+#[derive(Parser, Debug, Clone)]
+pub struct ConfigOpts {
+    #[command(subcommand)]
+    pub cmd: Command,
+}
+
+impl ConfigOpts {
+    /// Config commands only touch the config files, so they run without a `PulsarContext`.
+    /// Reads see all files merged. Like kubectl, changes are written to the file defining the
+    /// changed entry, and new entries and the current context to the first file.
+    pub fn run(&self, config_paths: &[PathBuf], output: Output) -> Result<(), Error> {
+        let merged = Configs::load_merged(config_paths)?;
+        let config_path = match &self.cmd {
+            Command::SetCluster(opts) => defining_path(config_paths, |c| c.has_cluster(opts.name.as_str()))?,
+            Command::SetUser(opts) => defining_path(config_paths, |c| c.has_user(opts.name.as_str()))?,
+            Command::SetContext(opts) => defining_path(config_paths, |c| c.has_context(opts.name.as_str()))?,
+            Command::DeleteContext(opts) => defining_path(config_paths, |c| c.has_context(opts.name.as_str()))?,
+            Command::RenameContext(opts) => defining_path(config_paths, |c| c.has_context(opts.name.as_str()))?,
+            _ => &config_paths[0],
+        };
+        let mut configs = Configs::load_or_default(config_path)?;
+        let modified = match &self.cmd {
+            Command::View(opts) => opts.run(&merged, output)?,
+            Command::GetContexts(opts) => opts.run(&merged, output)?,
+            Command::UseContext(opts) => opts.run(&merged, &mut configs, output)?,
+            Command::SetCluster(opts) => opts.run(&mut configs, output)?,
+            Command::SetUser(opts) => opts.run(&mut configs, output)?,
+            Command::SetContext(opts) => opts.run(&mut configs, output)?,
+            Command::DeleteContext(opts) => opts.run(&mut configs, output)?,
+            Command::RenameContext(opts) => opts.run(&merged, &mut configs, output)?,
+        };
+        if modified {
+            configs.save_to(config_path)?;
+        }
+
+        // the current context may be set in another file than the context itself
+        let (name, new_name) = match &self.cmd {
+            Command::DeleteContext(opts) => (opts.name.as_str(), None),
+            Command::RenameContext(opts) => (opts.name.as_str(), Some(opts.new_name.as_str())),
+            _ => return Ok(()),
+        };
+        if merged.current_context() == Some(name) {
+            let current_path = defining_path(config_paths, |c| c.current_context().is_some())?;
+            if current_path != config_path {
+                let mut configs = Configs::load_from(current_path)?;
+                match new_name {
+                    Some(new_name) => configs.set_current_context(new_name),
+                    None => configs.unset_current_context(),
+                }
+                configs.save_to(current_path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The first of the existing files `defines` matches, or the first file if there is none.
+fn defining_path(config_paths: &[PathBuf], defines: impl Fn(&Configs) -> bool) -> Result<&PathBuf, Error> {
+    for path in config_paths {
+        if path.exists() && defines(&Configs::load_from(path)?) {
+            return Ok(path);
+        }
+    }
+    Ok(&config_paths[0])
+}
+
+#[derive(Parser, Debug, Clone)]
+pub enum Command {
+    View(ViewOpts),
+    GetContexts(GetContextsOpts),
+    UseContext(UseContextOpts),
+    SetCluster(SetClusterOpts),
+    SetUser(SetUserOpts),
+    SetContext(SetContextOpts),
+    DeleteContext(DeleteContextOpts),
+    RenameContext(RenameContextOpts),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ViewOpts {
+    /// Show auth params instead of redacting them
+    #[arg(long)]
+    pub raw: bool,
+}
+
+impl ViewOpts {
+    fn run(&self, configs: &Configs, output: Output) -> Result<bool, Error> {
+        let mut value = serde_yaml::to_value(configs)?;
+        if !self.raw {
+            if let Some(users) = value.get_mut("users").and_then(|v| v.as_sequence_mut()) {
+                for user in users.iter_mut() {
+                    if let Some(params) = user.get_mut("user").and_then(|v| v.get_mut("auth-params")) {
+                        *params = serde_yaml::Value::String("REDACTED".to_string());
+                    }
+                }
+            }
+        }
+        match output.format() {
+            OutputFormat::Json | OutputFormat::Yaml => output.render(&serde_json::to_value(&value)?)?,
+            _ => print!("{}", serde_yaml::to_string(&value)?),
+        }
+        Ok(false)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetContextsOpts {}
+
+impl GetContextsOpts {
+    fn run(&self, configs: &Configs, output: Output) -> Result<bool, Error> {
+        let contexts: Vec<ContextView> = configs.contexts().iter()
+            .map(|item| ContextView {
+                current: configs.current_context() == Some(item.name.as_str()),
+                name: item.name.clone(),
+                cluster: item.context.cluster.clone(),
+                user: item.context.user.clone(),
+                tenant: item.context.tenant.clone(),
+                namespace: item.context.namespace.clone(),
+            })
+            .collect();
+        output.render(&contexts)?;
+        Ok(false)
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ContextView {
+    current: bool,
+    name: String,
+    cluster: String,
+    user: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tenant: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+}
+
+impl Render for Vec<ContextView> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["CURRENT", "NAME", "CLUSTER", "USER", "NAMESPACE"]);
+        for item in self {
+            let namespace = match (&item.tenant, &item.namespace) {
+                (Some(tenant), Some(namespace)) if !namespace.contains('/') => format!("{}/{}", tenant, namespace),
+                (_, Some(namespace)) => namespace.clone(),
+                (Some(tenant), None) => format!("{}/", tenant),
+                (None, None) => String::new(),
+            };
+            let current = if item.current { "*" } else { "" };
+            table.add_row(vec![current.to_string(), item.name.clone(), item.cluster.clone(), item.user.clone(),
+                               namespace]);
+        }
+        vec![table]
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UseContextOpts {
+    pub name: String,
+}
+
+impl UseContextOpts {
+    fn run(&self, merged: &Configs, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        if !merged.has_context(self.name.as_str()) {
+            return Err(Error::Custom(format!("context [{}] not exist", self.name)));
+        }
+        configs.set_current_context(self.name.as_str());
+        output.message(format!("Switched to context \"{}\".", self.name).as_str());
+        Ok(true)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetClusterOpts {
+    pub name: String,
+
+    #[arg(long)]
+    pub url: Option<String>,
+
+    #[arg(long)]
+    pub admin_url: Option<String>,
+
+    #[arg(long)]
+    pub allow_insecure_connection: Option<bool>,
+
+    #[arg(long)]
+    pub tls_hostname_verification_enabled: Option<bool>,
+
+    #[arg(long)]
+    pub tls_trust_certs_file_path: Option<String>,
+
+    #[arg(long)]
+    pub tls_cert_file_path: Option<String>,
+
+    #[arg(long)]
+    pub tls_key_file_path: Option<String>,
+
+    #[arg(long)]
+    pub proxy_url: Option<String>,
+
+    /// Timeout of admin requests in seconds
+    #[arg(long)]
+    pub request_timeout: Option<u64>,
+
+    /// Timeout of connecting to the admin service in seconds
+    #[arg(long)]
+    pub connect_timeout: Option<u64>,
+
+    /// Retries of failed admin requests
+    #[arg(long)]
+    pub max_retries: Option<u32>,
+}
+
+impl SetClusterOpts {
+    fn run(&self, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        let cluster = configs.cluster_mut(self.name.as_str());
+        if let Some(url) = &self.url {
+            cluster.url = url.clone();
+        }
+        if let Some(admin_url) = &self.admin_url {
+            cluster.admin_service_url = Some(admin_url.clone());
+        }
+        if let Some(allow) = self.allow_insecure_connection {
+            cluster.allow_insecure_connection = allow;
+        }
+        if let Some(enabled) = self.tls_hostname_verification_enabled {
+            cluster.tls_hostname_verification_enabled = enabled;
+        }
+        if let Some(path) = &self.tls_trust_certs_file_path {
+            cluster.tls_trust_certs_file_path = Some(path.clone());
+        }
+        if let Some(path) = &self.tls_cert_file_path {
+            cluster.tls_cert_file_path = Some(path.clone());
+        }
+        if let Some(path) = &self.tls_key_file_path {
+            cluster.tls_key_file_path = Some(path.clone());
+        }
+        if let Some(proxy_url) = &self.proxy_url {
+            cluster.proxy_url = Some(proxy_url.clone());
+        }
+        if let Some(timeout) = self.request_timeout {
+            cluster.request_timeout = Some(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            cluster.connect_timeout = Some(timeout);
+        }
+        if let Some(max_retries) = self.max_retries {
+            cluster.max_retries = Some(max_retries);
+        }
+        if cluster.url.is_empty() {
+            return Err(Error::Custom(format!("url is required for cluster [{}]", self.name)));
+        }
+        if cluster.admin_service_url.is_none() {
+            return Err(Error::Custom(format!("admin-url is required for cluster [{}]", self.name)));
+        }
+        output.message(format!("Cluster \"{}\" set.", self.name).as_str());
+        Ok(true)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetUserOpts {
+    pub name: String,
+
+    /// Auth method: token, oauth2, basic or tls. tls only works for admin commands, as the pulsar
+    /// client cannot present client certificates
+    #[arg(long)]
+    pub auth_name: Option<String>,
+
+    #[arg(long)]
+    pub auth_params: Option<String>,
+}
+
+impl SetUserOpts {
+    fn run(&self, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        let user = configs.user_mut(self.name.as_str());
+        if let Some(auth_name) = &self.auth_name {
+            user.auth_name = Some(auth_name.clone());
+        }
+        if let Some(auth_params) = &self.auth_params {
+            user.auth_params = Some(auth_params.clone());
+        }
+        output.message(format!("User \"{}\" set.", self.name).as_str());
+        Ok(true)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetContextOpts {
+    pub name: String,
+
+    #[arg(long)]
+    pub cluster: Option<String>,
+
+    #[arg(long)]
+    pub user: Option<String>,
+
+    #[arg(long)]
+    pub tenant: Option<String>,
+
+    #[arg(long)]
+    pub namespace: Option<String>,
+}
+
+impl SetContextOpts {
+    fn run(&self, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        let context = configs.context_mut(self.name.as_str());
+        if let Some(cluster) = &self.cluster {
+            context.cluster = cluster.clone();
+        }
+        if let Some(user) = &self.user {
+            context.user = user.clone();
+        }
+        if let Some(tenant) = &self.tenant {
+            context.tenant = Some(tenant.clone());
+        }
+        if let Some(namespace) = &self.namespace {
+            context.namespace = Some(namespace.clone());
+        }
+        if context.cluster.is_empty() {
+            return Err(Error::Custom(format!("cluster is required for context [{}]", self.name)));
+        }
+        if context.user.is_empty() {
+            return Err(Error::Custom(format!("user is required for context [{}]", self.name)));
+        }
+        output.message(format!("Context \"{}\" set.", self.name).as_str());
+        Ok(true)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct DeleteContextOpts {
+    pub name: String,
+}
+
+impl DeleteContextOpts {
+    fn run(&self, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        configs.delete_context(self.name.as_str())?;
+        output.message(format!("Deleted context \"{}\".", self.name).as_str());
+        Ok(true)
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct RenameContextOpts {
+    pub name: String,
+
+    pub new_name: String,
+}
+
+impl RenameContextOpts {
+    fn run(&self, merged: &Configs, configs: &mut Configs, output: Output) -> Result<bool, Error> {
+        if merged.has_context(self.new_name.as_str()) {
+            return Err(Error::Custom(format!("context [{}] already exists", self.new_name)));
+        }
+        configs.rename_context(self.name.as_str(), self.new_name.as_str())?;
+        output.message(format!("Context \"{}\" renamed to \"{}\".", self.name, self.new_name).as_str());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::Parser;
+
+    use crate::cmd::config::ConfigOpts;
+    use crate::config::{ConfigOverrides, Configs};
+    use crate::output::Output;
+
+    fn run(path: &Path, args: &[&str]) -> Result<(), crate::error::Error> {
+        ConfigOpts::try_parse_from([&["config"], args].concat()).unwrap()
+            .run(&[path.to_path_buf()], Output::default())
+    }
+
+    #[test]
+    fn manage_contexts() {
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-config-manage-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config");
+        std::fs::write(&path, "preferences:\n  color: true\n").unwrap();
+
+        run(&path, &["set-cluster", "local", "--url", "pulsar://localhost:6650",
+            "--admin-url", "http://localhost:8080"]).unwrap();
+        run(&path, &["set-user", "admin", "--auth-name", "token", "--auth-params", "abc"]).unwrap();
+        run(&path, &["set-context", "local", "--cluster", "local", "--user", "admin"]).unwrap();
+        run(&path, &["use-context", "local"]).unwrap();
+        run(&path, &["set-cluster", "local", "--admin-url", "http://localhost:8443"]).unwrap();
+        assert!(run(&path, &["set-cluster", "dev", "--url", "pulsar://dev:6650"]).is_err());
+        assert!(run(&path, &["set-context", "dev", "--cluster", "local"]).is_err());
+        let configs = Configs::load_from(&path).unwrap();
+        assert!(!configs.has_cluster("dev") && !configs.has_context("dev"));
+        let cfg = Configs::load_from(&path).unwrap().get_pulsar_config("local", &ConfigOverrides::default()).unwrap();
+        assert_eq!((cfg.url.as_str(), cfg.admin_url.as_str()), ("pulsar://localhost:6650", "http://localhost:8443"));
+        assert_eq!(cfg.auth_params.as_deref(), Some("abc"));
+
+        run(&path, &["rename-context", "local", "dev"]).unwrap();
+        assert_eq!(Configs::load_from(&path).unwrap().current_context(), Some("dev"));
+        assert!(run(&path, &["use-context", "local"]).is_err());
+
+        run(&path, &["delete-context", "dev"]).unwrap();
+        let configs = Configs::load_from(&path).unwrap();
+        assert!(configs.contexts().is_empty());
+        assert_eq!(configs.current_context(), None);
+        let saved: serde_yaml::Value = serde_yaml::from_str(std::fs::read_to_string(&path).unwrap().as_str()).unwrap();
+        assert_eq!(saved["preferences"]["color"], true);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn change_the_defining_file() {
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-config-cmd-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("first");
+        let second = dir.join("second");
+        std::fs::write(&first, "current-context: prod\n").unwrap();
+        std::fs::write(&second, r#"
+clusters:
+- name: prod
+  cluster:
+    url: pulsar://prod:6650
+    admin-url: http://prod:8080
+users:
+- name: admin
+  user: {}
+contexts:
+- name: prod
+  context:
+    cluster: prod
+    user: admin
+"#).unwrap();
+        let paths = vec![first.clone(), second.clone()];
+        let run = |args: &[&str]| ConfigOpts::try_parse_from([&["config"], args].concat()).unwrap()
+            .run(&paths, Output::default());
+
+        run(&["set-cluster", "prod", "--admin-url", "http://prod:8443"]).unwrap();
+        run(&["set-cluster", "dev", "--url", "pulsar://dev:6650", "--admin-url", "http://dev:8080"]).unwrap();
+        let cfg = Configs::load_merged(&paths).unwrap().get_pulsar_config("prod", &ConfigOverrides::default()).unwrap();
+        assert_eq!((cfg.url.as_str(), cfg.admin_url.as_str()), ("pulsar://prod:6650", "http://prod:8443"));
+        assert!(Configs::load_from(&first).unwrap().has_cluster("dev"));
+        assert!(!Configs::load_from(&first).unwrap().has_cluster("prod"));
+
+        run(&["rename-context", "prod", "production"]).unwrap();
+        let merged = Configs::load_merged(&paths).unwrap();
+        assert_eq!(merged.current_context(), Some("production"));
+        assert!(Configs::load_from(&second).unwrap().has_context("production"));
+
+        run(&["delete-context", "production"]).unwrap();
+        let merged = Configs::load_merged(&paths).unwrap();
+        assert!(!merged.has_context("production"));
+        assert_eq!(merged.current_context(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
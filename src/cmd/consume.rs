@@ -7,8 +7,11 @@ use pulsar::consumer::{InitialPosition, Message};
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
 use crate::error::Error;
+use crate::output::{Output, OutputFormat, Render};
+use std::collections::BTreeMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use chrono::TimeZone;
+use serde::Serialize;
 
 #[derive(Parser, Debug, Clone)]
 pub struct ConsumeOpts {
@@ -52,9 +55,10 @@ impl ConsumeOpts {
 #[async_trait]
 impl AsyncCmd for ConsumeOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         let mut consumer: Consumer<String, _> = pulsar_ctx.client().await?
             .consumer()
-            .with_topic(self.topic.clone())
+            .with_topic(topic.to_string())
             .with_consumer_name(self.name.as_ref().unwrap_or(&String::from("smith")).clone())
             .with_subscription(self.subscription_name.clone())
             .with_subscription_type(Self::parse_sub_type(self.subscription_type.as_str())?)
@@ -73,7 +77,7 @@ impl AsyncCmd for ConsumeOpts {
         while let Some(msg) = consumer.try_next().await? {
             consumer.ack(&msg).await?;
 
-            self.print_msg(msg);
+            self.print_msg(msg, pulsar_ctx.output())?;
             counter += 1;
             if self.num.unwrap_or(u64::MAX) <= counter {
                 break;
@@ -84,19 +88,49 @@ impl AsyncCmd for ConsumeOpts {
     }
 }
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumedMessage {
+    topic: String,
+    publish_time: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_time: Option<String>,
+    latency_ms: u128,
+    properties: BTreeMap<String, String>,
+    payload: String,
+}
+
+impl Render for ConsumedMessage {}
+
 impl ConsumeOpts {
-    fn print_msg(&self, msg: Message<String>) {
+    fn print_msg(&self, msg: Message<String>, output: Output) -> Result<(), Error> {
         debug!("got message, topic: [{}], metadata: [{:?}], data: [{:?}]", &msg.topic, &msg.payload.metadata, &msg.payload.data);
+        let metadata = msg.metadata();
         let latency_ms = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
-            .as_millis() - msg.metadata().publish_time as u128;
-        println!("latency(ms): {}", latency_ms);
-        if let Some(time) = msg.metadata().event_time {
-            println!("event time(ms): {}", chrono::Utc.timestamp_millis(time as i64).to_rfc3339());
+            .as_millis() - metadata.publish_time as u128;
+        let message = ConsumedMessage {
+            topic: msg.topic.clone(),
+            publish_time: chrono::Utc.timestamp_millis(metadata.publish_time as i64).to_rfc3339(),
+            event_time: metadata.event_time.map(|time| chrono::Utc.timestamp_millis(time as i64).to_rfc3339()),
+            latency_ms,
+            properties: metadata.properties.iter().map(|kv| (kv.key.clone(), kv.value.clone())).collect(),
+            payload: String::from_utf8_lossy(&msg.payload.data).to_string(),
+        };
+        match output.format() {
+            OutputFormat::Table | OutputFormat::Wide => {
+                println!("latency(ms): {}", message.latency_ms);
+                if let Some(time) = &message.event_time {
+                    println!("event time(ms): {}", time);
+                }
+                println!("properties: {:?}", message.properties);
+                println!("msg:\n{}", message.payload);
+            }
+            OutputFormat::Plain => println!("{}", message.payload),
+            OutputFormat::Json => println!("{}", serde_json::to_string(&message)?),
+            OutputFormat::Yaml => output.render(&message)?,
         }
-        println!("properties: {:?}", msg.metadata().properties);
-
-        println!("msg:\n{}", String::from_utf8(msg.payload.data).unwrap());
+        Ok(())
     }
 }
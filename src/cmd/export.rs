@@ -0,0 +1,51 @@
+use std::fs;
+
+use async_trait::async_trait;
+use clap::Parser;
+
+use crate::admin::naming::NamespaceName;
+use crate::cmd::cmd::AsyncCmd;
+use crate::context::PulsarContext;
+use crate::error::Error;
+use crate::manifest::export::{export_namespace, export_tenant};
+use crate::manifest::Manifest;
+use crate::output::OutputFormat;
+
+/// Write a tenant or namespace as a manifest for apply, with its namespaces, topics,
+/// permissions, subscriptions and schemas
+#[derive(Parser, Debug, Clone)]
+pub struct ExportOpts {
+    /// A tenant, or a namespace as tenant/namespace which is exported without its tenant
+    pub name: String,
+
+    /// File to write instead of stdout
+    #[arg(short = 'f', long = "filename")]
+    pub filename: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for ExportOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let output = pulsar_ctx.output();
+        let admin = pulsar_ctx.admin().await?;
+        let manifest = if self.name.contains('/') {
+            let namespace = NamespaceName::parse(self.name.as_str())?;
+            Manifest {
+                tenants: Vec::new(),
+                namespaces: vec![export_namespace(admin, &namespace).await?],
+            }
+        } else {
+            export_tenant(admin, self.name.as_str()).await?
+        };
+        match (&self.filename, output.format()) {
+            (Some(filename), _) => {
+                fs::write(filename, serde_yaml::to_string(&manifest)?)?;
+                output.message(format!("Exported {} to {}", self.name, filename).as_str());
+            }
+            (None, OutputFormat::Json) | (None, OutputFormat::Yaml) =>
+                output.render(&serde_json::to_value(&manifest)?)?,
+            (None, _) => print!("{}", serde_yaml::to_string(&manifest)?),
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 
 use crate::error::Error;
+use crate::admin::naming::NamespaceName;
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
 use clap::Parser;
@@ -30,20 +31,26 @@ pub enum Command {
 #[derive(Parser, Debug, Clone)]
 pub struct ListOpts {
     #[arg(long)]
-    pub tenant: String,
+    pub tenant: Option<String>,
 
     #[arg(long)]
-    pub namespace: String,
+    pub namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for ListOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = match (&self.tenant, &self.namespace) {
+            (Some(tenant), Some(namespace)) => NamespaceName::new(tenant, namespace)?,
+            (None, Some(namespace)) => pulsar_ctx.get_config().resolve_namespace(Some(namespace.as_str()))?,
+            (Some(tenant), None) => NamespaceName::new(tenant, "default")?,
+            (None, None) => pulsar_ctx.get_config().resolve_namespace(None)?,
+        };
         let r = pulsar_ctx.admin().await?
             .functions()
-            .list(format!("{}/{}", self.tenant, self.namespace).as_str())
+            .list(&namespace)
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
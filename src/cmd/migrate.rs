@@ -0,0 +1,275 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use regex::Regex;
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::naming::NamespaceName;
+use crate::config::{ConfigOverrides, Configs};
+use crate::context::PulsarContext;
+use crate::error::Error;
+use crate::manifest::export::{export_namespace, export_tenant_info};
+use crate::manifest::plan::{Action, Change, ChangeResult, Plan};
+use crate::manifest::Manifest;
+use crate::output::Output;
+
+/// Copy namespaces with their tenants, policies, permissions, topics, subscriptions and
+/// schemas from one context to another
+#[derive(Parser, Debug, Clone)]
+pub struct MigrateOpts {
+    /// Context to copy from
+    #[arg(long)]
+    pub from: String,
+
+    /// Context to copy to
+    #[arg(long)]
+    pub to: String,
+
+    /// Only copy namespaces, as tenant/namespace, matching one of these regular expressions
+    #[arg(short = 'i', long)]
+    pub include: Vec<Regex>,
+
+    /// Do not copy namespaces matching one of these regular expressions
+    #[arg(short = 'e', long)]
+    pub exclude: Vec<Regex>,
+
+    /// What to do with settings the target already has with different values
+    #[arg(long, value_enum, default_value = "fail")]
+    pub on_conflict: ConflictStrategy,
+
+    /// Only print what would be copied
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Keep the target's values and copy only what it lacks
+    Skip,
+    /// Replace the target's values
+    Overwrite,
+    /// Copy nothing if there is any conflict
+    Fail,
+}
+
+impl MigrateOpts {
+    /// Migrations use two contexts, so they run without the `PulsarContext` of the others.
+    pub async fn run(&self, config_paths: &[PathBuf], output: Output) -> Result<(), Error> {
+        let configs = Configs::load_merged(config_paths)?;
+        let mut source: PulsarContext = configs.get_pulsar_config(self.from.as_str(), &ConfigOverrides::default())?.into();
+        let mut target: PulsarContext = configs.get_pulsar_config(self.to.as_str(), &ConfigOverrides::default())?.into();
+        let source = source.admin().await?;
+        let target = target.admin().await?;
+
+        let manifest = self.read(source, target).await?;
+        let plan = Plan::compute(target, &manifest, false).await?;
+        let (report, result) = self.copy(target, &plan).await;
+        if report.is_empty() {
+            output.message(format!("Nothing to copy, {} has everything", self.to).as_str());
+        } else {
+            output.render(&report)?;
+        }
+        result
+    }
+
+    /// Applies the changes one by one and reports the status of each, with the first failure,
+    /// so that the report also shows what was copied before it.
+    async fn copy<'p>(&self, target: &PulsarAdmin, plan: &'p Plan) -> (Vec<ChangeResult<'p>>, Result<(), Error>) {
+        let conflicts = plan.changes.iter().filter(|c| c.is_conflict()).count();
+        let fail = self.on_conflict == ConflictStrategy::Fail && conflicts > 0;
+        let mut report = Vec::new();
+        let mut result = Ok(());
+        for change in &plan.changes {
+            let status = if fail {
+                if change.is_conflict() { "conflict" } else { "not copied" }
+            } else if result.is_err() {
+                "not copied"
+            } else if self.skip(change) {
+                "skipped"
+            } else if self.dry_run {
+                "dry-run"
+            } else {
+                match change.apply(target, false).await {
+                    Ok(()) if change.action == Action::Delete => "deleted",
+                    Ok(()) => "copied",
+                    Err(e) => {
+                        result = Err(e.into());
+                        "failed"
+                    }
+                }
+            };
+            report.push(ChangeResult { change, status });
+        }
+        if fail && !self.dry_run {
+            result = Err(Error::Custom(format!(
+                "{} changes conflict with {}, nothing was copied; use --on-conflict skip or overwrite",
+                conflicts, self.to)));
+        }
+        (report, result)
+    }
+
+    fn selected(&self, namespace: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|r| r.is_match(namespace)))
+            && !self.exclude.iter().any(|r| r.is_match(namespace))
+    }
+
+    fn skip(&self, change: &Change) -> bool {
+        self.on_conflict == ConflictStrategy::Skip && change.is_conflict()
+    }
+
+    /// The selected namespaces of the source with their tenants, as a manifest for the target.
+    async fn read(&self, source: &PulsarAdmin, target: &PulsarAdmin) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::default();
+        for tenant in source.tenants().list().await? {
+            let namespaces: Vec<String> = source.namespaces().list(tenant.as_str()).await?.into_iter()
+                .filter(|namespace| self.selected(namespace))
+                .collect();
+            if namespaces.is_empty() {
+                continue;
+            }
+            manifest.tenants.push(export_tenant_info(source, tenant.as_str()).await?);
+            for namespace in namespaces {
+                info!("reading {}", namespace);
+                manifest.namespaces.push(export_namespace(source, &NamespaceName::parse(namespace.as_str())?).await?);
+            }
+        }
+
+        // cluster names usually differ between regions, so only those the target knows are kept
+        let clusters = target.clusters().list().await?;
+        let known = |names: Option<Vec<String>>| names
+            .map(|names| names.into_iter().filter(|name| clusters.contains(name)).collect::<Vec<String>>())
+            .filter(|names| !names.is_empty());
+        for tenant in &mut manifest.tenants {
+            tenant.allowed_clusters = known(tenant.allowed_clusters.take());
+        }
+        for namespace in &mut manifest.namespaces {
+            namespace.replication_clusters = known(namespace.replication_clusters.take());
+        }
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::admin::admin::PulsarAdmin;
+    use crate::cmd::migrate::MigrateOpts;
+    use crate::manifest::plan::Plan;
+    use crate::testing::{MockResponse, MockServer};
+
+    async fn source() -> MockServer {
+        MockServer::start(|req, _| match req.path.as_str() {
+            "/admin/v2/tenants" => MockResponse::new(200, r#"["acme"]"#),
+            "/admin/v2/tenants/acme" => MockResponse::new(200, r#"{"adminRoles":[],"allowedClusters":["us-east"]}"#),
+            "/admin/v2/namespaces/acme" => MockResponse::new(200, r#"["acme/orders","acme/tmp"]"#),
+            "/admin/v2/namespaces/acme/orders" =>
+                MockResponse::new(200, r#"{"replication_clusters":["us-east"],"message_ttl_in_seconds":60}"#),
+            "/admin/v2/persistent/acme/orders" => MockResponse::new(200, r#"["persistent://acme/orders/in"]"#),
+            path if path.starts_with("/admin/v2/schemas/") => MockResponse::new(404, r#"{"reason":"Not found"}"#),
+            path if path.ends_with("/permissions") => MockResponse::new(200, "{}"),
+            path if path.ends_with("/subscriptions") || path.contains("/acme/orders/partitioned")
+                || path.starts_with("/admin/v2/non-persistent/") => MockResponse::new(200, "[]"),
+            _ => MockResponse::new(204, ""),
+        }).await
+    }
+
+    /// A target with a different TTL and an extra permission, rejecting writes to `failing`.
+    async fn target(failing: &'static str) -> MockServer {
+        MockServer::start(move |req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/clusters") => MockResponse::new(200, r#"["us-west"]"#),
+            ("GET", "/admin/v2/tenants") => MockResponse::new(200, r#"["acme"]"#),
+            ("GET", "/admin/v2/tenants/acme") =>
+                MockResponse::new(200, r#"{"adminRoles":[],"allowedClusters":["us-west"]}"#),
+            ("GET", "/admin/v2/namespaces/acme") => MockResponse::new(200, r#"["acme/orders"]"#),
+            ("GET", "/admin/v2/namespaces/acme/orders/messageTTL") => MockResponse::new(200, "30"),
+            ("GET", "/admin/v2/namespaces/acme/orders/permissions") =>
+                MockResponse::new(200, r#"{"legacy":["consume"]}"#),
+            ("GET", _) => MockResponse::new(200, "[]"),
+            (_, path) if path == failing => MockResponse::new(500, r#"{"reason":"broker unavailable"}"#),
+            _ => MockResponse::new(204, ""),
+        }).await
+    }
+
+    /// The statuses `opts` reports copying the source to the target, whether it failed, and the
+    /// writes it made.
+    async fn migrate(args: &[&str], failing: &'static str) -> (Vec<String>, bool, Vec<String>) {
+        let (source, target) = (source().await, target(failing).await);
+        let source = PulsarAdmin::builder(source.url().to_string()).build().unwrap();
+        let admin = PulsarAdmin::builder(target.url().to_string()).build().unwrap();
+        let opts = MigrateOpts::try_parse_from(["migrate", "--from", "east", "--to", "west", "-e", "/tmp$"].iter()
+            .chain(args)).unwrap();
+
+        let manifest = opts.read(&source, &admin).await.unwrap();
+        assert_eq!(manifest.namespaces.len(), 1);
+        assert_eq!(manifest.tenants[0].allowed_clusters, None);
+        assert_eq!(manifest.namespaces[0].replication_clusters, None);
+
+        let plan = Plan::compute(&admin, &manifest, false).await.unwrap();
+        let (report, result) = opts.copy(&admin, &plan).await;
+        let statuses = report.iter()
+            .map(|r| format!("{} {} {}", r.change.name, r.change.field.as_deref().unwrap_or("-"), r.status))
+            .collect();
+        let writes = target.requests().into_iter()
+            .filter(|r| r.method != "GET")
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        (statuses, result.is_err(), writes)
+    }
+
+    #[tokio::test]
+    async fn migrate_with_skip() {
+        let (statuses, failed, writes) = migrate(&["--on-conflict", "skip"], "").await;
+        assert_eq!(statuses, vec![
+            "acme/orders messageTtl skipped",
+            "acme/orders permissions.legacy skipped",
+            "persistent://acme/orders/in - copied",
+        ]);
+        assert!(!failed);
+        assert_eq!(writes, vec!["PUT /admin/v2/persistent/acme/orders/in"]);
+    }
+
+    #[tokio::test]
+    async fn migrate_with_fail() {
+        let conflicts = vec![
+            "acme/orders messageTtl conflict",
+            "acme/orders permissions.legacy conflict",
+            "persistent://acme/orders/in - not copied",
+        ];
+        let (statuses, failed, writes) = migrate(&[], "").await;
+        assert_eq!(statuses, conflicts);
+        assert!(failed);
+        assert!(writes.is_empty());
+
+        let (statuses, failed, writes) = migrate(&["--dry-run"], "").await;
+        assert_eq!(statuses, conflicts);
+        assert!(!failed);
+        assert!(writes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn migrate_with_overwrite() {
+        let (statuses, failed, writes) = migrate(&["--on-conflict", "overwrite"], "").await;
+        assert_eq!(statuses, vec![
+            "acme/orders messageTtl copied",
+            "acme/orders permissions.legacy deleted",
+            "persistent://acme/orders/in - copied",
+        ]);
+        assert!(!failed);
+        assert_eq!(writes, vec![
+            "POST /admin/v2/namespaces/acme/orders/messageTTL",
+            "DELETE /admin/v2/namespaces/acme/orders/permissions/legacy",
+            "PUT /admin/v2/persistent/acme/orders/in",
+        ]);
+
+        let (statuses, failed, writes) = migrate(&["--on-conflict", "overwrite"],
+                                                 "/admin/v2/namespaces/acme/orders/permissions/legacy").await;
+        assert_eq!(statuses, vec![
+            "acme/orders messageTtl copied",
+            "acme/orders permissions.legacy failed",
+            "persistent://acme/orders/in - not copied",
+        ]);
+        assert!(failed);
+        assert!(!writes.iter().any(|w| w.ends_with("/acme/orders/in")));
+    }
+}
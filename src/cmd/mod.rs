@@ -10,3 +10,8 @@ pub mod functions;
 pub mod sinks;
 pub mod perf;
 pub mod commons;
+pub mod config;
+pub mod shell;
+pub mod apply;
+pub mod export;
+pub mod migrate;
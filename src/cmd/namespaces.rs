@@ -1,12 +1,27 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
 use async_trait::async_trait;
-use clap::Parser;
+use clap::{ArgGroup, Args, Parser};
 use regex::Regex;
+use serde::Serialize;
 
-use crate::admin::namespaces::{NamespacePolicies, PersistencePolicies};
-use crate::admin::topics::TopicDomain;
+use crate::admin::namespaces::{AutoSubscriptionCreationOverride, AutoTopicCreationOverride, BacklogQuota,
+                               BacklogQuotaPolicy, BacklogQuotaType, BundlesData, Deduplication,
+                               DelayedDeliveryPolicies, DispatchRate, EncryptionRequired, InactiveTopicDeleteMode,
+                               InactiveTopicPolicies, MaxConsumersPerTopic, MaxProducersPerTopic,
+                               MaxSubscriptionsPerTopic, MessageTtl, NamespacePolicies, NamespacePolicy,
+                               PersistencePolicies, PublishRate, ReplicatorDispatchRate, RetentionPolicies,
+                               SchemaCompatibilityStrategy, SchemaValidationEnforced, SplitAlgorithm, SubscribeRate,
+                               SubscriptionDispatchRate, SubscriptionExpirationTime, TopicType};
+use crate::admin::naming::{TopicDomain, TopicName};
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
+use crate::duration::parse_relative_time;
 use crate::error::Error;
+use crate::output::{Render, Table};
 
 #[derive(Parser, Debug, Clone)]
 pub struct NamespacesOpts {
@@ -20,6 +35,7 @@ impl AsyncCmd for NamespacesOpts {
         let cmd: &dyn AsyncCmd = match &self.cmd {
             Command::List(opts) => opts,
             Command::Create(opts) => opts,
+            Command::Delete(opts) => opts,
             Command::Policies(opts) => opts,
             Command::Permissions(opts) => opts,
             Command::GrantPermission(opts) => opts,
@@ -27,8 +43,73 @@ impl AsyncCmd for NamespacesOpts {
             Command::GetPersistence(opts) => opts,
             Command::SetPersistence(opts) => opts,
             Command::RemovePersistence(opts) => opts,
+            Command::GetRetention(opts) => opts,
+            Command::SetRetention(opts) => opts,
+            Command::RemoveRetention(opts) => opts,
+            Command::GetMessageTtl(opts) => opts,
+            Command::SetMessageTtl(opts) => opts,
+            Command::RemoveMessageTtl(opts) => opts,
+            Command::GetBacklogQuotas(opts) => opts,
+            Command::SetBacklogQuota(opts) => opts,
+            Command::RemoveBacklogQuota(opts) => opts,
+            Command::GetDispatchRate(opts) => opts,
+            Command::SetDispatchRate(opts) => opts,
+            Command::RemoveDispatchRate(opts) => opts,
+            Command::GetSubscriptionDispatchRate(opts) => opts,
+            Command::SetSubscriptionDispatchRate(opts) => opts,
+            Command::RemoveSubscriptionDispatchRate(opts) => opts,
+            Command::GetReplicatorDispatchRate(opts) => opts,
+            Command::SetReplicatorDispatchRate(opts) => opts,
+            Command::RemoveReplicatorDispatchRate(opts) => opts,
+            Command::GetSubscribeRate(opts) => opts,
+            Command::SetSubscribeRate(opts) => opts,
+            Command::RemoveSubscribeRate(opts) => opts,
+            Command::GetPublishRate(opts) => opts,
+            Command::SetPublishRate(opts) => opts,
+            Command::RemovePublishRate(opts) => opts,
+            Command::GetDeduplication(opts) => opts,
+            Command::SetDeduplication(opts) => opts,
+            Command::RemoveDeduplication(opts) => opts,
+            Command::GetSubscriptionExpirationTime(opts) => opts,
+            Command::SetSubscriptionExpirationTime(opts) => opts,
+            Command::RemoveSubscriptionExpirationTime(opts) => opts,
+            Command::GetMaxProducersPerTopic(opts) => opts,
+            Command::SetMaxProducersPerTopic(opts) => opts,
+            Command::RemoveMaxProducersPerTopic(opts) => opts,
+            Command::GetMaxConsumersPerTopic(opts) => opts,
+            Command::SetMaxConsumersPerTopic(opts) => opts,
+            Command::RemoveMaxConsumersPerTopic(opts) => opts,
+            Command::GetMaxSubscriptionsPerTopic(opts) => opts,
+            Command::SetMaxSubscriptionsPerTopic(opts) => opts,
+            Command::RemoveMaxSubscriptionsPerTopic(opts) => opts,
+            Command::GetAutoTopicCreation(opts) => opts,
+            Command::SetAutoTopicCreation(opts) => opts,
+            Command::RemoveAutoTopicCreation(opts) => opts,
+            Command::GetAutoSubscriptionCreation(opts) => opts,
+            Command::SetAutoSubscriptionCreation(opts) => opts,
+            Command::RemoveAutoSubscriptionCreation(opts) => opts,
+            Command::GetSchemaCompatibilityStrategy(opts) => opts,
+            Command::SetSchemaCompatibilityStrategy(opts) => opts,
+            Command::RemoveSchemaCompatibilityStrategy(opts) => opts,
+            Command::GetSchemaValidationEnforced(opts) => opts,
+            Command::SetSchemaValidationEnforced(opts) => opts,
+            Command::RemoveSchemaValidationEnforced(opts) => opts,
+            Command::GetEncryptionRequired(opts) => opts,
+            Command::SetEncryptionRequired(opts) => opts,
+            Command::RemoveEncryptionRequired(opts) => opts,
+            Command::GetDelayedDelivery(opts) => opts,
+            Command::SetDelayedDelivery(opts) => opts,
+            Command::RemoveDelayedDelivery(opts) => opts,
+            Command::GetInactiveTopicPolicies(opts) => opts,
+            Command::SetInactiveTopicPolicies(opts) => opts,
+            Command::RemoveInactiveTopicPolicies(opts) => opts,
             Command::Unsubscribe(opts) => opts,
             Command::DeleteTopics(opts) => opts,
+            Command::Bundles(opts) => opts,
+            Command::Unload(opts) => opts,
+            Command::SplitBundle(opts) => opts,
+            Command::ClearBacklog(opts) => opts,
+            Command::BundleOwnership(opts) => opts,
         };
         cmd.run(pulsar_ctx).await?;
         Ok(())
@@ -39,6 +120,7 @@ impl AsyncCmd for NamespacesOpts {
 pub enum Command {
     List(ListOpts),
     Create(CreateOpts),
+    Delete(DeleteOpts),
     Policies(PoliciesOpts),
     Permissions(PermissionsOpts),
     GrantPermission(GrantPermissionOpts),
@@ -46,23 +128,93 @@ pub enum Command {
     GetPersistence(GetPersistenceOpts),
     SetPersistence(SetPersistenceOpts),
     RemovePersistence(RemovePersistenceOpts),
+    GetRetention(GetPolicyOpts<RetentionPolicies>),
+    SetRetention(SetRetentionOpts),
+    RemoveRetention(RemovePolicyOpts<RetentionPolicies>),
+    GetMessageTtl(GetPolicyOpts<MessageTtl>),
+    SetMessageTtl(SetMessageTtlOpts),
+    RemoveMessageTtl(RemovePolicyOpts<MessageTtl>),
+    GetBacklogQuotas(GetBacklogQuotasOpts),
+    SetBacklogQuota(SetBacklogQuotaOpts),
+    RemoveBacklogQuota(RemoveBacklogQuotaOpts),
+    GetDispatchRate(GetPolicyOpts<DispatchRate>),
+    SetDispatchRate(SetDispatchRateOpts<DispatchRate>),
+    RemoveDispatchRate(RemovePolicyOpts<DispatchRate>),
+    GetSubscriptionDispatchRate(GetPolicyOpts<SubscriptionDispatchRate>),
+    SetSubscriptionDispatchRate(SetDispatchRateOpts<SubscriptionDispatchRate>),
+    RemoveSubscriptionDispatchRate(RemovePolicyOpts<SubscriptionDispatchRate>),
+    GetReplicatorDispatchRate(GetPolicyOpts<ReplicatorDispatchRate>),
+    SetReplicatorDispatchRate(SetDispatchRateOpts<ReplicatorDispatchRate>),
+    RemoveReplicatorDispatchRate(RemovePolicyOpts<ReplicatorDispatchRate>),
+    GetSubscribeRate(GetPolicyOpts<SubscribeRate>),
+    SetSubscribeRate(SetSubscribeRateOpts),
+    RemoveSubscribeRate(RemovePolicyOpts<SubscribeRate>),
+    GetPublishRate(GetPolicyOpts<PublishRate>),
+    SetPublishRate(SetPublishRateOpts),
+    RemovePublishRate(RemovePolicyOpts<PublishRate>),
+    GetDeduplication(GetPolicyOpts<Deduplication>),
+    SetDeduplication(SetFlagOpts<Deduplication>),
+    RemoveDeduplication(RemovePolicyOpts<Deduplication>),
+    GetSubscriptionExpirationTime(GetPolicyOpts<SubscriptionExpirationTime>),
+    SetSubscriptionExpirationTime(SetSubscriptionExpirationTimeOpts),
+    RemoveSubscriptionExpirationTime(RemovePolicyOpts<SubscriptionExpirationTime>),
+    GetMaxProducersPerTopic(GetPolicyOpts<MaxProducersPerTopic>),
+    SetMaxProducersPerTopic(SetMaxOpts<MaxProducersPerTopic>),
+    RemoveMaxProducersPerTopic(RemovePolicyOpts<MaxProducersPerTopic>),
+    GetMaxConsumersPerTopic(GetPolicyOpts<MaxConsumersPerTopic>),
+    SetMaxConsumersPerTopic(SetMaxOpts<MaxConsumersPerTopic>),
+    RemoveMaxConsumersPerTopic(RemovePolicyOpts<MaxConsumersPerTopic>),
+    GetMaxSubscriptionsPerTopic(GetPolicyOpts<MaxSubscriptionsPerTopic>),
+    SetMaxSubscriptionsPerTopic(SetMaxOpts<MaxSubscriptionsPerTopic>),
+    RemoveMaxSubscriptionsPerTopic(RemovePolicyOpts<MaxSubscriptionsPerTopic>),
+    GetAutoTopicCreation(GetPolicyOpts<AutoTopicCreationOverride>),
+    SetAutoTopicCreation(SetAutoTopicCreationOpts),
+    RemoveAutoTopicCreation(RemovePolicyOpts<AutoTopicCreationOverride>),
+    GetAutoSubscriptionCreation(GetPolicyOpts<AutoSubscriptionCreationOverride>),
+    SetAutoSubscriptionCreation(SetFlagOpts<AutoSubscriptionCreationOverride>),
+    RemoveAutoSubscriptionCreation(RemovePolicyOpts<AutoSubscriptionCreationOverride>),
+    GetSchemaCompatibilityStrategy(GetPolicyOpts<SchemaCompatibilityStrategy>),
+    SetSchemaCompatibilityStrategy(SetSchemaCompatibilityStrategyOpts),
+    RemoveSchemaCompatibilityStrategy(RemovePolicyOpts<SchemaCompatibilityStrategy>),
+    GetSchemaValidationEnforced(GetPolicyOpts<SchemaValidationEnforced>),
+    SetSchemaValidationEnforced(SetFlagOpts<SchemaValidationEnforced>),
+    RemoveSchemaValidationEnforced(RemovePolicyOpts<SchemaValidationEnforced>),
+    GetEncryptionRequired(GetPolicyOpts<EncryptionRequired>),
+    SetEncryptionRequired(SetFlagOpts<EncryptionRequired>),
+    RemoveEncryptionRequired(RemovePolicyOpts<EncryptionRequired>),
+    GetDelayedDelivery(GetPolicyOpts<DelayedDeliveryPolicies>),
+    SetDelayedDelivery(SetDelayedDeliveryOpts),
+    RemoveDelayedDelivery(RemovePolicyOpts<DelayedDeliveryPolicies>),
+    GetInactiveTopicPolicies(GetPolicyOpts<InactiveTopicPolicies>),
+    SetInactiveTopicPolicies(SetInactiveTopicPoliciesOpts),
+    RemoveInactiveTopicPolicies(RemovePolicyOpts<InactiveTopicPolicies>),
     Unsubscribe(UnsubscribeOpts),
     DeleteTopics(DeleteTopicsOpts),
+    /// List the bundles of a namespace
+    Bundles(BundlesOpts),
+    /// Unload a namespace or one of its bundles, so other brokers can pick it up
+    Unload(UnloadOpts),
+    SplitBundle(SplitBundleOpts),
+    /// Skip all messages of the subscriptions in a namespace or bundle
+    ClearBacklog(ClearBacklogOpts),
+    /// Show which broker owns each bundle of a namespace
+    BundleOwnership(BundleOwnershipOpts),
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct ListOpts {
-    tenant: String,
+    tenant: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for ListOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let tenant = self.tenant.clone().unwrap_or_else(|| pulsar_ctx.get_config().default_tenant());
         let r = pulsar_ctx.admin().await?
             .namespaces()
-            .list(self.tenant.as_str())
+            .list(tenant.as_str())
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -81,7 +233,7 @@ pub struct CreateOpts {
 impl From<&CreateOpts> for NamespacePolicies {
     fn from(opts: &CreateOpts) -> Self {
         NamespacePolicies {
-            bundles: None,
+            bundles: opts.bundles.map(|num_bundles| BundlesData { num_bundles, boundaries: Vec::new() }),
             replication_clusters: Some(opts.clusters.clone()),
             ..Default::default()
         }
@@ -91,52 +243,77 @@ impl From<&CreateOpts> for NamespacePolicies {
 #[async_trait]
 impl AsyncCmd for CreateOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let r = pulsar_ctx.admin().await?
+        let namespace = pulsar_ctx.get_config().resolve_namespace(Some(self.namespace.as_str()))?;
+        pulsar_ctx.admin().await?
             .namespaces()
-            .create(self.namespace.as_str(), &self.into())
+            .create(&namespace, &self.into())
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().message(format!("Created namespace {}", namespace).as_str());
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
-pub struct PoliciesOpts {
+pub struct DeleteOpts {
     namespace: String,
+
+    /// Delete the namespace with its topics, if the broker allows forced deletes
+    #[arg(short = 'f', long, default_value = "false")]
+    force: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for DeleteOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(Some(self.namespace.as_str()))?;
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .delete(&namespace, self.force)
+            .await?;
+        pulsar_ctx.output().message(format!("Deleted namespace {}", namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct PoliciesOpts {
+    namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for PoliciesOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let r = pulsar_ctx.admin().await?
             .namespaces()
-            .policies(self.namespace.as_str())
+            .policies(&namespace)
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct PermissionsOpts {
-    namespace: String,
+    namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for PermissionsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let r = pulsar_ctx.admin().await?
             .namespaces()
-            .permissions(self.namespace.as_str())
+            .permissions(&namespace)
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct GrantPermissionOpts {
-    namespace: String,
+    namespace: Option<String>,
 
     #[arg(long)]
     role: String,
@@ -148,18 +325,19 @@ pub struct GrantPermissionOpts {
 #[async_trait]
 impl AsyncCmd for GrantPermissionOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let r = pulsar_ctx.admin().await?
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
             .namespaces()
-            .grant_permission(self.namespace.as_str(), self.role.as_str(), &self.actions)
+            .grant_permission(&namespace, self.role.as_str(), &self.actions)
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().message(format!("Granted {:?} on {} to {}", self.actions, namespace, self.role).as_str());
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct RevokePermissionOpts {
-    namespace: String,
+    namespace: Option<String>,
 
     #[arg(long)]
     role: String,
@@ -168,36 +346,38 @@ pub struct RevokePermissionOpts {
 #[async_trait]
 impl AsyncCmd for RevokePermissionOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let r = pulsar_ctx.admin().await?
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
             .namespaces()
-            .revoke_permission(self.namespace.as_str(), self.role.as_str())
+            .revoke_permission(&namespace, self.role.as_str())
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().message(format!("Revoked permissions on {} from {}", namespace, self.role).as_str());
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct GetPersistenceOpts {
-    namespace: String,
+    namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for GetPersistenceOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let r = pulsar_ctx.admin().await?
             .namespaces()
-            .policies(self.namespace.as_str())
+            .policies(&namespace)
             .await?
             .persistence;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct SetPersistenceOpts {
-    namespace: String,
+    namespace: Option<String>,
 
     #[arg(short = 'a', long, default_value = "0")]
     bookkeeper_ack_quorum: i32,
@@ -226,33 +406,499 @@ impl From<&SetPersistenceOpts> for PersistencePolicies {
 #[async_trait]
 impl AsyncCmd for SetPersistenceOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         pulsar_ctx.admin().await?
             .namespaces()
-            .update_persistence(self.namespace.as_str(), &self.into())
+            .update_persistence(&namespace, &self.into())
             .await?;
+        pulsar_ctx.output().message(format!("Updated persistence policies of {}", namespace).as_str());
         Ok(())
     }
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct RemovePersistenceOpts {
-    namespace: String,
+    namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for RemovePersistenceOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .remove_persistence(&namespace)
+            .await?;
+        pulsar_ctx.output().message(format!("Removed persistence policies of {}", namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetPolicyOpts<P: NamespacePolicy + Debug + Clone> {
+    namespace: Option<String>,
+
+    #[arg(skip)]
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: NamespacePolicy + Render + Debug + Clone> AsyncCmd for GetPolicyOpts<P> {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let r = pulsar_ctx.admin().await?
+            .namespaces()
+            .policy::<P>(&namespace)
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct RemovePolicyOpts<P: NamespacePolicy + Debug + Clone> {
+    namespace: Option<String>,
+
+    #[arg(skip)]
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: NamespacePolicy + Debug + Clone> AsyncCmd for RemovePolicyOpts<P> {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         pulsar_ctx.admin().await?
             .namespaces()
-            .remove_persistence(self.namespace.as_str())
+            .remove_policy::<P>(&namespace)
             .await?;
+        pulsar_ctx.output().message(format!("Removed {} of {}", P::NAME, namespace).as_str());
         Ok(())
     }
 }
 
+/// Sets a policy and reports it, for the `set-*` commands.
+async fn set_policy<P: NamespacePolicy>(pulsar_ctx: &mut PulsarContext, namespace: Option<&str>, policy: &P)
+                                        -> Result<(), Error> {
+    let namespace = pulsar_ctx.get_config().resolve_namespace(namespace)?;
+    pulsar_ctx.admin().await?
+        .namespaces()
+        .set_policy(&namespace, policy)
+        .await?;
+    pulsar_ctx.output().message(format!("Set {} of {}", P::NAME, namespace).as_str());
+    Ok(())
+}
+
+// --enable or --disable, for the policies that are switched on or off.
+#[derive(Args, Debug, Clone)]
+#[command(group(ArgGroup::new("toggle").required(true).args(["enable", "disable"])))]
+pub struct Toggle {
+    #[arg(short = 'e', long)]
+    enable: bool,
+
+    #[arg(short = 'd', long)]
+    disable: bool,
+}
+
+/// `-1` for infinite, otherwise a relative time such as `7d`, in minutes.
+fn parse_retention_time(time: &str) -> Result<i32, String> {
+    if time.trim() == "-1" {
+        return Ok(-1);
+    }
+    i32::try_from(parse_relative_time(time)? / 60).map_err(|_| format!("retention time [{}] is too large", time))
+}
+
+/// A size such as `512M` or `10G` in bytes, or `-1` for no limit.
+fn parse_size(size: &str) -> Result<i64, String> {
+    let size = size.trim();
+    if size == "-1" {
+        return Ok(-1);
+    }
+    let (value, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => size.split_at(i),
+        None => (size, ""),
+    };
+    let value: i64 = value.parse().map_err(|_| format!("invalid size [{}]", size))?;
+    let unit = match unit.to_lowercase().as_str() {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return Err(format!("invalid unit [{}] in [{}], expected k, m, g or t", unit, size)),
+    };
+    value.checked_mul(unit).ok_or_else(|| format!("size [{}] is too large", size))
+}
+
+fn parse_seconds(time: &str) -> Result<i32, String> {
+    i32::try_from(parse_relative_time(time)?).map_err(|_| format!("time [{}] is too large", time))
+}
+
+fn parse_millis(time: &str) -> Result<i64, String> {
+    parse_relative_time(time)?.checked_mul(1000)
+        .and_then(|millis| i64::try_from(millis).ok())
+        .ok_or_else(|| format!("time [{}] is too large", time))
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetRetentionOpts {
+    namespace: Option<String>,
+
+    /// How long to keep acknowledged messages, e.g. 7d, or -1 for ever
+    #[arg(short = 't', long, allow_hyphen_values = true, value_parser = parse_retention_time)]
+    time: i32,
+
+    /// How much acknowledged messages to keep per topic, e.g. 10G, or -1 for no limit
+    #[arg(short = 's', long, allow_hyphen_values = true, value_parser = parse_size)]
+    size: i64,
+}
+
+#[async_trait]
+impl AsyncCmd for SetRetentionOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let policy = RetentionPolicies {
+            retention_time_in_minutes: self.time,
+            retention_size_in_mb: if self.size < 0 { -1 } else { self.size >> 20 },
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &policy).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetMessageTtlOpts {
+    namespace: Option<String>,
+
+    /// Time after which unacknowledged messages expire, e.g. 1h
+    #[arg(short = 't', long, value_parser = parse_seconds)]
+    ttl: i32,
+}
+
+#[async_trait]
+impl AsyncCmd for SetMessageTtlOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &MessageTtl(self.ttl)).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct GetBacklogQuotasOpts {
+    namespace: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for GetBacklogQuotasOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let r = pulsar_ctx.admin().await?
+            .namespaces()
+            .backlog_quotas(&namespace)
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetBacklogQuotaOpts {
+    namespace: Option<String>,
+
+    #[arg(long = "type", default_value = "destination_storage")]
+    quota_type: BacklogQuotaType,
+
+    /// Backlog size for destination_storage, e.g. 10G, or -1 for no limit
+    #[arg(long, allow_hyphen_values = true, value_parser = parse_size)]
+    limit_size: Option<i64>,
+
+    /// Age of the oldest message for message_age, e.g. 1d
+    #[arg(long, value_parser = parse_seconds)]
+    limit_time: Option<i32>,
+
+    #[arg(short = 'p', long)]
+    policy: BacklogQuotaPolicy,
+}
+
+#[async_trait]
+impl AsyncCmd for SetBacklogQuotaOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        match self.quota_type {
+            BacklogQuotaType::DestinationStorage if self.limit_size.is_none() =>
+                return Err(Error::Custom("--limit-size is required for destination_storage".to_string())),
+            BacklogQuotaType::MessageAge if self.limit_time.is_none() =>
+                return Err(Error::Custom("--limit-time is required for message_age".to_string())),
+            _ => {}
+        }
+        let quota = BacklogQuota {
+            limit_size: self.limit_size,
+            limit_time: self.limit_time,
+            policy: self.policy,
+        };
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .set_backlog_quota(&namespace, self.quota_type, &quota)
+            .await?;
+        pulsar_ctx.output().message(format!("Set {} backlog quota of {}", self.quota_type.name(), namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct RemoveBacklogQuotaOpts {
+    namespace: Option<String>,
+
+    #[arg(long = "type", default_value = "destination_storage")]
+    quota_type: BacklogQuotaType,
+}
+
+#[async_trait]
+impl AsyncCmd for RemoveBacklogQuotaOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .remove_backlog_quota(&namespace, self.quota_type)
+            .await?;
+        pulsar_ctx.output().message(format!("Removed {} backlog quota of {}", self.quota_type.name(), namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetDispatchRateOpts<P: NamespacePolicy + From<DispatchRate> + Debug + Clone> {
+    namespace: Option<String>,
+
+    /// Messages per period, -1 for no limit
+    #[arg(long, default_value = "-1", allow_hyphen_values = true)]
+    msg_dispatch_rate: i32,
+
+    /// Bytes per period, -1 for no limit
+    #[arg(long, default_value = "-1", allow_hyphen_values = true)]
+    byte_dispatch_rate: i64,
+
+    /// Period in seconds
+    #[arg(long, default_value = "1")]
+    dispatch_rate_period: i32,
+
+    /// Make the rates relative to the publish rate
+    #[arg(long, default_value = "false")]
+    relative_to_publish_rate: bool,
+
+    #[arg(skip)]
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: NamespacePolicy + From<DispatchRate> + Debug + Clone> AsyncCmd for SetDispatchRateOpts<P> {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let rate = DispatchRate {
+            dispatch_throttling_rate_in_msg: self.msg_dispatch_rate,
+            dispatch_throttling_rate_in_byte: self.byte_dispatch_rate,
+            relative_to_publish_rate: self.relative_to_publish_rate,
+            rate_period_in_second: self.dispatch_rate_period,
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &P::from(rate)).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetSubscribeRateOpts {
+    namespace: Option<String>,
+
+    /// Subscribes per consumer and period, -1 for no limit
+    #[arg(long, default_value = "-1", allow_hyphen_values = true)]
+    subscribe_rate: i32,
+
+    /// Period in seconds
+    #[arg(long, default_value = "30")]
+    subscribe_rate_period: i32,
+}
+
+#[async_trait]
+impl AsyncCmd for SetSubscribeRateOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let rate = SubscribeRate {
+            subscribe_throttling_rate_per_consumer: self.subscribe_rate,
+            rate_period_in_second: self.subscribe_rate_period,
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &rate).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetPublishRateOpts {
+    namespace: Option<String>,
+
+    /// Messages per second, -1 for no limit
+    #[arg(long, default_value = "-1", allow_hyphen_values = true)]
+    msg_publish_rate: i32,
+
+    /// Bytes per second, -1 for no limit
+    #[arg(long, default_value = "-1", allow_hyphen_values = true)]
+    byte_publish_rate: i64,
+}
+
+#[async_trait]
+impl AsyncCmd for SetPublishRateOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let rate = PublishRate {
+            publish_throttling_rate_in_msg: self.msg_publish_rate,
+            publish_throttling_rate_in_byte: self.byte_publish_rate,
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &rate).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetFlagOpts<P: NamespacePolicy + From<bool> + Debug + Clone> {
+    namespace: Option<String>,
+
+    #[command(flatten)]
+    toggle: Toggle,
+
+    #[arg(skip)]
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: NamespacePolicy + From<bool> + Debug + Clone> AsyncCmd for SetFlagOpts<P> {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &P::from(self.toggle.enable)).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetSubscriptionExpirationTimeOpts {
+    namespace: Option<String>,
+
+    /// Minutes after which inactive subscriptions are deleted, 0 to never delete them
+    #[arg(short = 't', long)]
+    time: i32,
+}
+
+#[async_trait]
+impl AsyncCmd for SetSubscriptionExpirationTimeOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &SubscriptionExpirationTime(self.time)).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetMaxOpts<P: NamespacePolicy + From<i32> + Debug + Clone> {
+    namespace: Option<String>,
+
+    /// The maximum, 0 for no limit
+    #[arg(long)]
+    max: i32,
+
+    #[arg(skip)]
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: NamespacePolicy + From<i32> + Debug + Clone> AsyncCmd for SetMaxOpts<P> {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &P::from(self.max)).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetAutoTopicCreationOpts {
+    namespace: Option<String>,
+
+    #[command(flatten)]
+    toggle: Toggle,
+
+    #[arg(short = 't', long = "type", default_value = "non-partitioned")]
+    topic_type: TopicType,
+
+    /// Partitions of auto created partitioned topics
+    #[arg(short = 'n', long)]
+    num_partitions: Option<i32>,
+}
+
+#[async_trait]
+impl AsyncCmd for SetAutoTopicCreationOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        if self.toggle.enable && self.topic_type == TopicType::Partitioned && self.num_partitions.is_none() {
+            return Err(Error::Custom("--num-partitions is required for partitioned topics".to_string()));
+        }
+        let policy = AutoTopicCreationOverride {
+            allow_auto_topic_creation: self.toggle.enable,
+            topic_type: if self.toggle.enable { Some(self.topic_type) } else { None },
+            default_num_partitions: self.num_partitions.filter(|_| self.topic_type == TopicType::Partitioned),
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &policy).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetSchemaCompatibilityStrategyOpts {
+    namespace: Option<String>,
+
+    #[arg(short = 'c', long, ignore_case = true)]
+    compatibility: SchemaCompatibilityStrategy,
+}
+
+#[async_trait]
+impl AsyncCmd for SetSchemaCompatibilityStrategyOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &self.compatibility).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetDelayedDeliveryOpts {
+    namespace: Option<String>,
+
+    #[command(flatten)]
+    toggle: Toggle,
+
+    /// How often to check for messages due, e.g. 1s
+    #[arg(short = 't', long, default_value = "1s", value_parser = parse_millis)]
+    tick_time: i64,
+}
+
+#[async_trait]
+impl AsyncCmd for SetDelayedDeliveryOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let policy = DelayedDeliveryPolicies {
+            active: self.toggle.enable,
+            tick_time: self.tick_time,
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &policy).await
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SetInactiveTopicPoliciesOpts {
+    namespace: Option<String>,
+
+    // --enable to delete inactive topics
+    #[command(flatten)]
+    toggle: Toggle,
+
+    #[arg(short = 'm', long)]
+    delete_mode: InactiveTopicDeleteMode,
+
+    /// Time without producers, consumers and messages after which a topic is inactive, e.g. 1h
+    #[arg(short = 't', long, value_parser = parse_seconds)]
+    max_inactive_duration: i32,
+}
+
+#[async_trait]
+impl AsyncCmd for SetInactiveTopicPoliciesOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let policy = InactiveTopicPolicies {
+            inactive_topic_delete_mode: self.delete_mode,
+            max_inactive_duration_seconds: self.max_inactive_duration,
+            delete_while_inactive: self.toggle.enable,
+        };
+        set_policy(pulsar_ctx, self.namespace.as_deref(), &policy).await
+    }
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct UnsubscribeOpts {
-    namespace: String,
+    namespace: Option<String>,
 
     #[arg(long, default_value = "false")]
     dry_run: bool,
@@ -270,19 +916,23 @@ pub struct UnsubscribeOpts {
 #[async_trait]
 impl AsyncCmd for UnsubscribeOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let topic_pattern = self.topic_pattern.as_ref().map(|p| Regex::new(p).unwrap());
         let subscription_pattern = self.subscription_pattern.as_ref().map(|p| Regex::new(p).unwrap());
+        let output = pulsar_ctx.output();
         let admin = pulsar_ctx.admin().await?;
         let topics = admin.topics()
-            .list(&self.namespace, TopicDomain::Persistent)
+            .list(&namespace, TopicDomain::Persistent)
             .await?;
+        let mut results = Vec::new();
         for topic in topics {
             if let Some(p) = &topic_pattern {
                 if !p.is_match(&topic) {
                     continue;
                 }
             }
-            println!("Topic {}", topic);
+            info!("Topic {}", topic);
+            let topic = TopicName::parse(&topic)?;
             let stats = admin.topics().stats(&topic, true, true).await?;
             if let Some(subs) = stats.get("subscriptions") {
                 trace!("{}", subs);
@@ -293,23 +943,49 @@ impl AsyncCmd for UnsubscribeOpts {
                                 continue;
                             }
                         }
-                        println!("Subscription {}: backlog {}", name,
-                                 info.get("msgBacklog").unwrap().as_i64().unwrap());
+                        let backlog = info.get("msgBacklog").unwrap().as_i64().unwrap();
+                        info!("Subscription {}: backlog {}", name, backlog);
                         if !self.dry_run {
                             admin.topics().unsubscribe(&topic, &name, self.force).await?;
-                            println!("Unsubscribed {} of {}", name, topic);
+                            info!("Unsubscribed {} of {}", name, topic);
                         }
+                        results.push(UnsubscribeResult {
+                            topic: topic.to_string(),
+                            subscription: name.clone(),
+                            backlog,
+                            unsubscribed: !self.dry_run,
+                        });
                     }
                 }
             }
         }
+        output.render(&results)?;
         Ok(())
     }
 }
 
+#[derive(Serialize, Debug)]
+pub struct UnsubscribeResult {
+    topic: String,
+    subscription: String,
+    backlog: i64,
+    unsubscribed: bool,
+}
+
+impl Render for Vec<UnsubscribeResult> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["TOPIC", "SUBSCRIPTION", "BACKLOG", "STATUS"]);
+        for r in self {
+            let status = if r.unsubscribed { "unsubscribed" } else { "dry-run" };
+            table.add_row(vec![r.topic.clone(), r.subscription.clone(), r.backlog.to_string(), status.to_string()]);
+        }
+        vec![table]
+    }
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct DeleteTopicsOpts {
-    namespace: String,
+    namespace: Option<String>,
 
     #[arg(long, default_value = "false")]
     dry_run: bool,
@@ -324,24 +1000,32 @@ pub struct DeleteTopicsOpts {
 #[async_trait]
 impl AsyncCmd for DeleteTopicsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let topic_pattern = self.topic_pattern.as_ref().map(|p| Regex::new(p).unwrap());
+        let output = pulsar_ctx.output();
         let admin = pulsar_ctx.admin().await?;
         let topics = admin.topics()
-            .list_partitioned(&self.namespace, TopicDomain::Persistent)
+            .list_partitioned(&namespace, TopicDomain::Persistent)
             .await?;
+        let mut results = Vec::new();
         for topic in topics {
             if let Some(p) = &topic_pattern {
                 if !p.is_match(&topic) {
                     continue;
                 }
             }
-            println!("Topic {}", topic);
+            info!("Topic {}", topic);
             if !self.dry_run {
-                admin.topics().delete_partitioned_topic(&topic, self.force, true).await?;
+                admin.topics().delete_partitioned_topic(&TopicName::parse(&topic)?, self.force, true).await?;
             }
+            results.push(DeleteTopicResult {
+                topic,
+                partitioned: true,
+                deleted: !self.dry_run,
+            });
         }
         let topics = admin.topics()
-            .list(&self.namespace, TopicDomain::Persistent)
+            .list(&namespace, TopicDomain::Persistent)
             .await?;
         for topic in topics {
             if let Some(p) = &topic_pattern {
@@ -349,11 +1033,281 @@ impl AsyncCmd for DeleteTopicsOpts {
                     continue;
                 }
             }
-            println!("Topic {}", topic);
+            info!("Topic {}", topic);
             if !self.dry_run {
-                admin.topics().delete_topic(&topic, self.force, true).await?;
+                admin.topics().delete_topic(&TopicName::parse(&topic)?, self.force, true).await?;
+            }
+            results.push(DeleteTopicResult {
+                topic,
+                partitioned: false,
+                deleted: !self.dry_run,
+            });
+        }
+        output.render(&results)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DeleteTopicResult {
+    topic: String,
+    partitioned: bool,
+    deleted: bool,
+}
+
+impl Render for Vec<DeleteTopicResult> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["TOPIC", "PARTITIONED", "STATUS"]);
+        for r in self {
+            let status = if r.deleted { "deleted" } else { "dry-run" };
+            table.add_row(vec![r.topic.clone(), r.partitioned.to_string(), status.to_string()]);
+        }
+        vec![table]
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct BundlesOpts {
+    namespace: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for BundlesOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let r = pulsar_ctx.admin().await?
+            .namespaces()
+            .bundles(&namespace)
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UnloadOpts {
+    namespace: Option<String>,
+
+    /// Bundle to unload, e.g. 0x00000000_0x40000000, instead of the whole namespace
+    #[arg(short = 'b', long)]
+    bundle: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for UnloadOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let admin = pulsar_ctx.admin().await?;
+        match &self.bundle {
+            Some(bundle) => admin.namespaces().unload_bundle(&namespace, bundle).await?,
+            None => admin.namespaces().unload(&namespace).await?,
+        }
+        let what = match &self.bundle {
+            Some(bundle) => format!("bundle {} of {}", bundle, namespace),
+            None => namespace.to_string(),
+        };
+        pulsar_ctx.output().message(format!("Unloaded {}", what).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct SplitBundleOpts {
+    namespace: Option<String>,
+
+    /// Bundle to split, e.g. 0x00000000_0x40000000
+    #[arg(short = 'b', long)]
+    bundle: String,
+
+    #[arg(short = 'a', long, default_value = "range_equally_divide")]
+    algorithm: SplitAlgorithm,
+
+    /// Unload the new bundles, so the load manager can move them to other brokers
+    #[arg(short = 'u', long, default_value = "false")]
+    unload: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for SplitBundleOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .split_bundle(&namespace, self.bundle.as_str(), self.algorithm, self.unload)
+            .await?;
+        pulsar_ctx.output().message(format!("Split bundle {} of {}", self.bundle, namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ClearBacklogOpts {
+    namespace: Option<String>,
+
+    /// Only clear the backlog in this bundle
+    #[arg(short = 'b', long)]
+    bundle: Option<String>,
+
+    /// Only clear the backlog of this subscription
+    #[arg(short = 's', long)]
+    subscription: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for ClearBacklogOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        pulsar_ctx.admin().await?
+            .namespaces()
+            .clear_backlog(&namespace, self.bundle.as_deref(), self.subscription.as_deref())
+            .await?;
+        pulsar_ctx.output().message(format!("Cleared backlog of {}", namespace).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct BundleOwnershipOpts {
+    namespace: Option<String>,
+
+    /// Cluster of the brokers, needed when there are several
+    #[arg(short = 'c', long)]
+    cluster: Option<String>,
+}
+
+#[async_trait]
+impl AsyncCmd for BundleOwnershipOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let output = pulsar_ctx.output();
+        let admin = pulsar_ctx.admin().await?;
+        let cluster = match &self.cluster {
+            Some(cluster) => cluster.clone(),
+            None => {
+                let clusters = admin.clusters().list().await?;
+                match clusters.as_slice() {
+                    [cluster] => cluster.clone(),
+                    _ => return Err(Error::Custom(
+                        format!("several clusters {:?}, choose one with --cluster", clusters))),
+                }
+            }
+        };
+        let prefix = format!("{}/", namespace);
+        let mut results = Vec::new();
+        for broker in admin.brokers().active_brokers(cluster.as_str()).await? {
+            for (name, status) in admin.brokers().owned_namespaces(cluster.as_str(), broker.as_str()).await? {
+                if let Some(bundle) = name.strip_prefix(prefix.as_str()) {
+                    results.push(BundleOwnership {
+                        bundle: bundle.to_string(),
+                        broker: broker.clone(),
+                        assignment: status.broker_assignment,
+                        active: status.is_active,
+                    });
+                }
             }
         }
+        results.sort_by(|a, b| a.bundle.cmp(&b.bundle));
+        output.render(&results)?;
         Ok(())
     }
 }
+
+#[derive(Serialize, Debug)]
+pub struct BundleOwnership {
+    bundle: String,
+    broker: String,
+    assignment: String,
+    active: bool,
+}
+
+impl Render for Vec<BundleOwnership> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["BUNDLE", "BROKER", "ASSIGNMENT", "ACTIVE"]);
+        for r in self {
+            table.add_row(vec![r.bundle.clone(), r.broker.clone(), r.assignment.clone(), r.active.to_string()]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for BundlesData {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["BUNDLE"]);
+        for range in self.ranges() {
+            table.add_row(vec![range]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for RetentionPolicies {}
+
+impl Render for MessageTtl {}
+
+impl Render for DispatchRate {}
+
+impl Render for SubscriptionDispatchRate {}
+
+impl Render for ReplicatorDispatchRate {}
+
+impl Render for SubscribeRate {}
+
+impl Render for PublishRate {}
+
+impl Render for Deduplication {}
+
+impl Render for SubscriptionExpirationTime {}
+
+impl Render for MaxProducersPerTopic {}
+
+impl Render for MaxConsumersPerTopic {}
+
+impl Render for MaxSubscriptionsPerTopic {}
+
+impl Render for AutoTopicCreationOverride {}
+
+impl Render for AutoSubscriptionCreationOverride {}
+
+impl Render for SchemaCompatibilityStrategy {}
+
+impl Render for SchemaValidationEnforced {}
+
+impl Render for EncryptionRequired {}
+
+impl Render for DelayedDeliveryPolicies {}
+
+impl Render for InactiveTopicPolicies {}
+
+impl Render for BTreeMap<String, BacklogQuota> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["TYPE", "LIMIT_SIZE", "LIMIT_TIME", "POLICY"]);
+        for (quota_type, quota) in self {
+            let limit = |l: Option<String>| l.unwrap_or_default();
+            table.add_row(vec![quota_type.clone(), limit(quota.limit_size.map(|l| l.to_string())),
+                               limit(quota.limit_time.map(|l| l.to_string())),
+                               serde_json::to_value(quota.policy).map(|p| crate::output::cell(&p)).unwrap_or_default()]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for NamespacePolicies {}
+
+impl Render for PersistencePolicies {}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::namespaces::{parse_retention_time, parse_seconds, parse_size};
+
+    #[test]
+    fn sizes_and_times() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("10G").unwrap(), 10 << 30);
+        assert_eq!(parse_size("-1").unwrap(), -1);
+        assert!(parse_size("1x").is_err());
+        assert_eq!(parse_retention_time("7d").unwrap(), 7 * 24 * 60);
+        assert_eq!(parse_retention_time("-1").unwrap(), -1);
+        assert!(parse_size("9000000000T").is_err());
+        assert!(parse_seconds("70y").is_err());
+    }
+}
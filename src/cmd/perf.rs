@@ -61,7 +61,7 @@ impl AsyncCmd for PerfProduceOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
         let opts = crate::perf::PerfOpts {
             pulsar_config: pulsar_ctx.get_config().clone(),
-            producer_opts: self.producer_opts.clone(),
+            producer_opts: self.producer_opts.resolve(pulsar_ctx.get_config())?,
             rate: self.rate,
             parallelism: self.parallelism,
             num_clients: self.num_clients,
@@ -23,7 +23,8 @@ pub struct ProduceOpts {
 #[async_trait]
 impl AsyncCmd for ProduceOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let mut producer = self.producer_opts.producer_builder(pulsar_ctx.client().await?)?
+        let producer_opts = self.producer_opts.resolve(pulsar_ctx.get_config())?;
+        let mut producer = producer_opts.producer_builder(pulsar_ctx.client().await?)?
             .build()
             .await?;
 
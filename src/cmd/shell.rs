@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use clap::{Arg, CommandFactory, Parser};
+use rustyline::{Context, Editor, Helper};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::naming::{NamespaceName, TopicDomain, TopicName};
+use crate::config::{config_dir, ConfigOverrides, Configs};
+use crate::context::PulsarContext;
+use crate::error::Error;
+use crate::opts::Command;
+use crate::output::{Output, OutputFormat};
+
+const HISTORY_FILE: &str = "history";
+
+/// Names fetched for completion are reused for this long.
+const NAMES_TTL: Duration = Duration::from_secs(10);
+
+/// Completion gives up on a slow or unreachable admin service after this long.
+const NAMES_TIMEOUT: Duration = Duration::from_secs(3);
+
+const BUILTINS: [&str; 5] = ["use", "context", "help", "exit", "quit"];
+
+const BUILTINS_HELP: &str = "\
+Shell commands:
+  use [NAMESPACE]    Show or change the default namespace for short names
+  context [NAME]     Show or switch the context
+  help               Show this help
+  exit, quit         Leave the shell, as does Ctrl-D";
+
+/// Run commands interactively, reusing one connection to the cluster, with history and tab
+/// completion of commands and of tenant, namespace, topic and subscription names
+#[derive(Parser, Debug, Clone)]
+pub struct ShellOpts {
+    /// File to keep the history in, defaults to ~/.pulsar-smith/history
+    #[arg(long)]
+    pub history_file: Option<PathBuf>,
+}
+
+// A line of input, parsed with the commands of the command line.
+#[derive(Parser, Debug)]
+#[command(name = "pulsar-smith", no_binary_name = true)]
+struct ShellLine {
+    /// Output format of command results
+    #[arg(short = 'o', long, value_enum, global = true)]
+    output: Option<OutputFormat>,
+
+    #[command(subcommand)]
+    cmd: Command,
+}
+
+impl ShellOpts {
+    pub async fn run(&self, pulsar_ctx: PulsarContext, config_paths: &[PathBuf], overrides: &ConfigOverrides,
+                     context: Option<String>) -> Result<(), Error> {
+        let configs = Configs::load_merged(config_paths)?;
+        let mut shell = Shell {
+            output: pulsar_ctx.output(),
+            pulsar_ctx,
+            context: context.or_else(|| configs.current_context().map(String::from)),
+            config_paths,
+            overrides,
+        };
+        let mut editor = Editor::<ShellHelper>::new();
+        editor.set_helper(Some(ShellHelper::new()));
+        let history = self.history_file.clone().or_else(|| config_dir().map(|dir| dir.join(HISTORY_FILE)));
+        if let Some(history) = &history {
+            if editor.load_history(history).is_err() {
+                debug!("no shell history at {}", history.display());
+            }
+        }
+
+        loop {
+            shell.update_completion(editor.helper_mut().unwrap()).await;
+            let prompt = shell.prompt();
+            let line = match tokio::task::block_in_place(|| editor.readline(prompt.as_str())) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(Error::Custom(format!("failed to read input: {}", e))),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            editor.add_history_entry(line);
+            match shell.execute(line).await {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(e) => eprintln!("error: {}", e),
+            }
+        }
+
+        if let Some(history) = &history {
+            if let Some(parent) = history.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            if let Err(e) = editor.save_history(history) {
+                warn!("failed to save the shell history to {}: {}", history.display(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Shell<'a> {
+    pulsar_ctx: PulsarContext,
+    /// Name of the current context, if it comes from the config.
+    context: Option<String>,
+    /// Output format for commands without `-o`.
+    output: Output,
+    config_paths: &'a [PathBuf],
+    overrides: &'a ConfigOverrides,
+}
+
+impl<'a> Shell<'a> {
+    fn prompt(&self) -> String {
+        format!("{}:{}> ", self.context.as_deref().unwrap_or("pulsar-smith"),
+                self.pulsar_ctx.get_config().default_namespace())
+    }
+
+    /// Runs a line of input, returning whether to leave the shell.
+    async fn execute(&mut self, line: &str) -> Result<bool, Error> {
+        let words = split_line(line)?;
+        match words[0].as_str() {
+            "exit" | "quit" => return Ok(true),
+            "help" if words.len() == 1 => {
+                ShellLine::command().print_help()?;
+                println!("\n{}", BUILTINS_HELP);
+            }
+            "use" => self.use_namespace(words.get(1))?,
+            "context" => self.switch_context(words.get(1))?,
+            _ => self.run_command(words).await?,
+        }
+        Ok(false)
+    }
+
+    fn use_namespace(&mut self, namespace: Option<&String>) -> Result<(), Error> {
+        match namespace {
+            Some(namespace) => {
+                let namespace = self.pulsar_ctx.get_config().resolve_namespace(Some(namespace.as_str()))?;
+                self.pulsar_ctx.set_namespace(&namespace);
+            }
+            None => println!("{}", self.pulsar_ctx.get_config().default_namespace()),
+        }
+        Ok(())
+    }
+
+    /// Replaces the context, and with it the pulsar client and admin.
+    fn switch_context(&mut self, name: Option<&String>) -> Result<(), Error> {
+        let name = match name {
+            Some(name) => name,
+            None => {
+                println!("{}", self.context.as_deref().unwrap_or("none"));
+                return Ok(());
+            }
+        };
+        let cfg = Configs::load_merged(self.config_paths)?.get_pulsar_config(name.as_str(), self.overrides)?;
+        self.pulsar_ctx = cfg.into();
+        self.context = Some(name.clone());
+        Ok(())
+    }
+
+    async fn run_command(&mut self, words: Vec<String>) -> Result<(), Error> {
+        let line = match ShellLine::try_parse_from(words) {
+            Ok(line) => line,
+            Err(e) => {
+                e.print()?;
+                return Ok(());
+            }
+        };
+        let output = line.output.map(Output::new).unwrap_or(self.output);
+        match &line.cmd {
+            Command::Config(opts) => opts.run(self.config_paths, output),
+            Command::Migrate(opts) => opts.run(self.config_paths, output).await,
+            Command::Shell(_) => Err(Error::Custom("already in a shell".to_string())),
+            cmd => {
+                self.pulsar_ctx.set_output(output);
+                cmd.as_async_cmd().unwrap().run(&mut self.pulsar_ctx).await
+            }
+        }
+    }
+
+    async fn update_completion(&mut self, helper: &mut ShellHelper) {
+        let admin = self.pulsar_ctx.admin().await.ok().cloned();
+        let contexts = Configs::load_merged(self.config_paths)
+            .map(|configs| configs.contexts().iter().map(|c| c.name.clone()).collect())
+            .unwrap_or_default();
+        let mut state = helper.state.lock().unwrap();
+        if admin.as_ref().map(|a| a.service_url()) != state.admin.as_ref().map(|a| a.service_url()) {
+            state.names.clear();
+        }
+        state.admin = admin;
+        state.namespace = self.pulsar_ctx.get_config().resolve_namespace(None).ok();
+        state.contexts = contexts;
+    }
+}
+
+/// Splits a line into words, with single and double quotes and backslash escapes like a
+/// POSIX shell, but no expansions.
+fn split_line(line: &str) -> Result<Vec<String>, Error> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.get_or_insert_with(String::new).push(c),
+            (_, '\\') => {
+                let escaped = chars.next().ok_or_else(|| Error::Custom("trailing backslash".to_string()))?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(quote) = quote {
+        return Err(Error::Custom(format!("unterminated {} quote", quote)));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Live names offered for the value of an argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Names {
+    Tenants,
+    Namespaces(String),
+    Topics(NamespaceName),
+    Subscriptions(TopicName),
+}
+
+impl Names {
+    async fn fetch(&self, admin: &PulsarAdmin) -> Result<Vec<String>, crate::admin::error::Error> {
+        match self {
+            Names::Tenants => admin.tenants().list().await,
+            Names::Namespaces(tenant) => admin.namespaces().list(tenant.as_str()).await,
+            Names::Topics(namespace) => {
+                let mut topics = admin.topics().list_partitioned(namespace, TopicDomain::Persistent).await?;
+                topics.extend(admin.topics().list(namespace, TopicDomain::Persistent).await?.into_iter()
+                    .filter(|t| TopicName::parse(t).map(|t| !t.is_partitioned()).unwrap_or(true)));
+                Ok(topics)
+            }
+            Names::Subscriptions(topic) => admin.topics().subscriptions(topic).await,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CompletionState {
+    admin: Option<PulsarAdmin>,
+    /// The default namespace, for short topic names.
+    namespace: Option<NamespaceName>,
+    contexts: Vec<String>,
+    names: HashMap<Names, (Instant, Vec<String>)>,
+}
+
+/// Completes builtins, subcommands and flags from the clap command tree, and the values of
+/// `tenant`, `namespace`, `topic` and `subscription` arguments with names from the admin API.
+struct ShellHelper {
+    commands: clap::Command,
+    state: Mutex<CompletionState>,
+}
+
+impl ShellHelper {
+    fn new() -> ShellHelper {
+        let mut commands = ShellLine::command();
+        commands.build();
+        ShellHelper {
+            commands,
+            state: Mutex::new(CompletionState::default()),
+        }
+    }
+
+    fn candidates(&self, words: &[String], current: &str) -> Vec<String> {
+        match words.first().map(String::as_str) {
+            None => BUILTINS.iter().map(|b| b.to_string())
+                .chain(subcommands(&self.commands))
+                .collect(),
+            Some("use") if words.len() == 1 => self.namespaces(current),
+            Some("context") if words.len() == 1 => self.state.lock().unwrap().contexts.clone(),
+            Some(builtin) if BUILTINS.contains(&builtin) => Vec::new(),
+            Some(_) => self.command_candidates(words, current),
+        }
+    }
+
+    /// Walks the command tree along the words, then completes whatever comes next: a flag's
+    /// value, a flag, a subcommand or a positional argument.
+    fn command_candidates(&self, words: &[String], current: &str) -> Vec<String> {
+        let mut cmd = &self.commands;
+        let mut positionals = 0;
+        let mut pending: Option<&Arg> = None;
+        let mut values: HashMap<String, String> = HashMap::new();
+        for word in words {
+            if let Some(arg) = pending.take() {
+                values.insert(arg.get_id().to_string(), word.clone());
+            } else if let Some(subcommand) = cmd.find_subcommand(word) {
+                cmd = subcommand;
+                positionals = 0;
+            } else if word.starts_with('-') {
+                let (flag, inline_value) = match word.split_once('=') {
+                    Some((flag, value)) => (flag, Some(value)),
+                    None => (word.as_str(), None),
+                };
+                let arg = cmd.get_arguments().find(|a| match flag.strip_prefix("--") {
+                    Some(long) => a.get_long() == Some(long),
+                    None => flag.len() == 2 && a.get_short() == flag.chars().nth(1),
+                });
+                if let Some(arg) = arg.filter(|a| a.get_action().takes_values()) {
+                    match inline_value {
+                        Some(value) => {
+                            values.insert(arg.get_id().to_string(), value.to_string());
+                        }
+                        None => pending = Some(arg),
+                    }
+                }
+            } else {
+                if let Some(arg) = cmd.get_positionals().nth(positionals) {
+                    values.insert(arg.get_id().to_string(), word.clone());
+                }
+                positionals += 1;
+            }
+        }
+
+        let arg = match pending {
+            Some(arg) => arg,
+            None if current.starts_with('-') => return cmd.get_arguments()
+                .filter(|a| !a.is_positional() && !a.is_hide_set())
+                .filter_map(|a| a.get_long().map(|long| format!("--{}", long)))
+                .collect(),
+            None if cmd.has_subcommands() => return subcommands(cmd).collect(),
+            None => match cmd.get_positionals().nth(positionals) {
+                Some(arg) => arg,
+                None => return Vec::new(),
+            },
+        };
+        let possible_values = arg.get_possible_values();
+        if !possible_values.is_empty() {
+            return possible_values.iter()
+                .filter(|v| !v.is_hide_set())
+                .map(|v| v.get_name().to_string())
+                .collect();
+        }
+        match arg.get_id().as_str() {
+            "tenant" => self.names(Names::Tenants),
+            "namespace" => self.namespaces(current),
+            "topic" => self.topics(current),
+            "subscription" | "subscription_name" => {
+                let namespace = self.state.lock().unwrap().namespace.clone();
+                let topic = values.get("topic")
+                    .and_then(|t| namespace.and_then(|ns| TopicName::parse_in(t, &ns).ok()));
+                match topic {
+                    Some(topic) => self.names(Names::Subscriptions(topic)),
+                    None => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Namespaces of the tenant being typed, or of the default tenant along with the tenants.
+    fn namespaces(&self, current: &str) -> Vec<String> {
+        if let Some((tenant, _)) = current.split_once('/') {
+            return self.names(Names::Namespaces(tenant.to_string()));
+        }
+        let tenant = self.state.lock().unwrap().namespace.as_ref().map(|ns| ns.tenant().to_string());
+        let mut namespaces: Vec<String> = self.names(Names::Tenants).into_iter().map(|t| t + "/").collect();
+        if let Some(tenant) = tenant {
+            namespaces.extend(self.names(Names::Namespaces(tenant)));
+        }
+        namespaces
+    }
+
+    /// Topics in the same form as typed so far: full names, `tenant/namespace/topic`, or short
+    /// names in the default namespace.
+    fn topics(&self, current: &str) -> Vec<String> {
+        let (prefix, rest) = match current.split_once("://") {
+            Some((domain, rest)) => (Some(domain), rest),
+            None => (None, current),
+        };
+        let parts: Vec<&str> = rest.split('/').collect();
+        let namespace = if parts.len() >= 3 {
+            NamespaceName::parse(format!("{}/{}", parts[0], parts[1]).as_str()).ok()
+        } else if prefix.is_some() || parts.len() == 2 {
+            // still typing the namespace
+            let namespaces = self.namespaces(rest);
+            return namespaces.into_iter()
+                .map(|ns| match prefix {
+                    Some(domain) => format!("{}://{}", domain, ns),
+                    None => ns,
+                })
+                .collect();
+        } else {
+            self.state.lock().unwrap().namespace.clone()
+        };
+        let namespace = match namespace {
+            Some(namespace) => namespace,
+            None => return Vec::new(),
+        };
+        self.names(Names::Topics(namespace)).into_iter()
+            .filter_map(|name| TopicName::parse(name.as_str()).ok())
+            .map(|topic| match (prefix, parts.len()) {
+                (Some(_), _) => topic.to_string(),
+                (None, 1) => topic.local_name().to_string(),
+                (None, _) => format!("{}/{}", topic.namespace(), topic.local_name()),
+            })
+            .collect()
+    }
+
+    /// Names from the admin API, cached for a little while, or none if it cannot tell.
+    fn names(&self, names: Names) -> Vec<String> {
+        let admin = {
+            let state = self.state.lock().unwrap();
+            if let Some((fetched, cached)) = state.names.get(&names) {
+                if fetched.elapsed() < NAMES_TTL {
+                    return cached.clone();
+                }
+            }
+            match &state.admin {
+                Some(admin) => admin.clone(),
+                None => return Vec::new(),
+            }
+        };
+        let fetched = tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(
+            tokio::time::timeout(NAMES_TIMEOUT, names.fetch(&admin))));
+        match fetched {
+            Ok(Ok(fetched)) => {
+                self.state.lock().unwrap().names.insert(names, (Instant::now(), fetched.clone()));
+                fetched
+            }
+            Ok(Err(e)) => {
+                debug!("failed to fetch {:?} for completion: {}", names, e);
+                Vec::new()
+            }
+            Err(_) => {
+                debug!("timed out fetching {:?} for completion", names);
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn subcommands(cmd: &clap::Command) -> impl Iterator<Item = String> + '_ {
+    cmd.get_subcommands()
+        .filter(|c| !c.is_hide_set())
+        .map(|c| c.get_name().to_string())
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let words = split_line(&line[..start]).unwrap_or_default();
+        let current = &line[start..];
+        let mut candidates: Vec<String> = self.candidates(&words, current).into_iter()
+            .filter(|c| c.starts_with(current))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates.into_iter()
+            .map(|c| Pair { display: c.clone(), replacement: c })
+            .collect()))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+#[cfg(test)]
+mod tests {
+    use rustyline::completion::Completer;
+    use rustyline::Context;
+    use rustyline::history::History;
+
+    use crate::admin::admin::PulsarAdmin;
+    use crate::admin::naming::NamespaceName;
+    use crate::cmd::shell::{ShellHelper, split_line};
+    use crate::testing::{MockResponse, MockServer};
+
+    #[test]
+    fn split() {
+        assert_eq!(split_line("  topics   list ").unwrap(), vec!["topics", "list"]);
+        assert_eq!(split_line(r#"produce -m "hello world" -m 'it''s' a\ b """#).unwrap(),
+                   vec!["produce", "-m", "hello world", "-m", "its", "a b", ""]);
+        assert!(split_line("produce -m \"hello").is_err());
+    }
+
+    /// Where the replacement starts, and the candidates completing `line` at `pos`.
+    fn complete_at(helper: &ShellHelper, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let history = History::new();
+        let (start, pairs) = helper.complete(line, pos, &Context::new(&history)).unwrap();
+        (start, pairs.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    fn complete(helper: &ShellHelper, line: &str) -> Vec<String> {
+        complete_at(helper, line, line.len()).1
+    }
+
+    #[test]
+    fn commands_and_flags() {
+        let helper = ShellHelper::new();
+        assert!(complete(&helper, "").contains(&"use".to_string()));
+        assert_eq!(complete(&helper, "to"), vec!["topics"]);
+        assert!(complete(&helper, "topics ").contains(&"list".to_string()));
+        assert_eq!(complete(&helper, "topics list --d"), vec!["--domain"]);
+        assert_eq!(complete(&helper, "topics list -o j"), vec!["json"]);
+        assert!(complete(&helper, "topics lookup ").is_empty());
+
+        // words are split on any whitespace, and only the line before the cursor counts
+        assert_eq!(complete_at(&helper, "topics\t loo", 11), (8, vec!["lookup".to_string()]));
+        assert_eq!(complete_at(&helper, "to list", 2), (0, vec!["topics".to_string()]));
+        let flags = complete(&helper, "topics list -");
+        let mut sorted = flags.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(flags, sorted);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn live_names() {
+        let server = MockServer::start(|req, _| match req.path.as_str() {
+            "/admin/v2/tenants" => MockResponse::new(200, r#"["acme","public"]"#),
+            "/admin/v2/namespaces/acme" => MockResponse::new(200, r#"["acme/orders","acme/billing"]"#),
+            "/admin/v2/persistent/acme/orders/partitioned" => MockResponse::new(200, r#"["persistent://acme/orders/events"]"#),
+            "/admin/v2/persistent/acme/orders" => MockResponse::new(200,
+                r#"["persistent://acme/orders/events-partition-0","persistent://acme/orders/audit"]"#),
+            "/admin/v2/persistent/acme/orders/audit/subscriptions" => MockResponse::new(200, r#"["archiver"]"#),
+            _ => MockResponse::new(404, "{}"),
+        }).await;
+        let helper = ShellHelper::new();
+        {
+            let mut state = helper.state.lock().unwrap();
+            state.admin = Some(PulsarAdmin::builder(server.url().to_string()).build().unwrap());
+            state.namespace = Some(NamespaceName::parse("acme/orders").unwrap());
+        }
+        assert_eq!(complete(&helper, "use acme/"), vec!["acme/billing", "acme/orders"]);
+        assert_eq!(complete(&helper, "namespaces list "), vec!["acme", "public"]);
+        assert_eq!(complete(&helper, "topics lookup "), vec!["audit", "events"]);
+        assert_eq!(complete(&helper, "topics lookup acme/orders/a"), vec!["acme/orders/audit"]);
+        assert_eq!(complete(&helper, "topics lookup persistent://acme/orders/e"), vec!["persistent://acme/orders/events"]);
+        assert_eq!(complete(&helper, "topics unsubscribe audit -s "), vec!["archiver"]);
+
+        // names are cached for a while
+        let requests = server.requests().len();
+        complete(&helper, "topics lookup ");
+        assert_eq!(server.requests().len(), requests);
+    }
+}
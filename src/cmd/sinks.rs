@@ -1,9 +1,12 @@
 use async_trait::async_trait;
 use clap::Parser;
 
+use crate::admin::naming::NamespaceName;
+use crate::admin::sinks::SinkDef;
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
 use crate::error::Error;
+use crate::output::{Render, Table};
 
 #[derive(Parser, Debug, Clone)]
 pub struct SinksOpts {
@@ -32,20 +35,26 @@ pub enum Command {
 #[derive(Parser, Debug, Clone)]
 pub struct ListOpts {
     #[arg(long)]
-    pub tenant: String,
+    pub tenant: Option<String>,
 
     #[arg(long)]
-    pub namespace: String,
+    pub namespace: Option<String>,
 }
 
 #[async_trait]
 impl AsyncCmd for ListOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = match (&self.tenant, &self.namespace) {
+            (Some(tenant), Some(namespace)) => NamespaceName::new(tenant, namespace)?,
+            (None, Some(namespace)) => pulsar_ctx.get_config().resolve_namespace(Some(namespace.as_str()))?,
+            (Some(tenant), None) => NamespaceName::new(tenant, "default")?,
+            (None, None) => pulsar_ctx.get_config().resolve_namespace(None)?,
+        };
         let r = pulsar_ctx.admin().await?
             .sinks()
-            .list(format!("{}/{}", self.tenant, self.namespace).as_str())
+            .list(&namespace)
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -60,7 +69,26 @@ impl AsyncCmd for AvailableSinksOpts {
             .sinks()
             .builtin_sinks()
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
+
+impl Render for Vec<SinkDef> {
+    fn tables(&self, wide: bool) -> Vec<Table> {
+        let mut table = if wide {
+            Table::new(&["NAME", "VERSION", "SINK CLASS", "DESCRIPTION"])
+        } else {
+            Table::new(&["NAME", "DESCRIPTION"])
+        };
+        for sink in self {
+            if wide {
+                table.add_row(vec![sink.name.clone(), sink.version.clone(),
+                                   sink.sink_class.clone().unwrap_or_default(), sink.description.clone()]);
+            } else {
+                table.add_row(vec![sink.name.clone(), sink.description.clone()]);
+            }
+        }
+        vec![table]
+    }
+}
@@ -1,10 +1,17 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
-use clap::Parser;
+use clap::{ArgGroup, Parser};
+use serde::Serialize;
 
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::functions::workers_list;
+use crate::admin::naming::{NamespaceName, TopicDomain, TopicName};
 use crate::admin::tenants::TenantInfo;
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
 use crate::error::Error;
+use crate::output::{Render, Table};
 
 #[derive(Parser, Debug, Clone)]
 pub struct TenantsOpts {
@@ -19,6 +26,8 @@ impl AsyncCmd for TenantsOpts {
             Command::List(opts) => opts,
             Command::Create(opts) => opts,
             Command::Get(opts) => opts,
+            Command::Update(opts) => opts,
+            Command::Delete(opts) => opts,
         };
         cmd.run(pulsar_ctx).await?;
         Ok(())
@@ -30,6 +39,9 @@ pub enum Command {
     List(ListOpts),
     Create(CreateOpts),
     Get(GetOpts),
+    /// Add or remove admin roles and allowed clusters of a tenant
+    Update(UpdateOpts),
+    Delete(DeleteOpts),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -42,7 +54,7 @@ impl AsyncCmd for ListOpts {
             .tenants()
             .list()
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -61,14 +73,14 @@ pub struct CreateOpts {
 #[async_trait]
 impl AsyncCmd for CreateOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let r = pulsar_ctx.admin().await?
+        pulsar_ctx.admin().await?
             .tenants()
             .create(self.tenant.as_str(), TenantInfo {
                 admin_roles: self.admin_roles.clone().unwrap_or(Vec::new()),
                 allowed_clusters: self.allowed_clusters.clone().unwrap_or(Vec::new()),
             })
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().message(format!("Created tenant {}", self.tenant).as_str());
         Ok(())
     }
 }
@@ -86,7 +98,284 @@ impl AsyncCmd for GetOpts {
             .tenants()
             .get(self.tenant.as_str())
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(group(ArgGroup::new("changes").required(true).multiple(true)))]
+pub struct UpdateOpts {
+    pub tenant: String,
+
+    #[arg(long = "add-admin-role", group = "changes")]
+    pub add_admin_roles: Vec<String>,
+
+    #[arg(long = "remove-admin-role", group = "changes")]
+    pub remove_admin_roles: Vec<String>,
+
+    #[arg(long = "add-allowed-cluster", group = "changes")]
+    pub add_allowed_clusters: Vec<String>,
+
+    #[arg(long = "remove-allowed-cluster", group = "changes")]
+    pub remove_allowed_clusters: Vec<String>,
+}
+
+impl UpdateOpts {
+    fn apply(&self, info: &mut TenantInfo) {
+        fn update(values: &mut Vec<String>, add: &[String], remove: &[String]) {
+            values.retain(|v| !remove.contains(v));
+            for v in add {
+                if !values.contains(v) {
+                    values.push(v.clone());
+                }
+            }
+        }
+        update(&mut info.admin_roles, &self.add_admin_roles, &self.remove_admin_roles);
+        update(&mut info.allowed_clusters, &self.add_allowed_clusters, &self.remove_allowed_clusters);
+    }
+}
+
+#[async_trait]
+impl AsyncCmd for UpdateOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let admin = pulsar_ctx.admin().await?;
+        let mut info = admin.tenants().get(self.tenant.as_str()).await?;
+        self.apply(&mut info);
+        admin.tenants().update(self.tenant.as_str(), &info).await?;
+        pulsar_ctx.output().message(format!("Updated tenant {}", self.tenant).as_str());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct DeleteOpts {
+    pub tenant: String,
+
+    /// Delete the functions, sinks, topics and namespaces of the tenant first
+    #[arg(long, default_value = "false")]
+    cascade: bool,
+
+    /// Only list what --cascade would delete
+    #[arg(long, default_value = "false", requires = "cascade")]
+    dry_run: bool,
+
+    /// Delete topics even if they have producers or consumers connected
+    #[arg(long, default_value = "false")]
+    force: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for DeleteOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let output = pulsar_ctx.output();
+        let admin = pulsar_ctx.admin().await?;
+        if !self.cascade {
+            admin.tenants().delete(self.tenant.as_str(), false).await?;
+            output.message(format!("Deleted tenant {}", self.tenant).as_str());
+            return Ok(());
+        }
+        let (results, result) = self.delete_all(admin, cascade(admin, self.tenant.as_str()).await?).await;
+        output.render(&results)?;
+        result
+    }
+}
+
+impl DeleteOpts {
+    /// Deletes the resources one by one and reports the status of each, with the first failure,
+    /// so that the report also shows what was deleted before it.
+    async fn delete_all(&self, admin: &PulsarAdmin, resources: Vec<Resource>) -> (Vec<DeleteResult>, Result<(), Error>) {
+        let mut results = Vec::new();
+        let mut result = Ok(());
+        for resource in resources {
+            let status = if self.dry_run {
+                "dry-run"
+            } else if result.is_err() {
+                "not deleted"
+            } else {
+                info!("{} {}", resource.kind(), resource.name());
+                match resource.delete(admin, self.force).await {
+                    Ok(()) => "deleted",
+                    Err(e) => {
+                        result = Err(e);
+                        "failed"
+                    }
+                }
+            };
+            results.push(DeleteResult {
+                kind: resource.kind().to_string(),
+                name: resource.name(),
+                status,
+            });
+        }
+        (results, result)
+    }
+}
+
+/// Something under a tenant that `delete --cascade` removes.
+#[derive(Debug, PartialEq)]
+enum Resource {
+    Function(NamespaceName, String),
+    Sink(NamespaceName, String),
+    PartitionedTopic(TopicName),
+    Topic(TopicName),
+    Namespace(NamespaceName),
+    Tenant(String),
+}
+
+impl Resource {
+    fn kind(&self) -> &'static str {
+        match self {
+            Resource::Function(..) => "function",
+            Resource::Sink(..) => "sink",
+            Resource::PartitionedTopic(_) => "partitioned-topic",
+            Resource::Topic(_) => "topic",
+            Resource::Namespace(_) => "namespace",
+            Resource::Tenant(_) => "tenant",
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Resource::Function(namespace, name) | Resource::Sink(namespace, name) => format!("{}/{}", namespace, name),
+            Resource::PartitionedTopic(topic) | Resource::Topic(topic) => topic.to_string(),
+            Resource::Namespace(namespace) => namespace.to_string(),
+            Resource::Tenant(tenant) => tenant.clone(),
+        }
+    }
+
+    async fn delete(&self, admin: &PulsarAdmin, force: bool) -> Result<(), Error> {
+        match self {
+            Resource::Function(namespace, name) => admin.functions().delete(namespace, name).await?,
+            Resource::Sink(namespace, name) => admin.sinks().delete(namespace, name).await?,
+            Resource::PartitionedTopic(topic) => admin.topics().delete_partitioned_topic(topic, force, true).await?,
+            Resource::Topic(topic) => admin.topics().delete_topic(topic, force, true).await?,
+            Resource::Namespace(namespace) => admin.namespaces().delete(namespace, false).await?,
+            Resource::Tenant(tenant) => admin.tenants().delete(tenant, false).await?,
+        }
         Ok(())
     }
 }
+
+/// Everything under a tenant in the order it can be deleted: functions and sinks before the
+/// topics they use, topics before their namespace and namespaces before the tenant.
+async fn cascade(admin: &PulsarAdmin, tenant: &str) -> Result<Vec<Resource>, Error> {
+    let mut resources = Vec::new();
+    for namespace in admin.namespaces().list(tenant).await? {
+        let namespace = NamespaceName::parse(namespace.as_str())?;
+        for function in workers_list(admin.functions().list(&namespace).await)? {
+            resources.push(Resource::Function(namespace.clone(), function));
+        }
+        for sink in workers_list(admin.sinks().list(&namespace).await)? {
+            resources.push(Resource::Sink(namespace.clone(), sink));
+        }
+        for domain in [TopicDomain::Persistent, TopicDomain::NonPersistent] {
+            let mut partitioned = HashSet::new();
+            for topic in admin.topics().list_partitioned(&namespace, domain).await? {
+                let topic = TopicName::parse(topic.as_str())?;
+                partitioned.insert(topic.to_string());
+                resources.push(Resource::PartitionedTopic(topic));
+            }
+            for topic in admin.topics().list(&namespace, domain).await? {
+                let topic = TopicName::parse(topic.as_str())?;
+                // partitions go away with their partitioned topic
+                if topic.partition_index().is_none() || !partitioned.contains(&topic.partitioned_topic_name().to_string()) {
+                    resources.push(Resource::Topic(topic));
+                }
+            }
+        }
+        resources.push(Resource::Namespace(namespace));
+    }
+    resources.push(Resource::Tenant(tenant.to_string()));
+    Ok(resources)
+}
+
+#[derive(Serialize, Debug)]
+pub struct DeleteResult {
+    kind: String,
+    name: String,
+    status: &'static str,
+}
+
+impl Render for Vec<DeleteResult> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["KIND", "NAME", "STATUS"]);
+        for r in self {
+            table.add_row(vec![r.kind.clone(), r.name.clone(), r.status.to_string()]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for TenantInfo {}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::admin::admin::PulsarAdmin;
+    use crate::cmd::tenants::{cascade, DeleteOpts};
+    use crate::testing::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn cascade_order() {
+        let server = MockServer::start(|req, _| match req.path.as_str() {
+            "/admin/v2/namespaces/acme" => MockResponse::new(200, r#"["acme/orders"]"#),
+            "/admin/v3/functions/acme/orders" => MockResponse::new(200, r#"["enrich"]"#),
+            "/admin/v3/sinks/acme/orders" => MockResponse::new(409, r#"{"reason":"Function worker service is not enabled"}"#),
+            "/admin/v2/persistent/acme/orders/partitioned" => MockResponse::new(200, r#"["persistent://acme/orders/in"]"#),
+            "/admin/v2/persistent/acme/orders" => MockResponse::new(200,
+                r#"["persistent://acme/orders/in-partition-0","persistent://acme/orders/in-partition-1","persistent://acme/orders/dlq"]"#),
+            _ => MockResponse::new(200, "[]"),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+
+        let resources = cascade(&admin, "acme").await.unwrap();
+        let names: Vec<String> = resources.iter().map(|r| format!("{} {}", r.kind(), r.name())).collect();
+        assert_eq!(names, vec![
+            "function acme/orders/enrich",
+            "partitioned-topic persistent://acme/orders/in",
+            "topic persistent://acme/orders/dlq",
+            "namespace acme/orders",
+            "tenant acme",
+        ]);
+        for resource in &resources {
+            resource.delete(&admin, true).await.unwrap();
+        }
+        let deletes: Vec<String> = server.requests().into_iter()
+            .filter(|r| r.method == "DELETE")
+            .map(|r| r.path)
+            .collect();
+        assert_eq!(deletes, vec![
+            "/admin/v3/functions/acme/orders/enrich",
+            "/admin/v2/persistent/acme/orders/in/partitions?force=true&deleteSchema=true",
+            "/admin/v2/persistent/acme/orders/dlq?force=true&deleteSchema=true",
+            "/admin/v2/namespaces/acme/orders?force=false",
+            "/admin/v2/tenants/acme?force=false",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn cascade_reports_failure() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/namespaces/acme") => MockResponse::new(200, r#"["acme/orders"]"#),
+            ("GET", "/admin/v2/persistent/acme/orders") => MockResponse::new(200, r#"["persistent://acme/orders/dlq"]"#),
+            ("GET", _) => MockResponse::new(200, "[]"),
+            ("DELETE", path) if path.starts_with("/admin/v2/namespaces/acme/orders?") =>
+                MockResponse::new(409, r#"{"reason":"Cannot delete non empty namespace"}"#),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let opts = DeleteOpts::try_parse_from(["delete", "acme", "--cascade"]).unwrap();
+
+        let (results, result) = opts.delete_all(&admin, cascade(&admin, "acme").await.unwrap()).await;
+        let statuses: Vec<String> = results.iter().map(|r| format!("{} {}", r.name, r.status)).collect();
+        assert_eq!(statuses, vec![
+            "persistent://acme/orders/dlq deleted",
+            "acme/orders failed",
+            "acme not deleted",
+        ]);
+        assert!(result.is_err());
+        assert!(!server.requests().iter().any(|r| r.method == "DELETE" && r.path.starts_with("/admin/v2/tenants/")));
+    }
+}
@@ -1,10 +1,16 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 use clap::Parser;
+use serde::Serialize;
+use serde_json::Value;
 
-use crate::admin::topics::TopicDomain;
+use crate::admin::naming::{TopicDomain, TopicName};
 use crate::cmd::cmd::AsyncCmd;
 use crate::context::PulsarContext;
+use crate::admin::topics::{LookupResponse, PartitionedTopicMetadata};
 use crate::error::Error;
+use crate::output::{cell, Render, Table};
 
 #[derive(Parser, Debug, Clone)]
 pub struct TopicsOpts {
@@ -28,6 +34,9 @@ impl AsyncCmd for TopicsOpts {
             Command::RevokePermissions(opts) => opts,
             Command::Subscriptions(opts) => opts,
             Command::Unsubscribe(opts) => opts,
+            Command::GetPartitionedMetadata(opts) => opts,
+            Command::UpdatePartitions(opts) => opts,
+            Command::CreateMissedPartitions(opts) => opts,
         };
         cmd.run(pulsar_ctx).await?;
         Ok(())
@@ -48,31 +57,83 @@ pub enum Command {
     RevokePermissions(RevokePermissionsOpts),
     Subscriptions(SubscriptionsOpts),
     Unsubscribe(UnsubscribeOpts),
+    GetPartitionedMetadata(GetPartitionedMetadataOpts),
+    UpdatePartitions(UpdatePartitionsOpts),
+    CreateMissedPartitions(CreateMissedPartitionsOpts),
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct ListOpts {
-    pub namespace: String,
+    pub namespace: Option<String>,
 
     #[arg(short = 'd', long, default_value = "Persistent")]
     pub domain: String,
+
+    /// Show partitioned topics once with their number of partitions instead of each partition
+    #[arg(short = 'c', long)]
+    pub collapse_partitions: bool,
 }
 
 #[async_trait]
 impl AsyncCmd for ListOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
-        let r = pulsar_ctx.admin().await?
-            .topics()
-            .list(self.namespace.as_str(), TopicDomain::parse(self.domain.as_ref())?)
-            .await?;
-        println!("{:?}", r);
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
+        let domain = TopicDomain::parse(self.domain.as_ref())?;
+        let admin = pulsar_ctx.admin().await?;
+        let r = admin.topics().list(&namespace, domain).await?;
+        if !self.collapse_partitions {
+            pulsar_ctx.output().render(&r)?;
+            return Ok(());
+        }
+        let mut partitioned = Vec::new();
+        for topic in admin.topics().list_partitioned(&namespace, domain).await? {
+            let topic = TopicName::parse(topic.as_str())?;
+            let partitions = admin.topics().partitioned_metadata(&topic).await?.partitions;
+            partitioned.push((topic, partitions));
+        }
+        let entries = collapse_partitions(&r, &partitioned)?;
+        pulsar_ctx.output().render(&entries)?;
         Ok(())
     }
 }
 
+#[derive(Serialize, Debug, PartialEq)]
+pub struct TopicListEntry {
+    pub name: String,
+
+    /// `None` for non-partitioned topics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<u32>,
+}
+
+/// Replaces the partitions of the partitioned topics by the topics themselves. Partitions whose
+/// partitioned topic is not known are listed as they are.
+fn collapse_partitions(topics: &[String], partitioned: &[(TopicName, u32)]) -> Result<Vec<TopicListEntry>, Error> {
+    let mut entries: BTreeMap<String, Option<u32>> = partitioned.iter()
+        .map(|(topic, partitions)| (topic.to_string(), Some(*partitions)))
+        .collect();
+    for topic in topics {
+        let topic = TopicName::parse(topic.as_str())?;
+        if topic.partition_index().is_none() || !entries.contains_key(&topic.partitioned_topic_name().to_string()) {
+            entries.insert(topic.to_string(), None);
+        }
+    }
+    Ok(entries.into_iter().map(|(name, partitions)| TopicListEntry { name, partitions }).collect())
+}
+
+impl Render for Vec<TopicListEntry> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["NAME", "PARTITIONS"]);
+        for entry in self {
+            table.add_row(vec![entry.name.clone(), entry.partitions.map(|p| p.to_string()).unwrap_or_default()]);
+        }
+        vec![table]
+    }
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct ListPartitionedTopicsOpts {
-    pub namespace: String,
+    pub namespace: Option<String>,
 
     #[arg(short = 'd', long, default_value = "Persistent")]
     pub domain: String,
@@ -81,11 +142,12 @@ pub struct ListPartitionedTopicsOpts {
 #[async_trait]
 impl AsyncCmd for ListPartitionedTopicsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let namespace = pulsar_ctx.get_config().resolve_namespace(self.namespace.as_deref())?;
         let r = pulsar_ctx.admin().await?
             .topics()
-            .list_partitioned(self.namespace.as_str(), TopicDomain::parse(self.domain.as_ref())?)
+            .list_partitioned(&namespace, TopicDomain::parse(self.domain.as_ref())?)
             .await?;
-        println!("{:?}", r);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -101,17 +163,20 @@ pub struct CreateTopicOpts {
 #[async_trait]
 impl AsyncCmd for CreateTopicOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         if self.partitions == 0 {
             pulsar_ctx.admin().await?
                 .topics()
-                .create_non_partitioned_topic(self.topic.as_str())
+                .create_non_partitioned_topic(&topic)
                 .await?;
+            pulsar_ctx.output().message(format!("Created topic {}", topic).as_str());
             Ok(())
         } else if self.partitions > 0 {
             pulsar_ctx.admin().await?
                 .topics()
-                .create_partitioned_topic(self.topic.as_str(), self.partitions)
+                .create_partitioned_topic(&topic, self.partitions)
                 .await?;
+            pulsar_ctx.output().message(format!("Created topic {} with {} partitions", topic, self.partitions).as_str());
             Ok(())
         } else {
             Err(Error::Custom(format!("invalid partitions [{}]", self.partitions)))
@@ -133,10 +198,12 @@ pub struct DeleteTopicOpts {
 #[async_trait]
 impl AsyncCmd for DeleteTopicOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         pulsar_ctx.admin().await?
             .topics()
-            .delete_topic(self.topic.as_str(), self.force, self.delete_schema)
+            .delete_topic(&topic, self.force, self.delete_schema)
             .await?;
+        pulsar_ctx.output().message(format!("Deleted topic {}", topic).as_str());
         Ok(())
     }
 }
@@ -155,10 +222,12 @@ pub struct DeletePartitionedTopicOpts {
 #[async_trait]
 impl AsyncCmd for DeletePartitionedTopicOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         pulsar_ctx.admin().await?
             .topics()
-            .delete_partitioned_topic(self.topic.as_str(), self.force, self.delete_schema)
+            .delete_partitioned_topic(&topic, self.force, self.delete_schema)
             .await?;
+        pulsar_ctx.output().message(format!("Deleted partitioned topic {}", topic).as_str());
         Ok(())
     }
 }
@@ -171,11 +240,12 @@ pub struct LookupOpts {
 #[async_trait]
 impl AsyncCmd for LookupOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         let r = pulsar_ctx.admin().await?
             .topics()
-            .lookup(self.topic.as_str())
+            .lookup(&topic)
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -194,11 +264,12 @@ pub struct StatsOpts {
 #[async_trait]
 impl AsyncCmd for StatsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         let r = pulsar_ctx.admin().await?
             .topics()
-            .stats(self.topic.as_str(), self.get_precise_backlog, self.subscription_backlog_size)
+            .stats(&topic, self.get_precise_backlog, self.subscription_backlog_size)
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&TopicStatsView(r))?;
         Ok(())
     }
 }
@@ -211,11 +282,12 @@ pub struct PermissionsOpts {
 #[async_trait]
 impl AsyncCmd for PermissionsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         let r = pulsar_ctx.admin().await?
             .topics()
-            .permissions(self.topic.as_str())
+            .permissions(&topic)
             .await?;
-        println!("{}", serde_json::to_string(&r)?);
+        pulsar_ctx.output().render(&r)?;
         Ok(())
     }
 }
@@ -235,10 +307,12 @@ pub struct GrantPermissionsOpts {
 #[async_trait]
 impl AsyncCmd for GrantPermissionsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         pulsar_ctx.admin().await?
             .topics()
-            .grant_permissions(self.topic.as_str(), self.role.as_str(), &self.actions)
+            .grant_permissions(&topic, self.role.as_str(), &self.actions)
             .await?;
+        pulsar_ctx.output().message(format!("Granted {:?} on {} to {}", self.actions, topic, self.role).as_str());
         Ok(())
     }
 }
@@ -254,10 +328,12 @@ pub struct RevokePermissionsOpts {
 #[async_trait]
 impl AsyncCmd for RevokePermissionsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         pulsar_ctx.admin().await?
             .topics()
-            .revoke_permissions(self.topic.as_str(), self.role.as_str())
+            .revoke_permissions(&topic, self.role.as_str())
             .await?;
+        pulsar_ctx.output().message(format!("Revoked permissions on {} from {}", topic, self.role).as_str());
         Ok(())
     }
 }
@@ -270,11 +346,12 @@ pub struct SubscriptionsOpts {
 #[async_trait]
 impl AsyncCmd for SubscriptionsOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         let subscriptions = pulsar_ctx.admin().await?
             .topics()
-            .subscriptions(self.topic.as_str())
+            .subscriptions(&topic)
             .await?;
-        println!("{}", serde_json::to_string(&subscriptions)?);
+        pulsar_ctx.output().render(&subscriptions)?;
         Ok(())
     }
 }
@@ -293,10 +370,169 @@ pub struct UnsubscribeOpts {
 #[async_trait]
 impl AsyncCmd for UnsubscribeOpts {
     async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
         pulsar_ctx.admin().await?
             .topics()
-            .unsubscribe(self.topic.as_str(), self.subscription.as_str(), self.force)
+            .unsubscribe(&topic, self.subscription.as_str(), self.force)
             .await?;
+        pulsar_ctx.output().message(format!("Unsubscribed {} from {}", self.subscription, topic).as_str());
         Ok(())
     }
 }
+
+/// Number of partitions of a topic, `0` if it is not partitioned
+#[derive(Parser, Debug, Clone)]
+pub struct GetPartitionedMetadataOpts {
+    pub topic: String,
+}
+
+#[async_trait]
+impl AsyncCmd for GetPartitionedMetadataOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
+        let r = pulsar_ctx.admin().await?
+            .topics()
+            .partitioned_metadata(&topic)
+            .await?;
+        pulsar_ctx.output().render(&r)?;
+        Ok(())
+    }
+}
+
+/// Increase the number of partitions of a partitioned topic
+#[derive(Parser, Debug, Clone)]
+pub struct UpdatePartitionsOpts {
+    pub topic: String,
+
+    /// The new number of partitions, more than the topic has
+    #[arg(short = 'p', long)]
+    pub partitions: u32,
+
+    /// Only update the topic in this cluster, not in the clusters it is replicated to
+    #[arg(long)]
+    pub update_local_topic_only: bool,
+
+    /// Create the new partitions even if some of them already exist
+    #[arg(short = 'f', long)]
+    pub force: bool,
+}
+
+#[async_trait]
+impl AsyncCmd for UpdatePartitionsOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
+        pulsar_ctx.admin().await?
+            .topics()
+            .update_partitions(&topic, self.partitions, self.update_local_topic_only, self.force)
+            .await?;
+        pulsar_ctx.output().message(format!("Updated topic {} to {} partitions", topic, self.partitions).as_str());
+        Ok(())
+    }
+}
+
+/// Create the partitions of a partitioned topic that do not exist
+#[derive(Parser, Debug, Clone)]
+pub struct CreateMissedPartitionsOpts {
+    pub topic: String,
+}
+
+#[async_trait]
+impl AsyncCmd for CreateMissedPartitionsOpts {
+    async fn run(&self, pulsar_ctx: &mut PulsarContext) -> Result<(), Error> {
+        let topic = pulsar_ctx.get_config().resolve_topic(&self.topic)?;
+        pulsar_ctx.admin().await?
+            .topics()
+            .create_missed_partitions(&topic)
+            .await?;
+        pulsar_ctx.output().message(format!("Created missed partitions of topic {}", topic).as_str());
+        Ok(())
+    }
+}
+
+impl Render for LookupResponse {}
+
+impl Render for PartitionedTopicMetadata {}
+
+/// Topic stats with a summary table of the topic, its subscriptions and, when wide, its publishers.
+#[derive(Serialize, Debug)]
+#[serde(transparent)]
+pub struct TopicStatsView(pub Value);
+
+const TOPIC_STATS_FIELDS: [&str; 9] = ["msgRateIn", "msgRateOut", "msgThroughputIn", "msgThroughputOut",
+    "averageMsgSize", "storageSize", "backlogSize", "msgInCounter", "msgOutCounter"];
+
+impl Render for TopicStatsView {
+    fn tables(&self, wide: bool) -> Vec<Table> {
+        let mut tables = Vec::new();
+
+        let mut topic = Table::new(&["FIELD", "VALUE"]);
+        if let Some(fields) = self.0.as_object() {
+            for (name, value) in fields {
+                if (wide && !value.is_object() && !value.is_array()) || TOPIC_STATS_FIELDS.contains(&name.as_str()) {
+                    topic.add_row(vec![name.clone(), cell(value)]);
+                }
+            }
+        }
+        tables.push(topic);
+
+        let mut subscriptions = if wide {
+            Table::new(&["SUBSCRIPTION", "TYPE", "BACKLOG", "MSG RATE OUT", "CONSUMERS", "UNACKED", "EXPIRED RATE"])
+        } else {
+            Table::new(&["SUBSCRIPTION", "TYPE", "BACKLOG", "MSG RATE OUT", "CONSUMERS"])
+        };
+        if let Some(subs) = self.0.get("subscriptions").and_then(|s| s.as_object()) {
+            for (name, sub) in subs {
+                let field = |f: &str| sub.get(f).map(cell).unwrap_or_default();
+                let consumers = sub.get("consumers").and_then(|c| c.as_array()).map(|c| c.len()).unwrap_or(0);
+                let mut row = vec![name.clone(), field("type"), field("msgBacklog"), field("msgRateOut"),
+                                   consumers.to_string()];
+                if wide {
+                    row.push(field("unackedMessages"));
+                    row.push(field("msgRateExpired"));
+                }
+                subscriptions.add_row(row);
+            }
+        }
+        tables.push(subscriptions);
+
+        if wide {
+            let mut publishers = Table::new(&["PRODUCER", "ADDRESS", "MSG RATE IN", "THROUGHPUT IN"]);
+            if let Some(pubs) = self.0.get("publishers").and_then(|p| p.as_array()) {
+                for p in pubs {
+                    let field = |f: &str| p.get(f).map(cell).unwrap_or_default();
+                    publishers.add_row(vec![field("producerName"), field("address"), field("msgRateIn"),
+                                            field("msgThroughputIn")]);
+                }
+            }
+            tables.push(publishers);
+        }
+        tables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::naming::TopicName;
+    use crate::cmd::topics::{collapse_partitions, TopicListEntry};
+
+    #[test]
+    fn collapse() {
+        let topics: Vec<String> = ["in-partition-0", "in-partition-1", "out", "orphan-partition-0"].iter()
+            .map(|name| format!("persistent://acme/orders/{}", name))
+            .collect();
+        let partitioned = vec![
+            (TopicName::parse("acme/orders/in").unwrap(), 2),
+            (TopicName::parse("acme/orders/empty").unwrap(), 4),
+        ];
+        let entry = |name: &str, partitions| TopicListEntry {
+            name: format!("persistent://acme/orders/{}", name),
+            partitions,
+        };
+        assert_eq!(collapse_partitions(&topics, &partitioned).unwrap(), vec![
+            entry("empty", Some(4)),
+            entry("in", Some(2)),
+            entry("orphan-partition-0", None),
+            entry("out", None),
+        ]);
+    }
+}
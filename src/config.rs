@@ -1,8 +1,29 @@
+use crate::admin::naming::{NamespaceName, TopicName};
 use crate::error::Error;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 
-const CONFIG_PATH: &str = ".pulsar-smith/config";
+const CONFIG_DIR: &str = ".pulsar-smith";
+const CONFIG_FILE: &str = "config";
+
+pub const ENV_CONFIG: &str = "PULSAR_SMITH_CONFIG";
+pub const ENV_CONTEXT: &str = "PULSAR_SMITH_CONTEXT";
+pub const ENV_URL: &str = "PULSAR_SMITH_URL";
+pub const ENV_ADMIN_URL: &str = "PULSAR_SMITH_ADMIN_URL";
+pub const ENV_AUTH_NAME: &str = "PULSAR_SMITH_AUTH_NAME";
+pub const ENV_AUTH_PARAMS: &str = "PULSAR_SMITH_AUTH_PARAMS";
+pub const ENV_ALLOW_INSECURE_CONNECTION: &str = "PULSAR_SMITH_ALLOW_INSECURE_CONNECTION";
+pub const ENV_TLS_HOSTNAME_VERIFICATION_ENABLED: &str = "PULSAR_SMITH_TLS_HOSTNAME_VERIFICATION_ENABLED";
+pub const ENV_NAMESPACE: &str = "PULSAR_SMITH_NAMESPACE";
+pub const ENV_PROXY_URL: &str = "PULSAR_SMITH_PROXY_URL";
+pub const ENV_TLS_TRUST_CERTS_FILE_PATH: &str = "PULSAR_SMITH_TLS_TRUST_CERTS_FILE_PATH";
+pub const ENV_TLS_CERT_FILE_PATH: &str = "PULSAR_SMITH_TLS_CERT_FILE_PATH";
+pub const ENV_TLS_KEY_FILE_PATH: &str = "PULSAR_SMITH_TLS_KEY_FILE_PATH";
+pub const ENV_REQUEST_TIMEOUT: &str = "PULSAR_SMITH_REQUEST_TIMEOUT";
+pub const ENV_CONNECT_TIMEOUT: &str = "PULSAR_SMITH_CONNECT_TIMEOUT";
+pub const ENV_MAX_RETRIES: &str = "PULSAR_SMITH_MAX_RETRIES";
 
 #[derive(Clone)]
 pub struct PulsarConfig {
@@ -17,38 +38,385 @@ pub struct PulsarConfig {
     pub allow_insecure_connection: bool,
 
     pub tls_hostname_verification_enabled: bool,
+
+    pub tls_trust_certs_file_path: Option<String>,
+
+    pub tls_cert_file_path: Option<String>,
+
+    pub tls_key_file_path: Option<String>,
+
+    pub proxy_url: Option<String>,
+
+    /// Timeout of admin requests, in seconds.
+    pub request_timeout: Option<u64>,
+
+    /// Timeout of establishing admin connections, in seconds.
+    pub connect_timeout: Option<u64>,
+
+    /// How many times a failed admin request is retried.
+    pub max_retries: Option<u32>,
+
+    pub tenant: Option<String>,
+
+    pub namespace: Option<String>,
+}
+
+impl Default for PulsarConfig {
+    fn default() -> Self {
+        PulsarConfig {
+            url: String::from("pulsar://localhost:6650"),
+            admin_url: String::from("http://localhost:8080"),
+            auth_name: None,
+            auth_params: None,
+            allow_insecure_connection: false,
+            tls_hostname_verification_enabled: true,
+            tls_trust_certs_file_path: None,
+            tls_cert_file_path: None,
+            tls_key_file_path: None,
+            proxy_url: None,
+            request_timeout: None,
+            connect_timeout: None,
+            max_retries: None,
+            tenant: None,
+            namespace: None,
+        }
+    }
 }
 
 impl PulsarConfig {
+    /// The defaults with `overrides` applied, for commands run without a context.
+    pub fn from_overrides(overrides: &ConfigOverrides) -> PulsarConfig {
+        let mut cfg = PulsarConfig::default();
+        cfg.apply(overrides);
+        cfg
+    }
+
+    pub fn apply(&mut self, overrides: &ConfigOverrides) {
+        if let Some(url) = &overrides.url {
+            self.url = url.clone();
+        }
+        if let Some(admin_url) = &overrides.admin_url {
+            self.admin_url = admin_url.clone();
+        }
+        if let Some(auth_name) = &overrides.auth_name {
+            self.auth_name = Some(auth_name.clone());
+        }
+        if let Some(auth_params) = &overrides.auth_params {
+            self.auth_params = Some(auth_params.clone());
+        }
+        if let Some(allow) = overrides.allow_insecure_connection {
+            self.allow_insecure_connection = allow;
+        }
+        if let Some(enabled) = overrides.tls_hostname_verification_enabled {
+            self.tls_hostname_verification_enabled = enabled;
+        }
+        if let Some(path) = &overrides.tls_trust_certs_file_path {
+            self.tls_trust_certs_file_path = Some(path.clone());
+        }
+        if let Some(path) = &overrides.tls_cert_file_path {
+            self.tls_cert_file_path = Some(path.clone());
+        }
+        if let Some(path) = &overrides.tls_key_file_path {
+            self.tls_key_file_path = Some(path.clone());
+        }
+        if let Some(proxy_url) = &overrides.proxy_url {
+            self.proxy_url = Some(proxy_url.clone());
+        }
+        if let Some(timeout) = overrides.request_timeout {
+            self.request_timeout = Some(timeout);
+        }
+        if let Some(timeout) = overrides.connect_timeout {
+            self.connect_timeout = Some(timeout);
+        }
+        if let Some(max_retries) = overrides.max_retries {
+            self.max_retries = Some(max_retries);
+        }
+        if let Some(namespace) = &overrides.namespace {
+            let namespace = qualify_namespace(namespace, self.tenant.as_deref());
+            self.tenant = namespace.split('/').next().map(|t| t.to_string());
+            self.namespace = Some(namespace);
+        }
+    }
+
+    /// The namespace short names are resolved against: `--namespace`, then the context's
+    /// namespace, then `default` under the context's tenant, then `public/default`.
+    pub fn default_namespace(&self) -> String {
+        match (&self.namespace, &self.tenant) {
+            (Some(namespace), _) => namespace.clone(),
+            (None, Some(tenant)) => format!("{}/default", tenant),
+            (None, None) => String::from("public/default"),
+        }
+    }
+
+    pub fn default_tenant(&self) -> String {
+        match &self.tenant {
+            Some(tenant) => tenant.clone(),
+            None => self.default_namespace().split('/').next().unwrap().to_string(),
+        }
+    }
+
+    /// Resolves an optional `tenant/namespace` or bare `namespace` against the defaults.
+    pub fn resolve_namespace(&self, namespace: Option<&str>) -> Result<NamespaceName, Error> {
+        let namespace = match namespace {
+            Some(namespace) => qualify_namespace(namespace, Some(self.default_tenant().as_str())),
+            None => self.default_namespace(),
+        };
+        Ok(NamespaceName::parse(namespace.as_str())?)
+    }
+
+    /// Resolves a topic given as `domain://tenant/namespace/topic`, `tenant/namespace/topic`
+    /// or a bare `topic` in the default namespace.
+    pub fn resolve_topic(&self, topic: &str) -> Result<TopicName, Error> {
+        let default_namespace = NamespaceName::parse(self.default_namespace().as_str())?;
+        Ok(TopicName::parse_in(topic, &default_namespace)?)
+    }
+}
+
+/// Prefixes a bare namespace with the tenant, `public` if none is known.
+fn qualify_namespace(namespace: &str, tenant: Option<&str>) -> String {
+    if namespace.contains('/') {
+        namespace.to_string()
+    } else {
+        format!("{}/{}", tenant.unwrap_or("public"), namespace)
+    }
+}
+
+/// Settings layered on top of the selected context, coming from command line flags or
+/// `PULSAR_SMITH_*` environment variables.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigOverrides {
+    pub url: Option<String>,
+
+    pub admin_url: Option<String>,
+
+    pub auth_name: Option<String>,
+
+    pub auth_params: Option<String>,
+
+    pub allow_insecure_connection: Option<bool>,
+
+    pub tls_hostname_verification_enabled: Option<bool>,
+
+    pub tls_trust_certs_file_path: Option<String>,
+
+    pub tls_cert_file_path: Option<String>,
+
+    pub tls_key_file_path: Option<String>,
+
+    pub proxy_url: Option<String>,
+
+    pub request_timeout: Option<u64>,
+
+    pub connect_timeout: Option<u64>,
+
+    pub max_retries: Option<u32>,
+
+    pub namespace: Option<String>,
+}
+
+impl ConfigOverrides {
+    pub fn from_env() -> Result<ConfigOverrides, Error> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Like `from_env`, reading the variables through `lookup`.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<ConfigOverrides, Error> {
+        let var = |name: &str| lookup(name).filter(|v| !v.is_empty());
+        Ok(ConfigOverrides {
+            url: var(ENV_URL),
+            admin_url: var(ENV_ADMIN_URL),
+            auth_name: var(ENV_AUTH_NAME),
+            auth_params: var(ENV_AUTH_PARAMS),
+            allow_insecure_connection: parse_bool(ENV_ALLOW_INSECURE_CONNECTION, var(ENV_ALLOW_INSECURE_CONNECTION))?,
+            tls_hostname_verification_enabled: parse_bool(ENV_TLS_HOSTNAME_VERIFICATION_ENABLED,
+                                                          var(ENV_TLS_HOSTNAME_VERIFICATION_ENABLED))?,
+            tls_trust_certs_file_path: var(ENV_TLS_TRUST_CERTS_FILE_PATH),
+            tls_cert_file_path: var(ENV_TLS_CERT_FILE_PATH),
+            tls_key_file_path: var(ENV_TLS_KEY_FILE_PATH),
+            proxy_url: var(ENV_PROXY_URL),
+            request_timeout: parse_number(ENV_REQUEST_TIMEOUT, var(ENV_REQUEST_TIMEOUT))?,
+            connect_timeout: parse_number(ENV_CONNECT_TIMEOUT, var(ENV_CONNECT_TIMEOUT))?,
+            max_retries: parse_number(ENV_MAX_RETRIES, var(ENV_MAX_RETRIES))?,
+            namespace: var(ENV_NAMESPACE),
+        })
+    }
+
+    /// Fills the unset fields of `self` from `other`, so `self` takes precedence.
+    pub fn or(self, other: ConfigOverrides) -> ConfigOverrides {
+        ConfigOverrides {
+            url: self.url.or(other.url),
+            admin_url: self.admin_url.or(other.admin_url),
+            auth_name: self.auth_name.or(other.auth_name),
+            auth_params: self.auth_params.or(other.auth_params),
+            allow_insecure_connection: self.allow_insecure_connection.or(other.allow_insecure_connection),
+            tls_hostname_verification_enabled: self.tls_hostname_verification_enabled
+                .or(other.tls_hostname_verification_enabled),
+            tls_trust_certs_file_path: self.tls_trust_certs_file_path.or(other.tls_trust_certs_file_path),
+            tls_cert_file_path: self.tls_cert_file_path.or(other.tls_cert_file_path),
+            tls_key_file_path: self.tls_key_file_path.or(other.tls_key_file_path),
+            proxy_url: self.proxy_url.or(other.proxy_url),
+            request_timeout: self.request_timeout.or(other.request_timeout),
+            connect_timeout: self.connect_timeout.or(other.connect_timeout),
+            max_retries: self.max_retries.or(other.max_retries),
+            namespace: self.namespace.or(other.namespace),
+        }
+    }
+}
+
+/// The `.pulsar-smith` directory under the home directory, also holding caches.
+pub fn config_dir() -> Option<PathBuf> {
+    #![allow(deprecated)]
+    std::env::home_dir().map(|home_dir| home_dir.join(CONFIG_DIR))
+}
+
+pub(crate) fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_bool(name: &str, value: Option<String>) -> Result<Option<bool>, Error> {
+    match value {
+        Some(v) => match v.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(Some(true)),
+            "false" | "0" | "no" => Ok(Some(false)),
+            _ => Err(Error::Custom(format!("invalid boolean [{}] for {}", v, name))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<Option<T>, Error> {
+    match value {
+        Some(v) => v.parse::<T>()
+            .map(Some)
+            .map_err(|_| Error::Custom(format!("invalid number [{}] for {}", v, name))),
+        None => Ok(None),
+    }
 }
 
 impl Configs {
-    pub fn load()-> Result<Configs, Error> {
-        #![allow(deprecated)]
-        if let Some(home_dir) = std::env::home_dir() {
-            let config_path = home_dir.join(CONFIG_PATH);
-            let str = fs::read_to_string(config_path)?;
-            let cfg: Configs = serde_yaml::from_str(str.as_str())?;
-            Ok(cfg)
+    pub fn default_path() -> Result<PathBuf, Error> {
+        if let Some(config_dir) = config_dir() {
+            Ok(config_dir.join(CONFIG_FILE))
+        } else {
+            Err(Error::Custom("cannot determine home dir".to_string()))
+        }
+    }
+
+    /// Resolves the config files to read: the `--config` value if given, otherwise the
+    /// `PULSAR_SMITH_CONFIG` list (separated like `PATH`), otherwise the default location.
+    pub fn resolve_paths(config: Option<&str>) -> Result<Vec<PathBuf>, Error> {
+        let value = match config {
+            Some(config) => Some(config.to_string()),
+            None => env_var(ENV_CONFIG),
+        };
+        let paths: Vec<PathBuf> = match value {
+            Some(value) => std::env::split_paths(value.as_str())
+                .filter(|p| !p.as_os_str().is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+        if paths.is_empty() {
+            Ok(vec![Self::default_path()?])
+        } else {
+            Ok(paths)
+        }
+    }
+
+    /// Loads and merges the given config files like `KUBECONFIG`: missing files are skipped and
+    /// the first file to define a cluster, user, context or the current context wins.
+    pub fn load_merged(paths: &[PathBuf]) -> Result<Configs, Error> {
+        let mut merged = Configs::default();
+        for path in paths {
+            if path.exists() {
+                merged.merge(Self::load_from(path)?);
+            } else {
+                debug!("config file {} does not exist, skipped", path.display());
+            }
+        }
+        Ok(merged)
+    }
+
+    fn merge(&mut self, other: Configs) {
+        for item in other.clusters {
+            if !self.clusters.iter().any(|p| p.name == item.name) {
+                self.clusters.push(item);
+            }
+        }
+        for item in other.users {
+            if !self.users.iter().any(|p| p.name == item.name) {
+                self.users.push(item);
+            }
+        }
+        for item in other.contexts {
+            if !self.contexts.iter().any(|p| p.name == item.name) {
+                self.contexts.push(item);
+            }
+        }
+        if self.current_context.is_none() {
+            self.current_context = other.current_context;
+        }
+        for (key, value) in other.extra {
+            self.extra.entry(key).or_insert(value);
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Configs, Error> {
+        let str = fs::read_to_string(path)?;
+        let cfg: Configs = serde_yaml::from_str(str.as_str())?;
+        Ok(cfg)
+    }
+
+    /// Loads the configs at `path`, or starts from an empty config if the file does not exist yet.
+    pub fn load_or_default(path: &Path) -> Result<Configs, Error> {
+        if path.exists() {
+            Self::load_from(path)
         } else {
-            Err(Error::Custom(format!("cannot determine home dir")))
+            Ok(Configs::default())
         }
     }
 
-    pub fn get_pulsar_config(&self, context_name: &str) -> Result<PulsarConfig, Error> {
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// The config of a context with `overrides` applied, which may supply settings the context
+    /// lacks.
+    pub fn get_pulsar_config(&self, context_name: &str, overrides: &ConfigOverrides) -> Result<PulsarConfig, Error> {
         if let Some(context_item) = self.contexts.iter().find(|p| {p.name == context_name}) {
             if let Some(cluster_item) = self.clusters.iter().find(|p| {p.name == context_item.context.cluster}) {
                 if let Some(user_item) = self.users.iter().find(|p| p.name == context_item.context.user) {
                     let user = &user_item.user;
-                    Ok(PulsarConfig {
+                    let context = &context_item.context;
+                    let namespace = context.namespace.as_ref()
+                        .map(|ns| qualify_namespace(ns, context.tenant.as_deref()));
+                    let tenant = context.tenant.clone()
+                        .or_else(|| namespace.as_ref().map(|ns| ns.split('/').next().unwrap().to_string()));
+                    let admin_url = overrides.admin_url.clone()
+                        .or_else(|| cluster_item.cluster.admin_service_url.clone())
+                        .ok_or_else(|| Error::Custom(format!("admin-url not defined for cluster [{}]", cluster_item.name)))?;
+                    let mut cfg = PulsarConfig {
                         url: cluster_item.cluster.url.clone(),
-                        admin_url: cluster_item.cluster.admin_service_url.clone()
-                            .expect(format!("admin-url not defined for cluster [{}]", cluster_item.name.as_str()).as_str()),
+                        admin_url,
                         allow_insecure_connection: cluster_item.cluster.allow_insecure_connection,
                         tls_hostname_verification_enabled: cluster_item.cluster.tls_hostname_verification_enabled,
+                        tls_trust_certs_file_path: cluster_item.cluster.tls_trust_certs_file_path.clone(),
+                        tls_cert_file_path: cluster_item.cluster.tls_cert_file_path.clone(),
+                        tls_key_file_path: cluster_item.cluster.tls_key_file_path.clone(),
+                        proxy_url: cluster_item.cluster.proxy_url.clone(),
+                        request_timeout: cluster_item.cluster.request_timeout,
+                        connect_timeout: cluster_item.cluster.connect_timeout,
+                        max_retries: cluster_item.cluster.max_retries,
                         auth_name: user.auth_name.clone(),
                         auth_params: user.auth_params.clone(),
-                    })
+                        tenant,
+                        namespace,
+                    };
+                    cfg.apply(overrides);
+                    Ok(cfg)
                 } else {
                     Err(Error::Custom(format!("context [{}] not exist", context_name)))
                 }
@@ -64,69 +432,203 @@ impl Configs {
         return self.current_context.is_some()
     }
 
-    pub fn get_current_pulsar_config(&self) -> Result<PulsarConfig, Error> {
+    pub fn get_current_pulsar_config(&self, overrides: &ConfigOverrides) -> Result<PulsarConfig, Error> {
         match &self.current_context {
-            Some(current) => self.get_pulsar_config(current.as_str()),
+            Some(current) => self.get_pulsar_config(current.as_str(), overrides),
             None => Err(Error::Custom("current context not set".to_string())),
         }
     }
+
+    pub fn current_context(&self) -> Option<&str> {
+        self.current_context.as_deref()
+    }
+
+    pub fn contexts(&self) -> &[ContextItem] {
+        &self.contexts
+    }
+
+    pub fn has_context(&self, name: &str) -> bool {
+        self.contexts.iter().any(|p| p.name == name)
+    }
+
+    pub fn has_cluster(&self, name: &str) -> bool {
+        self.clusters.iter().any(|p| p.name == name)
+    }
+
+    pub fn has_user(&self, name: &str) -> bool {
+        self.users.iter().any(|p| p.name == name)
+    }
+
+    pub fn set_current_context(&mut self, name: &str) {
+        self.current_context = Some(name.to_string());
+    }
+
+    pub fn unset_current_context(&mut self) {
+        self.current_context = None;
+    }
+
+    /// Returns the cluster with the given name, creating an empty one if it does not exist.
+    pub fn cluster_mut(&mut self, name: &str) -> &mut Cluster {
+        if let Some(idx) = self.clusters.iter().position(|p| p.name == name) {
+            &mut self.clusters[idx].cluster
+        } else {
+            self.clusters.push(ClusterItem {
+                name: name.to_string(),
+                cluster: Cluster::default(),
+                extra: BTreeMap::new(),
+            });
+            &mut self.clusters.last_mut().unwrap().cluster
+        }
+    }
+
+    /// Returns the user with the given name, creating an empty one if it does not exist.
+    pub fn user_mut(&mut self, name: &str) -> &mut User {
+        if let Some(idx) = self.users.iter().position(|p| p.name == name) {
+            &mut self.users[idx].user
+        } else {
+            self.users.push(UserItem {
+                name: name.to_string(),
+                user: User::default(),
+                extra: BTreeMap::new(),
+            });
+            &mut self.users.last_mut().unwrap().user
+        }
+    }
+
+    /// Returns the context with the given name, creating an empty one if it does not exist.
+    pub fn context_mut(&mut self, name: &str) -> &mut Context {
+        if let Some(idx) = self.contexts.iter().position(|p| p.name == name) {
+            &mut self.contexts[idx].context
+        } else {
+            self.contexts.push(ContextItem {
+                name: name.to_string(),
+                context: Context::default(),
+                extra: BTreeMap::new(),
+            });
+            &mut self.contexts.last_mut().unwrap().context
+        }
+    }
+
+    pub fn delete_context(&mut self, name: &str) -> Result<(), Error> {
+        let len = self.contexts.len();
+        self.contexts.retain(|p| p.name != name);
+        if self.contexts.len() == len {
+            return Err(Error::Custom(format!("context [{}] not exist", name)));
+        }
+        if self.current_context.as_deref() == Some(name) {
+            self.current_context = None;
+        }
+        Ok(())
+    }
+
+    pub fn rename_context(&mut self, name: &str, new_name: &str) -> Result<(), Error> {
+        if self.contexts.iter().any(|p| p.name == new_name) {
+            return Err(Error::Custom(format!("context [{}] already exists", new_name)));
+        }
+        match self.contexts.iter_mut().find(|p| p.name == name) {
+            Some(item) => item.name = new_name.to_string(),
+            None => return Err(Error::Custom(format!("context [{}] not exist", name))),
+        }
+        if self.current_context.as_deref() == Some(name) {
+            self.current_context = Some(new_name.to_string());
+        }
+        Ok(())
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Configs {
+    #[serde(default)]
     clusters: Vec<ClusterItem>,
+    #[serde(default)]
     users: Vec<UserItem>,
+    #[serde(default)]
     contexts: Vec<ContextItem>,
-    #[serde(rename = "current-context")]
+    #[serde(rename = "current-context", default, skip_serializing_if = "Option::is_none")]
     current_context: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ClusterItem {
     name: String,
     cluster: Cluster,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Cluster {
-    url: String,
-    #[serde(rename = "admin-url", default)]
-    admin_service_url: Option<String>,
+    pub url: String,
+    #[serde(rename = "admin-url", default, skip_serializing_if = "Option::is_none")]
+    pub admin_service_url: Option<String>,
     #[serde(rename = "allow-insecure-connection", default)]
-    allow_insecure_connection: bool,
+    pub allow_insecure_connection: bool,
     #[serde(rename = "tls-hostname-verification-enabled", default)]
-    tls_hostname_verification_enabled: bool,
+    pub tls_hostname_verification_enabled: bool,
+    #[serde(rename = "tls-trust-certs-file-path", default, skip_serializing_if = "Option::is_none")]
+    pub tls_trust_certs_file_path: Option<String>,
+    #[serde(rename = "tls-cert-file-path", default, skip_serializing_if = "Option::is_none")]
+    pub tls_cert_file_path: Option<String>,
+    #[serde(rename = "tls-key-file-path", default, skip_serializing_if = "Option::is_none")]
+    pub tls_key_file_path: Option<String>,
+    #[serde(rename = "proxy-url", default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(rename = "request-timeout", default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout: Option<u64>,
+    #[serde(rename = "connect-timeout", default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+    #[serde(rename = "max-retries", default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct UserItem {
     name: String,
     user: User,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct User {
-    #[serde(rename = "auth-name")]
-    auth_name: Option<String>,
-    #[serde(rename = "auth-params")]
-    auth_params: Option<String>,
+    #[serde(rename = "auth-name", default, skip_serializing_if = "Option::is_none")]
+    pub auth_name: Option<String>,
+    #[serde(rename = "auth-params", default, skip_serializing_if = "Option::is_none")]
+    pub auth_params: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ContextItem {
-    name: String,
-    context: Context,
+    pub name: String,
+    pub context: Context,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Context {
-    cluster: String,
-    user: String,
+    pub cluster: String,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::config::{Context, User};
+    use std::collections::HashMap;
+
+    use crate::config::{ConfigOverrides, Configs, Context, PulsarConfig, User, ENV_ALLOW_INSECURE_CONNECTION,
+                        ENV_MAX_RETRIES, ENV_NAMESPACE, ENV_URL};
     use serde::{Serialize, Deserialize};
 
     #[derive(Serialize, Deserialize)]
@@ -140,7 +642,160 @@ mod tests {
         let str = serde_json::to_string(&Test::User(User{
             auth_name: Some("test".to_string()),
             auth_params: Some("params".to_string()),
+            ..Default::default()
         })).unwrap();
         println!("user [{}]", str);
     }
+
+    #[test]
+    fn round_trip_keeps_unknown_fields() {
+        let yaml = r#"
+clusters:
+- name: local
+  cluster:
+    url: pulsar://localhost:6650
+    admin-url: http://localhost:8080
+    tls-trust-certs-file-path: /tmp/ca.pem
+users:
+- name: admin
+  user:
+    auth-name: token
+    auth-params: abc
+contexts:
+- name: local
+  context:
+    cluster: local
+    user: admin
+  description: the local standalone
+current-context: local
+preferences:
+  color: true
+"#;
+        let mut configs: Configs = serde_yaml::from_str(yaml).unwrap();
+        configs.context_mut("other").cluster = "local".to_string();
+        let out = serde_yaml::to_string(&configs).unwrap();
+        let reloaded: serde_yaml::Value = serde_yaml::from_str(out.as_str()).unwrap();
+        assert_eq!(reloaded["clusters"][0]["cluster"]["tls-trust-certs-file-path"], "/tmp/ca.pem");
+        assert_eq!(reloaded["contexts"][0]["description"], "the local standalone");
+        assert_eq!(reloaded["contexts"][1]["name"], "other");
+        assert_eq!(reloaded["preferences"]["color"], true);
+        assert_eq!(reloaded["current-context"], "local");
+    }
+
+    #[test]
+    fn resolve_short_names() {
+        let mut cfg = PulsarConfig::default();
+        assert_eq!(cfg.resolve_topic("my-topic").unwrap().to_string(), "persistent://public/default/my-topic");
+        assert_eq!(cfg.resolve_namespace(None).unwrap().to_string(), "public/default");
+
+        cfg.tenant = Some("acme".to_string());
+        assert_eq!(cfg.resolve_topic("my-topic").unwrap().to_string(), "persistent://acme/default/my-topic");
+        assert_eq!(cfg.resolve_namespace(Some("orders")).unwrap().to_string(), "acme/orders");
+
+        cfg.apply(&ConfigOverrides {
+            namespace: Some("billing".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(cfg.resolve_topic("my-topic").unwrap().to_string(), "persistent://acme/billing/my-topic");
+        assert_eq!(cfg.resolve_topic("t/n/x").unwrap().to_string(), "persistent://t/n/x");
+        assert_eq!(cfg.resolve_topic("non-persistent://t/n/x").unwrap().to_string(), "non-persistent://t/n/x");
+        assert_eq!(cfg.resolve_namespace(Some("other/ns")).unwrap().to_string(), "other/ns");
+        assert!(cfg.resolve_topic("n/x").is_err());
+        assert!(cfg.resolve_namespace(Some("t/")).is_err());
+    }
+
+    #[test]
+    fn merge_first_file_wins() {
+        let dir = std::env::temp_dir().join(format!("pulsar-smith-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("first");
+        let second = dir.join("second");
+        std::fs::write(&first, r#"
+clusters:
+- name: shared
+  cluster:
+    url: pulsar://first:6650
+    admin-url: http://first:8080
+users: []
+contexts: []
+"#).unwrap();
+        std::fs::write(&second, r#"
+clusters:
+- name: shared
+  cluster:
+    url: pulsar://second:6650
+users:
+- name: ci
+  user:
+    auth-name: token
+    auth-params: abc
+contexts:
+- name: ci
+  context:
+    cluster: shared
+    user: ci
+current-context: ci
+"#).unwrap();
+        let paths = vec![first, dir.join("missing"), second];
+        let configs = Configs::load_merged(&paths).unwrap();
+        let cfg = configs.get_current_pulsar_config(&ConfigOverrides::default()).unwrap();
+        assert_eq!(cfg.url, "pulsar://first:6650");
+        assert_eq!(cfg.auth_params.as_deref(), Some("abc"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overrides_from_env_below_flags() {
+        let env: HashMap<&str, &str> = [
+            (ENV_URL, "pulsar://env:6650"),
+            (ENV_MAX_RETRIES, "7"),
+            (ENV_ALLOW_INSECURE_CONNECTION, "yes"),
+            (ENV_NAMESPACE, ""),
+        ].iter().cloned().collect();
+        let lookup = |name: &str| env.get(name).map(|v| v.to_string());
+        let flags = ConfigOverrides {
+            url: Some("pulsar://flag:6650".to_string()),
+            namespace: Some("orders".to_string()),
+            ..Default::default()
+        };
+        let overrides = flags.or(ConfigOverrides::from_lookup(lookup).unwrap());
+
+        let mut cfg = PulsarConfig {
+            tenant: Some("acme".to_string()),
+            ..Default::default()
+        };
+        cfg.apply(&overrides);
+        assert_eq!(cfg.url, "pulsar://flag:6650");
+        assert_eq!(cfg.max_retries, Some(7));
+        assert!(cfg.allow_insecure_connection);
+        assert_eq!(cfg.namespace.as_deref(), Some("acme/orders"));
+        assert_eq!(cfg.admin_url, PulsarConfig::default().admin_url);
+
+        assert!(ConfigOverrides::from_lookup(|name| (name == ENV_MAX_RETRIES).then(|| "many".to_string())).is_err());
+    }
+
+    #[test]
+    fn admin_url_from_overrides() {
+        let configs: Configs = serde_yaml::from_str(r#"
+clusters:
+- name: local
+  cluster:
+    url: pulsar://localhost:6650
+users:
+- name: admin
+  user: {}
+contexts:
+- name: local
+  context:
+    cluster: local
+    user: admin
+"#).unwrap();
+        let err = configs.get_pulsar_config("local", &ConfigOverrides::default()).err().unwrap();
+        assert_eq!(err.to_string(), "admin-url not defined for cluster [local]");
+        let overrides = ConfigOverrides {
+            admin_url: Some("http://localhost:8080".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(configs.get_pulsar_config("local", &overrides).unwrap().admin_url, "http://localhost:8080");
+    }
 }
@@ -1,10 +1,17 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use futures::lock::Mutex;
-use pulsar::{Authentication, Pulsar, TokioExecutor};
+use pulsar::{Pulsar, PulsarBuilder, TokioExecutor};
 
-use crate::admin::admin::PulsarAdmin;
-use crate::auth::auth::Authn;
+use crate::admin::admin::{PulsarAdmin, PulsarAdminBuilder};
+use crate::admin::naming::NamespaceName;
+use crate::admin::retry::RetryPolicy;
+use crate::auth::auth::{Authn, PulsarAuthentication};
 use crate::config::PulsarConfig;
 use crate::error::Error;
+use crate::output::Output;
 
 pub struct PulsarContext {
     mutex: Mutex<()>,
@@ -14,6 +21,10 @@ pub struct PulsarContext {
     client: Option<Box<Pulsar<TokioExecutor>>>,
 
     admin: Option<PulsarAdmin>,
+
+    authn: std::sync::Mutex<Option<Arc<dyn Authn>>>,
+
+    output: Output,
 }
 
 impl From<PulsarConfig> for PulsarContext {
@@ -23,6 +34,8 @@ impl From<PulsarConfig> for PulsarContext {
             config: cfg,
             client: None,
             admin: None,
+            authn: std::sync::Mutex::new(None),
+            output: Output::default(),
         }
     }
 }
@@ -32,62 +45,142 @@ impl PulsarContext {
         &self.config
     }
 
+    /// Changes the default namespace, and its tenant, for short names.
+    pub fn set_namespace(&mut self, namespace: &NamespaceName) {
+        self.config.tenant = Some(namespace.tenant().to_string());
+        self.config.namespace = Some(namespace.to_string());
+    }
+
+    pub fn output(&self) -> Output {
+        self.output
+    }
+
+    pub fn set_output(&mut self, output: Output) {
+        self.output = output;
+    }
+
     pub async fn client(&mut self) -> Result<&Pulsar<TokioExecutor>, Error> {
         let _guard = self.mutex.lock();
         if self.client.is_none() {
-            let mut builder = Pulsar::builder(self.config.url.clone(), TokioExecutor);
-            if let Some(auth_data) = self.config.auth_params.as_ref() {
-                let auth_name = "token";
-                let auth = crate::auth::auth::create(self.config.auth_name.as_ref().unwrap().clone(), auth_data.clone())?;
-                let auth_data = auth.get_token().await?;
-                builder = builder.with_auth(Authentication {
-                    name: auth_name.to_string(),
-                    data: auth_data.into_bytes(),
-                });
-            }
-            builder = builder.with_allow_insecure_connection(self.config.allow_insecure_connection);
-            builder = builder.with_tls_hostname_verification_enabled(self.config.tls_hostname_verification_enabled);
-            self.client = Some(Box::new(builder.build().await?));
+            self.client = Some(Box::new(self.client_builder()?.build().await?));
             info!("created a new pulsar client");
         }
         Ok(self.client.as_ref().unwrap())
     }
 
     pub async fn new_client(&self) -> Result<Pulsar<TokioExecutor>, Box<dyn std::error::Error>> {
+        Ok(self.client_builder()?.build().await?)
+    }
+
+    fn client_builder(&self) -> Result<PulsarBuilder<TokioExecutor>, Error> {
+        // pulsar-rs has no way to present a client certificate on the binary protocol
+        if self.config.tls_cert_file_path.is_some() || self.config.tls_key_file_path.is_some() {
+            return Err(Error::Custom(
+                "tls client certificates are only supported by admin commands, the pulsar client cannot present them".to_string()));
+        }
         let mut builder = Pulsar::builder(self.config.url.clone(), TokioExecutor);
-        if let Some(auth_data) = self.config.auth_params.as_ref() {
-            let auth_name = "token";
-            let auth = crate::auth::auth::create(self.config.auth_name.as_ref().unwrap().clone(), auth_data.clone())?;
-            let auth_data = auth.get_token().await?;
-            builder = builder.with_auth(Authentication {
-                name: auth_name.to_string(),
-                data: auth_data.into_bytes(),
-            });
+        if let Some(authn) = self.auth_provider()? {
+            if authn.tls_identity().is_some() {
+                return Err(Error::Custom(
+                    "tls auth is only supported by admin commands, the pulsar client cannot present client certificates".to_string()));
+            }
+            builder = builder.with_auth_provider(Box::new(PulsarAuthentication::new(authn)));
         }
         builder = builder.with_allow_insecure_connection(self.config.allow_insecure_connection);
         builder = builder.with_tls_hostname_verification_enabled(self.config.tls_hostname_verification_enabled);
-        Ok(builder.build().await?)
+        if let Some(path) = &self.config.tls_trust_certs_file_path {
+            builder = builder.with_certificate_chain_file(path)?;
+        }
+        Ok(builder)
     }
 
     pub async fn admin(&mut self) -> Result<&PulsarAdmin, Error> {
         let _guard = self.mutex.lock();
         if self.admin.is_none() {
-            let (auth_name, auth_params) = if self.config.auth_name.is_none() {
-                (None, None)
-            } else {
-                let auth = crate::auth::auth::create(self.config.auth_name.as_ref().unwrap().clone(),
-                                                     self.config.auth_params.as_ref().unwrap().clone())?;
-                (Some("token".to_string()), Some(auth.get_token().await?))
+            let mut builder = self.admin_builder();
+            let mut identity = match (&self.config.tls_cert_file_path, &self.config.tls_key_file_path) {
+                (Some(cert), Some(key)) => Some((PathBuf::from(cert), PathBuf::from(key))),
+                (None, None) => None,
+                _ => return Err(Error::Custom("tls cert and key file paths must be set together".to_string())),
             };
-            self.admin = Some(PulsarAdmin::new(self.config.admin_url.clone(),
-                                               auth_name,
-                                               auth_params));
+            if let Some(authn) = self.auth_provider()? {
+                identity = authn.tls_identity().or(identity);
+                builder = builder.with_auth_provider(authn);
+            }
+            if let Some((cert, key)) = identity {
+                builder = builder.with_client_certificate(cert, key);
+            }
+            self.admin = Some(builder.build()?);
         }
         Ok(self.admin.as_ref().unwrap())
     }
 
-    pub fn authn(&self) -> Result<Box<dyn Authn>, Error> {
-        Ok(crate::auth::auth::create(self.config.auth_name.clone().expect("auth name is not provided"),
-                                     self.config.auth_params.clone().expect("auth params is not provided"))?)
+    /// The admin's connection settings, without authentication.
+    fn admin_builder(&self) -> PulsarAdminBuilder {
+        let mut builder = PulsarAdmin::builder(self.config.admin_url.clone())
+            .with_allow_insecure_connection(self.config.allow_insecure_connection)
+            .with_tls_hostname_verification_enabled(self.config.tls_hostname_verification_enabled);
+        if let Some(path) = &self.config.tls_trust_certs_file_path {
+            builder = builder.with_trust_certs_file(path);
+        }
+        if let Some(proxy_url) = &self.config.proxy_url {
+            builder = builder.with_proxy(proxy_url.clone());
+        }
+        if let Some(timeout) = self.config.request_timeout {
+            builder = builder.with_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(timeout) = self.config.connect_timeout {
+            builder = builder.with_connect_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(max_retries) = self.config.max_retries {
+            builder = builder.with_retry_policy(RetryPolicy {
+                max_attempts: max_retries + 1,
+                ..RetryPolicy::default()
+            });
+        }
+        builder
+    }
+
+    pub fn authn(&self) -> Result<Arc<dyn Authn>, Error> {
+        self.auth_provider()?.ok_or_else(|| Error::Custom("auth name is not provided".to_string()))
+    }
+
+    /// The configured authentication, shared by the pulsar client and the admin so they use
+    /// the same cached tokens. It reaches e.g. OAuth2 issuers with the admin's TLS and proxy settings.
+    fn auth_provider(&self) -> Result<Option<Arc<dyn Authn>>, Error> {
+        let mut authn = self.authn.lock().unwrap();
+        if authn.is_none() {
+            if let Some(auth_name) = &self.config.auth_name {
+                let auth_params = self.config.auth_params.clone()
+                    .ok_or_else(|| Error::Custom("auth params is not provided".to_string()))?;
+                let http_client = self.admin_builder().http_client()?;
+                *authn = Some(Arc::from(crate::auth::auth::create(auth_name.clone(), auth_params, http_client)?));
+            }
+        }
+        Ok(authn.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PulsarConfig;
+    use crate::context::PulsarContext;
+    use crate::error::Error;
+
+    #[test]
+    fn client_rejects_client_certificates() {
+        let ctx: PulsarContext = PulsarConfig {
+            tls_cert_file_path: Some("/tmp/client.pem".to_string()),
+            tls_key_file_path: Some("/tmp/client.key".to_string()),
+            ..Default::default()
+        }.into();
+        assert!(matches!(ctx.client_builder(), Err(Error::Custom(e)) if e.contains("admin commands")));
+
+        let ctx: PulsarContext = PulsarConfig {
+            auth_name: Some("tls".to_string()),
+            auth_params: Some("tlsCertFile:/tmp/client.pem,tlsKeyFile:/tmp/client.key".to_string()),
+            ..Default::default()
+        }.into();
+        assert!(matches!(ctx.client_builder(), Err(Error::Custom(e)) if e.contains("admin commands")));
     }
 }
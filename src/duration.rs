@@ -0,0 +1,34 @@
+/// Parses a relative time such as `30s`, `10m`, `2h`, `7d`, `1w` or `1y` into seconds.
+pub fn parse_relative_time(time: &str) -> Result<u64, String> {
+    let time = time.trim();
+    let (value, unit) = match time.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => time.split_at(i),
+        None => (time, "s"),
+    };
+    let value: u64 = value.parse().map_err(|_| format!("invalid relative time [{}]", time))?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return Err(format!("invalid unit [{}] in [{}], expected s, m, h, d, w or y", unit, time)),
+    };
+    value.checked_mul(unit).ok_or_else(|| format!("relative time [{}] is too large", time))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::duration::parse_relative_time;
+
+    #[test]
+    fn relative_times() {
+        assert_eq!(parse_relative_time("30").unwrap(), 30);
+        assert_eq!(parse_relative_time("10m").unwrap(), 600);
+        assert_eq!(parse_relative_time("1y").unwrap(), 31536000);
+        assert!(parse_relative_time("1x").is_err());
+        assert!(parse_relative_time("h").is_err());
+        assert!(parse_relative_time("900000000000y").is_err());
+    }
+}
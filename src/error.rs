@@ -12,6 +12,33 @@ pub enum Error {
     Custom(String),
 }
 
+/// Process exit codes, so scripts can tell e.g. "already exists" from other failures.
+/// `2` is left to clap for usage errors.
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_NOT_FOUND: i32 = 3;
+pub const EXIT_CONFLICT: i32 = 4;
+pub const EXIT_UNAUTHORIZED: i32 = 5;
+pub const EXIT_FORBIDDEN: i32 = 6;
+pub const EXIT_PRECONDITION_FAILED: i32 = 7;
+pub const EXIT_SERVER_ERROR: i32 = 8;
+pub const EXIT_CLIENT_ERROR: i32 = 9;
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        use crate::admin::error::Error as AdminError;
+        match self {
+            Error::PulsarAdmin(AdminError::NotFound(_)) => EXIT_NOT_FOUND,
+            Error::PulsarAdmin(AdminError::Conflict(_)) => EXIT_CONFLICT,
+            Error::PulsarAdmin(AdminError::Unauthorized(_)) => EXIT_UNAUTHORIZED,
+            Error::PulsarAdmin(AdminError::Forbidden(_)) => EXIT_FORBIDDEN,
+            Error::PulsarAdmin(AdminError::PreconditionFailed(_)) => EXIT_PRECONDITION_FAILED,
+            Error::PulsarAdmin(AdminError::ClientError { .. }) => EXIT_CLIENT_ERROR,
+            Error::PulsarAdmin(AdminError::ServerError { .. }) => EXIT_SERVER_ERROR,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
 impl From<Box<dyn std::error::Error>> for Error {
     fn from(e: Box<dyn std::error::Error>) -> Self {
         Error::Custom(e.to_string())
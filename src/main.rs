@@ -1,10 +1,11 @@
 #[macro_use]
 extern crate log;
 
-use crate::cmd::cmd::AsyncCmd;
-use crate::config::Configs;
+use crate::config::{ConfigOverrides, Configs, ENV_CONTEXT, env_var, PulsarConfig};
 use crate::error::Error;
+use crate::context::PulsarContext;
 use crate::opts::{Command, parse_opts};
+use crate::output::Output;
 
 mod opts;
 mod context;
@@ -13,42 +14,60 @@ mod config;
 mod admin;
 mod auth;
 mod perf;
+mod output;
+mod manifest;
+mod duration;
+#[cfg(test)]
+mod testing;
 pub mod error;
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
+async fn main() {
     env_logger::init();
 
+    if let Err(e) = run().await {
+        eprintln!("error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+async fn run() -> Result<(), Error> {
     let opts = parse_opts();
-    let cfg = if let Some(_) = &opts.url {
-        opts.to_pulsar_config()
-    } else if matches!(opts.cmd, Command::Auth(_)) && opts.auth_name.is_some() && opts.auth_params.is_some() {
-        opts.to_pulsar_config()
+    let config_paths = Configs::resolve_paths(opts.config.as_deref())?;
+    if let Command::Config(x) = &opts.cmd {
+        return x.run(&config_paths, Output::new(opts.output));
+    }
+    if let Command::Migrate(x) = &opts.cmd {
+        return x.run(&config_paths, Output::new(opts.output)).await;
+    }
+
+    let overrides = opts.overrides().or(ConfigOverrides::from_env()?);
+    let cfg = if opts.url.is_some() || matches!(&opts.cmd, Command::Auth(x)
+        if !x.needs_context() || (opts.auth_name.is_some() && opts.auth_params.is_some())) {
+        PulsarConfig::from_overrides(&overrides)
     } else {
-        let configs = Configs::load()?;
-        if let Some(context_name) = &opts.context {
-            configs.get_pulsar_config(context_name)?
+        let configs = Configs::load_merged(&config_paths)?;
+        let context_name = opts.context.clone().or_else(|| env_var(ENV_CONTEXT));
+        if let Some(context_name) = &context_name {
+            configs.get_pulsar_config(context_name, &overrides)?
         } else if configs.has_current_context() {
-            configs.get_current_pulsar_config()?
+            configs.get_current_pulsar_config(&overrides)?
+        } else if overrides.url.is_some() {
+            PulsarConfig::from_overrides(&overrides)
         } else {
             return Err(Error::Custom("no valid contexts".to_string()));
         }
     };
 
-    let mut ctx = cfg.into();
-    let cmd: &dyn AsyncCmd = match &opts.cmd {
-        Command::Produce(x) => x,
-        Command::Consume(x) => x,
-        Command::Clusters(x) => x,
-        Command::Tenants(x) => x,
-        Command::Namespaces(x) => x,
-        Command::Topics(x) => x,
-        Command::Auth(x) => x,
-        Command::Functions(x) => x,
-        Command::Sinks(x) => x,
-        Command::Perf(x) => x,
-    };
-    cmd.run(&mut ctx).await?;
+    let mut ctx: PulsarContext = cfg.into();
+    ctx.set_output(Output::new(opts.output));
+    match &opts.cmd {
+        Command::Shell(x) => {
+            let context = opts.context.clone().or_else(|| env_var(ENV_CONTEXT));
+            x.run(ctx, &config_paths, &overrides, context).await?;
+        }
+        cmd => cmd.as_async_cmd().unwrap().run(&mut ctx).await?,
+    }
 
     Ok(())
 }
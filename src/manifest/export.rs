@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::namespaces::{BacklogQuotaType, Deduplication, EncryptionRequired, MaxConsumersPerTopic,
+                               MaxProducersPerTopic, MaxSubscriptionsPerTopic, MessageTtl,
+                               SchemaCompatibilityStrategy, SchemaValidationEnforced, SubscriptionExpirationTime};
+use crate::admin::naming::{NamespaceName, TopicName};
+use crate::error::Error;
+use crate::manifest::plan::{latest_schema, list_topics};
+use crate::manifest::{Manifest, NamespacePolicySpec, NamespaceSpec, TenantSpec, TopicPolicySpec, TopicSpec};
+
+/// Describes a tenant with all its namespaces.
+pub async fn export_tenant(admin: &PulsarAdmin, tenant: &str) -> Result<Manifest, Error> {
+    let mut manifest = Manifest {
+        tenants: vec![export_tenant_info(admin, tenant).await?],
+        namespaces: Vec::new(),
+    };
+    let mut namespaces = admin.namespaces().list(tenant).await?;
+    namespaces.sort();
+    for name in namespaces {
+        manifest.namespaces.push(export_namespace(admin, &NamespaceName::parse(name.as_str())?).await?);
+    }
+    Ok(manifest)
+}
+
+/// Describes a tenant without its namespaces.
+pub async fn export_tenant_info(admin: &PulsarAdmin, tenant: &str) -> Result<TenantSpec, Error> {
+    let info = admin.tenants().get(tenant).await?;
+    Ok(TenantSpec {
+        name: tenant.to_string(),
+        admin_roles: Some(info.admin_roles),
+        allowed_clusters: Some(info.allowed_clusters),
+    })
+}
+
+/// Describes a namespace with its topics, but not its tenant, so applying it elsewhere leaves
+/// the other namespaces of the tenant alone.
+pub async fn export_namespace(admin: &PulsarAdmin, namespace: &NamespaceName) -> Result<NamespaceSpec, Error> {
+    let document = admin.namespaces().policies(namespace).await?;
+    // rates are kept per cluster in the document, their endpoints answer for this cluster
+    let mut policies = NamespacePolicySpec {
+        retention: document.retention_policies,
+        message_ttl: document.message_ttl_in_seconds.map(MessageTtl),
+        backlog_quotas: None,
+        dispatch_rate: admin.namespaces().policy(namespace).await?,
+        subscription_dispatch_rate: admin.namespaces().policy(namespace).await?,
+        replicator_dispatch_rate: admin.namespaces().policy(namespace).await?,
+        subscribe_rate: admin.namespaces().policy(namespace).await?,
+        publish_rate: admin.namespaces().policy(namespace).await?,
+        deduplication: document.deduplication_enabled.map(Deduplication),
+        subscription_expiration_time: document.subscription_expiration_time_minutes.map(SubscriptionExpirationTime),
+        max_producers_per_topic: document.max_producers_per_topic.map(MaxProducersPerTopic),
+        max_consumers_per_topic: document.max_consumers_per_topic.map(MaxConsumersPerTopic),
+        max_subscriptions_per_topic: document.max_subscriptions_per_topic.map(MaxSubscriptionsPerTopic),
+        auto_topic_creation: document.auto_topic_creation_override,
+        auto_subscription_creation: document.auto_subscription_creation_override,
+        schema_compatibility_strategy: document.schema_compatibility_strategy
+            .filter(|s| *s != SchemaCompatibilityStrategy::Undefined),
+        schema_validation_enforced: document.schema_validation_enforced
+            .filter(|enforced| *enforced)
+            .map(SchemaValidationEnforced),
+        encryption_required: document.encryption_required.filter(|required| *required).map(EncryptionRequired),
+        delayed_delivery: document.delayed_delivery_policies,
+        inactive_topic_policies: document.inactive_topic_policies,
+        persistence: document.persistence,
+    };
+    let quotas: BTreeMap<BacklogQuotaType, _> = document.backlog_quota_map.unwrap_or_default().into_iter()
+        .filter_map(|(name, quota)| Some((serde_json::from_value(Value::String(name)).ok()?, quota)))
+        .collect();
+    if !quotas.is_empty() {
+        policies.backlog_quotas = Some(quotas);
+    }
+
+    let permissions = admin.namespaces().permissions(namespace).await?;
+    let mut topics = Vec::new();
+    let mut topic_policies = true;
+    for (topic, partitioned) in list_topics(admin, namespace).await? {
+        let mut spec = TopicSpec {
+            name: if topic.is_persistent() { topic.local_name().to_string() } else { topic.to_string() },
+            ..Default::default()
+        };
+        if partitioned {
+            spec.partitions = admin.topics().partitioned_metadata(&topic).await?.partitions;
+        }
+        if topic_policies {
+            match export_topic_policies(admin, &topic).await? {
+                Some(policies) => spec.policies = policies,
+                // the broker has topic level policies disabled
+                None => topic_policies = false,
+            }
+        }
+        spec.schema = latest_schema(admin, &topic).await?;
+        let granted: BTreeMap<String, Vec<String>> = admin.topics().permissions(&topic).await?.into_iter()
+            .filter(|(role, actions)| permissions.get(role) != Some(actions))
+            .collect();
+        if !granted.is_empty() {
+            spec.permissions = Some(granted);
+        }
+        let mut subscriptions = admin.topics().subscriptions(&topic).await?;
+        subscriptions.sort();
+        spec.subscriptions = Some(subscriptions);
+        topics.push(spec);
+    }
+
+    Ok(NamespaceSpec {
+        name: namespace.to_string(),
+        bundles: document.bundles.map(|bundles| bundles.num_bundles),
+        replication_clusters: document.replication_clusters.filter(|clusters| !clusters.is_empty()),
+        policies,
+        permissions: Some(permissions),
+        topics: Some(topics),
+    })
+}
+
+/// The policies set on the topic itself, `None` if the broker does not support them.
+async fn export_topic_policies(admin: &PulsarAdmin, topic: &TopicName) -> Result<Option<TopicPolicySpec>, Error> {
+    let retention = match admin.topics().policy(topic).await {
+        Ok(retention) => retention,
+        Err(e) if e.status() == Some(405) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(TopicPolicySpec {
+        retention,
+        dispatch_rate: admin.topics().policy(topic).await?,
+        subscription_dispatch_rate: admin.topics().policy(topic).await?,
+        replicator_dispatch_rate: admin.topics().policy(topic).await?,
+        subscribe_rate: admin.topics().policy(topic).await?,
+        publish_rate: admin.topics().policy(topic).await?,
+        max_subscriptions_per_topic: admin.topics().policy(topic).await?,
+        delayed_delivery: admin.topics().policy(topic).await?,
+        inactive_topic_policies: admin.topics().policy(topic).await?,
+        persistence: admin.topics().policy(topic).await?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::admin::PulsarAdmin;
+    use crate::manifest::export::export_tenant;
+    use crate::manifest::plan::Plan;
+    use crate::manifest::Manifest;
+    use crate::testing::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn export_and_reapply() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/tenants") => MockResponse::new(200, r#"["acme"]"#),
+            ("GET", "/admin/v2/tenants/acme") =>
+                MockResponse::new(200, r#"{"adminRoles":["admin"],"allowedClusters":["standalone"]}"#),
+            ("GET", "/admin/v2/namespaces/acme") => MockResponse::new(200, r#"["acme/orders"]"#),
+            ("GET", "/admin/v2/namespaces/acme/orders") => MockResponse::new(200, r#"{
+                "bundles":{"numBundles":2,"boundaries":["0x00000000","0x80000000","0xffffffff"]},
+                "replication_clusters":["standalone"],"message_ttl_in_seconds":60,"deleted":false,
+                "backlog_quota_map":{"destination_storage":{"limitSize":1024,"policy":"producer_request_hold"}},
+                "schema_compatibility_strategy":"UNDEFINED","encryption_required":false}"#),
+            ("GET", "/admin/v2/namespaces/acme/orders/messageTTL") => MockResponse::new(200, "60"),
+            ("GET", "/admin/v2/namespaces/acme/orders/backlogQuotaMap") =>
+                MockResponse::new(200, r#"{"destination_storage":{"limitSize":1024,"policy":"producer_request_hold"}}"#),
+            ("GET", "/admin/v2/namespaces/acme/orders/dispatchRate") => MockResponse::new(200,
+                r#"{"dispatchThrottlingRateInMsg":100,"dispatchThrottlingRateInByte":-1,"ratePeriodInSecond":1}"#),
+            ("GET", "/admin/v2/namespaces/acme/orders/permissions") => MockResponse::new(200, r#"{"app":["produce"]}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/partitioned") =>
+                MockResponse::new(200, r#"["persistent://acme/orders/in"]"#),
+            ("GET", "/admin/v2/persistent/acme/orders") => MockResponse::new(200,
+                r#"["persistent://acme/orders/in-partition-0","persistent://acme/orders/in-partition-1",
+                    "persistent://acme/orders/__change_events"]"#),
+            ("GET", "/admin/v2/non-persistent/acme/orders") | ("GET", "/admin/v2/non-persistent/acme/orders/partitioned") =>
+                MockResponse::new(200, "[]"),
+            ("GET", "/admin/v2/persistent/acme/orders/in/partitions") => MockResponse::new(200, r#"{"partitions":2}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/in/retention") =>
+                MockResponse::new(200, r#"{"retentionTimeInMinutes":60,"retentionSizeInMB":-1}"#),
+            ("GET", "/admin/v2/schemas/acme/orders/in/schema") =>
+                MockResponse::new(200, r#"{"version":3,"type":"STRING","timestamp":1,"data":"","properties":{}}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/in/permissions") =>
+                MockResponse::new(200, r#"{"app":["produce"],"ops":["consume"]}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/in/subscriptions") => MockResponse::new(200, r#"["billing"]"#),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+
+        let manifest = export_tenant(&admin, "acme").await.unwrap();
+        let yaml = serde_yaml::to_string(&manifest).unwrap();
+        assert!(!yaml.contains("__change_events"));
+        assert!(!yaml.contains("boundaries"));
+        assert!(!yaml.contains("deleted"));
+        assert!(!yaml.contains("version"));
+        let namespace = &manifest.namespaces[0];
+        assert_eq!(namespace.bundles, Some(2));
+        assert!(namespace.policies.schema_compatibility_strategy.is_none());
+        assert!(namespace.policies.encryption_required.is_none());
+        let topic = &namespace.topics.as_ref().unwrap()[0];
+        assert_eq!((topic.name.as_str(), topic.partitions), ("in", 2));
+        assert_eq!(topic.permissions.as_ref().unwrap().keys().collect::<Vec<_>>(), vec!["ops"]);
+        assert_eq!(topic.schema.as_ref().unwrap().schema_type, "STRING");
+
+        let reread = Manifest::parse(yaml.as_str()).unwrap();
+        assert_eq!(reread, manifest);
+        let plan = Plan::compute(&admin, &reread, true).await.unwrap();
+        assert!(plan.is_empty(), "{}", serde_json::to_string(&plan).unwrap());
+    }
+}
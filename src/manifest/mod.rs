@@ -0,0 +1,340 @@
+pub mod export;
+pub mod plan;
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use crate::admin::namespaces::{AutoSubscriptionCreationOverride, AutoTopicCreationOverride, BacklogQuota,
+                               BacklogQuotaType, DelayedDeliveryPolicies, Deduplication, DispatchRate,
+                               EncryptionRequired, InactiveTopicPolicies, MaxConsumersPerTopic,
+                               MaxProducersPerTopic, MaxSubscriptionsPerTopic, MessageTtl, PersistencePolicies,
+                               PublishRate, ReplicatorDispatchRate, RetentionPolicies,
+                               SchemaCompatibilityStrategy, SchemaValidationEnforced, SubscribeRate,
+                               SubscriptionDispatchRate, SubscriptionExpirationTime};
+use crate::admin::naming::{NamespaceName, TopicName};
+use crate::admin::schemas::PostSchemaPayload;
+use crate::error::Error;
+
+/// Tenants, namespaces, topics and subscriptions as they should be. Whatever a manifest
+/// leaves out, e.g. a policy or the `permissions` of a namespace, is not managed by it and
+/// stays as it is on the cluster.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tenants: Vec<TenantSpec>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<NamespaceSpec>,
+}
+
+/// A tenant; listing it also makes its namespaces subject to `--prune`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TenantSpec {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_roles: Option<Vec<String>>,
+
+    /// Defaults to the only cluster on create.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_clusters: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NamespaceSpec {
+    /// `tenant/namespace`
+    pub name: String,
+
+    /// Only used when the namespace is created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundles: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication_clusters: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "NamespacePolicySpec::is_empty")]
+    pub policies: NamespacePolicySpec,
+
+    /// Actions by role; roles missing here are revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<BTreeMap<String, Vec<String>>>,
+
+    /// Listing topics, even none, also makes the other topics of the namespace subject to
+    /// `--prune`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<TopicSpec>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NamespacePolicySpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_ttl: Option<MessageTtl>,
+
+    /// Quotas missing here are removed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backlog_quotas: Option<BTreeMap<BacklogQuotaType, BacklogQuota>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dispatch_rate: Option<DispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_dispatch_rate: Option<SubscriptionDispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicator_dispatch_rate: Option<ReplicatorDispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe_rate: Option<SubscribeRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_rate: Option<PublishRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deduplication: Option<Deduplication>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_expiration_time: Option<SubscriptionExpirationTime>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_producers_per_topic: Option<MaxProducersPerTopic>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_consumers_per_topic: Option<MaxConsumersPerTopic>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_subscriptions_per_topic: Option<MaxSubscriptionsPerTopic>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_topic_creation: Option<AutoTopicCreationOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_subscription_creation: Option<AutoSubscriptionCreationOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_compatibility_strategy: Option<SchemaCompatibilityStrategy>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_validation_enforced: Option<SchemaValidationEnforced>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_required: Option<EncryptionRequired>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delayed_delivery: Option<DelayedDeliveryPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_topic_policies: Option<InactiveTopicPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<PersistencePolicies>,
+}
+
+impl NamespacePolicySpec {
+    pub fn is_empty(&self) -> bool {
+        *self == NamespacePolicySpec::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TopicSpec {
+    /// A name in the namespace, or a full topic name.
+    pub name: String,
+
+    /// `0` for a non-partitioned topic. Partitions can be added but not removed.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub partitions: u32,
+
+    #[serde(default, skip_serializing_if = "TopicPolicySpec::is_empty")]
+    pub policies: TopicPolicySpec,
+
+    /// Uploaded as a new version when it differs from the latest one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<PostSchemaPayload>,
+
+    /// Actions by role; roles missing here are revoked, except those granted on the namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<BTreeMap<String, Vec<String>>>,
+
+    /// Subscriptions missing here are removed with `--prune`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscriptions: Option<Vec<String>>,
+}
+
+/// The policies a topic can override; the others only exist on the namespace.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TopicPolicySpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dispatch_rate: Option<DispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_dispatch_rate: Option<SubscriptionDispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicator_dispatch_rate: Option<ReplicatorDispatchRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe_rate: Option<SubscribeRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_rate: Option<PublishRate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_subscriptions_per_topic: Option<MaxSubscriptionsPerTopic>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delayed_delivery: Option<DelayedDeliveryPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_topic_policies: Option<InactiveTopicPolicies>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<PersistencePolicies>,
+}
+
+impl TopicPolicySpec {
+    pub fn is_empty(&self) -> bool {
+        *self == TopicPolicySpec::default()
+    }
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl Manifest {
+    /// Reads and merges manifests, each file possibly holding several YAML documents; `-`
+    /// reads standard input.
+    pub fn read(paths: &[String]) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::default();
+        for path in paths {
+            let content = if path == "-" {
+                let mut content = String::new();
+                std::io::stdin().read_to_string(&mut content)?;
+                content
+            } else {
+                fs::read_to_string(path)
+                    .map_err(|e| Error::Custom(format!("failed to read {}: {}", path, e)))?
+            };
+            let part = Manifest::parse(content.as_str())
+                .map_err(|e| Error::Custom(format!("invalid manifest {}: {}", path, e)))?;
+            manifest.tenants.extend(part.tenants);
+            manifest.namespaces.extend(part.namespaces);
+        }
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn parse(content: &str) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::default();
+        for document in serde_yaml::Deserializer::from_str(content) {
+            let part = Manifest::deserialize(document)?;
+            manifest.tenants.extend(part.tenants);
+            manifest.namespaces.extend(part.namespaces);
+        }
+        Ok(manifest)
+    }
+
+    /// Checks names and that nothing is declared twice.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut tenants = HashSet::new();
+        for tenant in &self.tenants {
+            if tenant.name.is_empty() || tenant.name.contains('/') {
+                return Err(Error::Custom(format!("invalid tenant name [{}]", tenant.name)));
+            }
+            if !tenants.insert(tenant.name.as_str()) {
+                return Err(Error::Custom(format!("tenant {} is declared twice", tenant.name)));
+            }
+        }
+        let mut namespaces = HashSet::new();
+        for spec in &self.namespaces {
+            let namespace = spec.namespace()?;
+            let mut topics = HashSet::new();
+            for topic in spec.topics.iter().flatten() {
+                let topic = spec.topic(topic)?;
+                if !topics.insert(topic.to_string()) {
+                    return Err(Error::Custom(format!("topic {} is declared twice", topic)));
+                }
+            }
+            if !namespaces.insert(namespace) {
+                return Err(Error::Custom(format!("namespace {} is declared twice", spec.name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NamespaceSpec {
+    pub fn namespace(&self) -> Result<NamespaceName, Error> {
+        Ok(NamespaceName::parse(self.name.as_str())?)
+    }
+
+    /// The full name of a topic of this namespace.
+    pub fn topic(&self, spec: &TopicSpec) -> Result<TopicName, Error> {
+        let namespace = self.namespace()?;
+        let topic = TopicName::parse_in(spec.name.as_str(), &namespace)?;
+        if topic.namespace() != &namespace {
+            return Err(Error::Custom(format!("topic {} is not in namespace {}", topic, namespace)));
+        }
+        if topic.is_partitioned() {
+            return Err(Error::Custom(format!("topic {} is a partition, declare its partitioned topic instead",
+                                             topic)));
+        }
+        Ok(topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::namespaces::{BacklogQuotaType, MessageTtl};
+    use crate::manifest::Manifest;
+
+    #[test]
+    fn parse() {
+        let manifest = Manifest::parse(r#"
+tenants:
+  - name: acme
+    adminRoles: [admin]
+---
+namespaces:
+  - name: acme/orders
+    policies:
+      messageTtl: 3600
+      backlogQuotas:
+        destination_storage: {limitSize: 1073741824, policy: producer_request_hold}
+    topics:
+      - name: in
+        partitions: 4
+        subscriptions: [billing]
+"#).unwrap();
+        assert_eq!(manifest.tenants[0].admin_roles, Some(vec!["admin".to_string()]));
+        assert_eq!(manifest.tenants[0].allowed_clusters, None);
+        let namespace = &manifest.namespaces[0];
+        assert_eq!(namespace.policies.message_ttl, Some(MessageTtl(3600)));
+        assert!(namespace.policies.backlog_quotas.as_ref().unwrap().contains_key(&BacklogQuotaType::DestinationStorage));
+        let topic = &namespace.topics.as_ref().unwrap()[0];
+        assert_eq!(namespace.topic(topic).unwrap().to_string(), "persistent://acme/orders/in");
+        assert!(manifest.validate().is_ok());
+
+        assert!(Manifest::parse("namespaces:\n  - name: acme/orders\n    retention: {}\n").is_err());
+        let duplicate = Manifest::parse("namespaces:\n  - name: acme/orders\n  - name: acme/orders\n").unwrap();
+        assert!(duplicate.validate().is_err());
+        let foreign = Manifest::parse("namespaces:\n  - name: acme/orders\n    topics:\n      - name: acme/other/in\n")
+            .unwrap();
+        assert!(foreign.validate().is_err());
+    }
+}
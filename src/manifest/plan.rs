@@ -0,0 +1,781 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::admin::admin::PulsarAdmin;
+use crate::admin::error::Error as AdminError;
+use crate::admin::functions::workers_list;
+use crate::admin::namespaces::{BacklogQuota, BacklogQuotaType, BundlesData, NamespacePolicies, NamespacePolicy};
+use crate::admin::naming::{NamespaceName, TopicDomain, TopicName};
+use crate::admin::schemas::PostSchemaPayload;
+use crate::admin::tenants::TenantInfo;
+use crate::error::Error;
+use crate::manifest::{Manifest, NamespacePolicySpec, NamespaceSpec, TenantSpec, TopicPolicySpec, TopicSpec};
+use crate::output::{cell, Render, Table};
+
+/// The changes that bring a cluster to a manifest, in the order they can be applied: parents
+/// are created before their children and deleted after them.
+#[derive(Serialize, Default)]
+#[serde(transparent)]
+pub struct Plan {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Serialize)]
+pub struct Change {
+    pub action: Action,
+
+    pub kind: &'static str,
+
+    pub name: String,
+
+    /// The manifest field for changes to a part of the resource, e.g. `retention`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Value>,
+
+    #[serde(skip)]
+    op: Op,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+        }
+    }
+}
+
+/// Where a policy or permission is set.
+#[derive(Debug, Clone)]
+enum Target {
+    Namespace(NamespaceName),
+    Topic(TopicName),
+}
+
+impl Target {
+    fn kind(&self) -> &'static str {
+        match self {
+            Target::Namespace(_) => "namespace",
+            Target::Topic(_) => "topic",
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Target::Namespace(namespace) => namespace.to_string(),
+            Target::Topic(topic) => topic.to_string(),
+        }
+    }
+
+    async fn policy<P: NamespacePolicy>(&self, admin: &PulsarAdmin) -> Result<Option<P>, AdminError> {
+        match self {
+            Target::Namespace(namespace) => admin.namespaces().policy::<P>(namespace).await,
+            Target::Topic(topic) => admin.topics().policy::<P>(topic).await,
+        }
+    }
+
+    async fn permissions(&self, admin: &PulsarAdmin) -> Result<BTreeMap<String, Vec<String>>, AdminError> {
+        match self {
+            Target::Namespace(namespace) => admin.namespaces().permissions(namespace).await,
+            Target::Topic(topic) => admin.topics().permissions(topic).await,
+        }
+    }
+}
+
+/// A policy value whose type is only known where the plan is computed.
+#[async_trait]
+trait PolicyValue: Send + Sync {
+    async fn set(&self, admin: &PulsarAdmin, target: &Target) -> Result<(), AdminError>;
+}
+
+#[async_trait]
+impl<P: NamespacePolicy> PolicyValue for P {
+    async fn set(&self, admin: &PulsarAdmin, target: &Target) -> Result<(), AdminError> {
+        match target {
+            Target::Namespace(namespace) => admin.namespaces().set_policy(namespace, self).await,
+            Target::Topic(topic) => admin.topics().set_policy(topic, self).await,
+        }
+    }
+}
+
+enum Op {
+    CreateTenant(String, TenantInfo),
+    UpdateTenant(String, TenantInfo),
+    CreateNamespace(NamespaceName, Option<u64>),
+    SetReplicationClusters(NamespaceName, Vec<String>),
+    SetPolicy(Target, Box<dyn PolicyValue>),
+    SetBacklogQuota(NamespaceName, BacklogQuotaType, BacklogQuota),
+    RemoveBacklogQuota(NamespaceName, BacklogQuotaType),
+    Grant(Target, String, Vec<String>),
+    Revoke(Target, String),
+    CreateTopic(TopicName, u32),
+    UpdatePartitions(TopicName, u32),
+    CreateSchema(TopicName, PostSchemaPayload),
+    CreateSubscription(TopicName, String),
+    DeleteSubscription(TopicName, String),
+    DeleteTopic(TopicName, bool),
+    DeleteFunction(NamespaceName, String),
+    DeleteSink(NamespaceName, String),
+    DeleteNamespace(NamespaceName),
+}
+
+impl Change {
+    /// Whether the change replaces or removes something the cluster already has, rather than
+    /// adding to it.
+    pub fn is_conflict(&self) -> bool {
+        self.action != Action::Create
+    }
+
+    /// With `force`, subscriptions and topics are deleted even if clients are connected to them.
+    pub(crate) async fn apply(&self, admin: &PulsarAdmin, force: bool) -> Result<(), AdminError> {
+        info!("{} {} {} {}", self.action.name(), self.kind, self.name, self.field.as_deref().unwrap_or(""));
+        match &self.op {
+            Op::CreateTenant(tenant, info) => admin.tenants().create(tenant, info.clone()).await,
+            Op::UpdateTenant(tenant, info) => admin.tenants().update(tenant, info).await,
+            Op::CreateNamespace(namespace, bundles) => {
+                let policies = NamespacePolicies {
+                    bundles: bundles.map(|num_bundles| BundlesData {
+                        num_bundles,
+                        boundaries: Vec::new(),
+                    }),
+                    ..Default::default()
+                };
+                admin.namespaces().create(namespace, &policies).await
+            }
+            Op::SetReplicationClusters(namespace, clusters) =>
+                admin.namespaces().set_replication_clusters(namespace, clusters).await,
+            Op::SetPolicy(target, policy) => policy.set(admin, target).await,
+            Op::SetBacklogQuota(namespace, quota_type, quota) =>
+                admin.namespaces().set_backlog_quota(namespace, *quota_type, quota).await,
+            Op::RemoveBacklogQuota(namespace, quota_type) =>
+                admin.namespaces().remove_backlog_quota(namespace, *quota_type).await,
+            Op::Grant(Target::Namespace(namespace), role, actions) =>
+                admin.namespaces().grant_permission(namespace, role, actions).await,
+            Op::Grant(Target::Topic(topic), role, actions) =>
+                admin.topics().grant_permissions(topic, role, actions).await,
+            Op::Revoke(Target::Namespace(namespace), role) =>
+                admin.namespaces().revoke_permission(namespace, role).await,
+            Op::Revoke(Target::Topic(topic), role) =>
+                admin.topics().revoke_permissions(topic, role).await,
+            Op::CreateTopic(topic, 0) => admin.topics().create_non_partitioned_topic(topic).await,
+            Op::CreateTopic(topic, partitions) =>
+                admin.topics().create_partitioned_topic(topic, *partitions as i32).await,
+            Op::UpdatePartitions(topic, partitions) => admin.topics().update_partitions(topic, *partitions, false, false).await,
+            Op::CreateSchema(topic, schema) => admin.schemas().create(topic, schema).await,
+            Op::CreateSubscription(topic, subscription) =>
+                admin.topics().create_subscription(topic, subscription).await,
+            Op::DeleteSubscription(topic, subscription) =>
+                admin.topics().unsubscribe(topic, subscription, force).await,
+            Op::DeleteTopic(topic, true) => admin.topics().delete_partitioned_topic(topic, force, true).await,
+            Op::DeleteTopic(topic, false) => admin.topics().delete_topic(topic, force, true).await,
+            Op::DeleteFunction(namespace, function) => admin.functions().delete(namespace, function).await,
+            Op::DeleteSink(namespace, sink) => admin.sinks().delete(namespace, sink).await,
+            Op::DeleteNamespace(namespace) => admin.namespaces().delete(namespace, false).await,
+        }
+    }
+}
+
+impl Plan {
+    /// Compares a manifest with the cluster. With `prune`, namespaces of the tenants, topics of
+    /// the namespaces and subscriptions of the topics the manifest lists completely are deleted
+    /// when the manifest does not declare them.
+    pub async fn compute(admin: &PulsarAdmin, manifest: &Manifest, prune: bool) -> Result<Plan, Error> {
+        let mut planner = Planner { admin, prune, changes: Vec::new(), deletes: Vec::new() };
+        let tenants = admin.tenants().list().await?;
+        for spec in &manifest.tenants {
+            planner.tenant(spec, tenants.contains(&spec.name)).await?;
+        }
+
+        let mut namespaces: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for tenant in &tenants {
+            let managed = manifest.tenants.iter().any(|t| &t.name == tenant)
+                || manifest.namespaces.iter().any(|ns| ns.name.split('/').next() == Some(tenant.as_str()));
+            if managed {
+                namespaces.insert(tenant.clone(), admin.namespaces().list(tenant).await?);
+            }
+        }
+        for spec in &manifest.namespaces {
+            let namespace = spec.namespace()?;
+            let exists = namespaces.get(namespace.tenant())
+                .map(|names| names.contains(&namespace.to_string()))
+                .unwrap_or(false);
+            planner.namespace(spec, &namespace, exists).await?;
+        }
+
+        if prune {
+            for spec in &manifest.tenants {
+                for name in namespaces.get(&spec.name).into_iter().flatten() {
+                    if !manifest.namespaces.iter().any(|ns| ns.namespace().map(|ns| &ns.to_string() == name).unwrap_or(false)) {
+                        planner.prune_namespace(&NamespaceName::parse(name)?).await?;
+                    }
+                }
+            }
+        }
+
+        let mut changes = planner.changes;
+        changes.extend(planner.deletes);
+        Ok(Plan { changes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Applies the changes in order, stopping at the first failure. With `force`, subscriptions
+    /// and topics are deleted even if clients are connected to them. Every change is reported
+    /// with its status, so a failure still shows what was applied before it; the failure itself
+    /// is returned after the report.
+    pub async fn apply(&self, admin: &PulsarAdmin, force: bool) -> (Vec<ChangeResult<'_>>, Result<(), Error>) {
+        let mut report = Vec::with_capacity(self.changes.len());
+        let mut result = Ok(());
+        for change in &self.changes {
+            let status = if result.is_err() {
+                "not applied"
+            } else {
+                match change.apply(admin, force).await {
+                    Ok(()) if change.action == Action::Delete => "deleted",
+                    Ok(()) => "applied",
+                    Err(e) => {
+                        result = Err(e.into());
+                        "failed"
+                    }
+                }
+            };
+            report.push(ChangeResult { change, status });
+        }
+        (report, result)
+    }
+}
+
+/// What became of a change of a plan.
+#[derive(Serialize)]
+pub struct ChangeResult<'a> {
+    #[serde(flatten)]
+    pub change: &'a Change,
+
+    pub status: &'static str,
+}
+
+struct Planner<'a> {
+    admin: &'a PulsarAdmin,
+    prune: bool,
+    changes: Vec<Change>,
+    /// Deletes of `--prune`, children first, applied after everything else.
+    deletes: Vec<Change>,
+}
+
+impl<'a> Planner<'a> {
+    fn push(&mut self, kind: &'static str, name: String, field: Option<&str>,
+            from: Option<Value>, to: Option<Value>, op: Op) {
+        let action = if from.is_none() { Action::Create } else { Action::Update };
+        self.changes.push(Change { action, kind, name, field: field.map(|f| f.to_string()), from, to, op });
+    }
+
+    fn delete(&mut self, kind: &'static str, name: String, op: Op) {
+        self.deletes.push(Change { action: Action::Delete, kind, name, field: None, from: None, to: None, op });
+    }
+
+    async fn tenant(&mut self, spec: &TenantSpec, exists: bool) -> Result<(), Error> {
+        if !exists {
+            let info = TenantInfo {
+                admin_roles: spec.admin_roles.clone().unwrap_or_default(),
+                allowed_clusters: spec.allowed_clusters.clone().unwrap_or_default(),
+            };
+            self.push("tenant", spec.name.clone(), None, None, Some(to_value(&info)),
+                      Op::CreateTenant(spec.name.clone(), info));
+            return Ok(());
+        }
+
+        let current = self.admin.tenants().get(spec.name.as_str()).await?;
+        let mut info = current.clone();
+        if let Some(roles) = &spec.admin_roles {
+            info.admin_roles = roles.clone();
+        }
+        if let Some(clusters) = &spec.allowed_clusters {
+            info.allowed_clusters = clusters.clone();
+        }
+        // a tenant is updated as a whole, so both fields changing is still a single change
+        let roles = !same_items(&current.admin_roles, &info.admin_roles);
+        let clusters = !same_items(&current.allowed_clusters, &info.allowed_clusters);
+        let (field, from, to) = match (roles, clusters) {
+            (false, false) => return Ok(()),
+            (true, false) => (Some("adminRoles"), to_value(&current.admin_roles), to_value(&info.admin_roles)),
+            (false, true) =>
+                (Some("allowedClusters"), to_value(&current.allowed_clusters), to_value(&info.allowed_clusters)),
+            (true, true) => (None, to_value(&current), to_value(&info)),
+        };
+        self.push("tenant", spec.name.clone(), field, Some(from), Some(to), Op::UpdateTenant(spec.name.clone(), info));
+        Ok(())
+    }
+
+    async fn namespace(&mut self, spec: &NamespaceSpec, namespace: &NamespaceName, exists: bool) -> Result<(), Error> {
+        let name = namespace.to_string();
+        if !exists {
+            self.push("namespace", name.clone(), None, None, spec.bundles.map(Value::from),
+                      Op::CreateNamespace(namespace.clone(), spec.bundles));
+        }
+
+        if let Some(clusters) = &spec.replication_clusters {
+            let current = if exists {
+                self.admin.namespaces().policies(namespace).await?.replication_clusters.unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            if !same_items(&current, clusters) {
+                let from = if exists { Some(to_value(&current)) } else { None };
+                self.push("namespace", name.clone(), Some("replicationClusters"), from, Some(to_value(clusters)),
+                          Op::SetReplicationClusters(namespace.clone(), clusters.clone()));
+            }
+        }
+
+        let target = Target::Namespace(namespace.clone());
+        self.namespace_policies(&spec.policies, &target, exists).await?;
+        if let Some(quotas) = &spec.policies.backlog_quotas {
+            self.backlog_quotas(namespace, quotas, exists).await?;
+        }
+        if let Some(permissions) = &spec.permissions {
+            self.permissions(&target, permissions, exists, &BTreeSet::new()).await?;
+        }
+
+        if let Some(topics) = &spec.topics {
+            // roles granted on the namespace show up on its topics but cannot be revoked there
+            let mut inherited: BTreeSet<String> = spec.permissions.iter().flatten().map(|(role, _)| role.clone()).collect();
+            if exists && topics.iter().any(|t| t.permissions.is_some()) {
+                inherited.extend(self.admin.namespaces().permissions(namespace).await?.into_keys());
+            }
+            let existing = if exists { list_topics(self.admin, namespace).await? } else { Vec::new() };
+            let mut declared = Vec::new();
+            for topic_spec in topics {
+                let topic = spec.topic(topic_spec)?;
+                let partitioned = existing.iter().find(|(t, _)| t == &topic).map(|(_, p)| *p);
+                self.topic(topic_spec, &topic, partitioned, &inherited).await?;
+                declared.push(topic);
+            }
+            if self.prune {
+                for (topic, partitioned) in existing {
+                    if !declared.contains(&topic) {
+                        self.delete("topic", topic.to_string(), Op::DeleteTopic(topic, partitioned));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn namespace_policies(&mut self, spec: &NamespacePolicySpec, target: &Target, exists: bool)
+                                -> Result<(), Error> {
+        self.policy(target, exists, "retention", &spec.retention).await?;
+        self.policy(target, exists, "messageTtl", &spec.message_ttl).await?;
+        self.policy(target, exists, "dispatchRate", &spec.dispatch_rate).await?;
+        self.policy(target, exists, "subscriptionDispatchRate", &spec.subscription_dispatch_rate).await?;
+        self.policy(target, exists, "replicatorDispatchRate", &spec.replicator_dispatch_rate).await?;
+        self.policy(target, exists, "subscribeRate", &spec.subscribe_rate).await?;
+        self.policy(target, exists, "publishRate", &spec.publish_rate).await?;
+        self.policy(target, exists, "deduplication", &spec.deduplication).await?;
+        self.policy(target, exists, "subscriptionExpirationTime", &spec.subscription_expiration_time).await?;
+        self.policy(target, exists, "maxProducersPerTopic", &spec.max_producers_per_topic).await?;
+        self.policy(target, exists, "maxConsumersPerTopic", &spec.max_consumers_per_topic).await?;
+        self.policy(target, exists, "maxSubscriptionsPerTopic", &spec.max_subscriptions_per_topic).await?;
+        self.policy(target, exists, "autoTopicCreation", &spec.auto_topic_creation).await?;
+        self.policy(target, exists, "autoSubscriptionCreation", &spec.auto_subscription_creation).await?;
+        self.policy(target, exists, "schemaCompatibilityStrategy", &spec.schema_compatibility_strategy).await?;
+        self.policy(target, exists, "schemaValidationEnforced", &spec.schema_validation_enforced).await?;
+        self.policy(target, exists, "encryptionRequired", &spec.encryption_required).await?;
+        self.policy(target, exists, "delayedDelivery", &spec.delayed_delivery).await?;
+        self.policy(target, exists, "inactiveTopicPolicies", &spec.inactive_topic_policies).await?;
+        self.policy(target, exists, "persistence", &spec.persistence).await?;
+        Ok(())
+    }
+
+    async fn topic_policies(&mut self, spec: &TopicPolicySpec, target: &Target, exists: bool) -> Result<(), Error> {
+        self.policy(target, exists, "retention", &spec.retention).await?;
+        self.policy(target, exists, "dispatchRate", &spec.dispatch_rate).await?;
+        self.policy(target, exists, "subscriptionDispatchRate", &spec.subscription_dispatch_rate).await?;
+        self.policy(target, exists, "replicatorDispatchRate", &spec.replicator_dispatch_rate).await?;
+        self.policy(target, exists, "subscribeRate", &spec.subscribe_rate).await?;
+        self.policy(target, exists, "publishRate", &spec.publish_rate).await?;
+        self.policy(target, exists, "maxSubscriptionsPerTopic", &spec.max_subscriptions_per_topic).await?;
+        self.policy(target, exists, "delayedDelivery", &spec.delayed_delivery).await?;
+        self.policy(target, exists, "inactiveTopicPolicies", &spec.inactive_topic_policies).await?;
+        self.policy(target, exists, "persistence", &spec.persistence).await?;
+        Ok(())
+    }
+
+    async fn policy<P: NamespacePolicy + Clone>(&mut self, target: &Target, exists: bool, field: &str,
+                                                desired: &Option<P>) -> Result<(), Error> {
+        let desired = match desired {
+            Some(desired) => desired,
+            None => return Ok(()),
+        };
+        let current = if exists { target.policy::<P>(self.admin).await? } else { None };
+        let from = current.as_ref().map(to_value);
+        let to = to_value(desired);
+        if from.as_ref() != Some(&to) {
+            self.push(target.kind(), target.name(), Some(field), from, Some(to),
+                      Op::SetPolicy(target.clone(), Box::new(desired.clone())));
+        }
+        Ok(())
+    }
+
+    async fn backlog_quotas(&mut self, namespace: &NamespaceName, quotas: &BTreeMap<BacklogQuotaType, BacklogQuota>,
+                            exists: bool) -> Result<(), Error> {
+        let current = if exists { self.admin.namespaces().backlog_quotas(namespace).await? } else { BTreeMap::new() };
+        for (quota_type, quota) in quotas {
+            let field = format!("backlogQuotas.{}", quota_type.name());
+            let from = current.get(quota_type.name()).map(to_value);
+            if from.as_ref() != Some(&to_value(quota)) {
+                self.push("namespace", namespace.to_string(), Some(field.as_str()), from, Some(to_value(quota)),
+                          Op::SetBacklogQuota(namespace.clone(), *quota_type, quota.clone()));
+            }
+        }
+        for quota_type in [BacklogQuotaType::DestinationStorage, BacklogQuotaType::MessageAge] {
+            if let (Some(quota), false) = (current.get(quota_type.name()), quotas.contains_key(&quota_type)) {
+                let field = format!("backlogQuotas.{}", quota_type.name());
+                self.changes.push(Change {
+                    action: Action::Delete,
+                    kind: "namespace",
+                    name: namespace.to_string(),
+                    field: Some(field),
+                    from: Some(to_value(quota)),
+                    to: None,
+                    op: Op::RemoveBacklogQuota(namespace.clone(), quota_type),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn permissions(&mut self, target: &Target, permissions: &BTreeMap<String, Vec<String>>, exists: bool,
+                         inherited: &BTreeSet<String>) -> Result<(), Error> {
+        let current = if exists { target.permissions(self.admin).await? } else { BTreeMap::new() };
+        for (role, actions) in permissions {
+            let field = format!("permissions.{}", role);
+            let from = current.get(role);
+            if from.map(|from| !same_items(from, actions)).unwrap_or(true) {
+                self.push(target.kind(), target.name(), Some(field.as_str()), from.map(to_value),
+                          Some(to_value(actions)), Op::Grant(target.clone(), role.clone(), actions.clone()));
+            }
+        }
+        for (role, actions) in &current {
+            if !permissions.contains_key(role) && !inherited.contains(role) {
+                self.changes.push(Change {
+                    action: Action::Delete,
+                    kind: target.kind(),
+                    name: target.name(),
+                    field: Some(format!("permissions.{}", role)),
+                    from: Some(to_value(actions)),
+                    to: None,
+                    op: Op::Revoke(target.clone(), role.clone()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn topic(&mut self, spec: &TopicSpec, topic: &TopicName, partitioned: Option<bool>,
+                   inherited: &BTreeSet<String>) -> Result<(), Error> {
+        let name = topic.to_string();
+        match partitioned {
+            None => self.push("topic", name.clone(), None, None, Some(Value::from(spec.partitions)),
+                              Op::CreateTopic(topic.clone(), spec.partitions)),
+            Some(partitioned) if partitioned != (spec.partitions > 0) =>
+                return Err(Error::Custom(format!(
+                    "topic {} is {}partitioned, it cannot be changed to {}partitioned", name,
+                    if partitioned { "" } else { "non-" }, if partitioned { "non-" } else { "" }))),
+            Some(true) => {
+                let current = self.admin.topics().partitioned_metadata(topic).await?.partitions;
+                if spec.partitions < current {
+                    return Err(Error::Custom(format!(
+                        "topic {} has {} partitions, they cannot be decreased to {}", name, current, spec.partitions)));
+                }
+                if spec.partitions > current {
+                    self.push("topic", name.clone(), Some("partitions"), Some(Value::from(current)),
+                              Some(Value::from(spec.partitions)), Op::UpdatePartitions(topic.clone(), spec.partitions));
+                }
+            }
+            Some(false) => {}
+        }
+
+        let exists = partitioned.is_some();
+        let target = Target::Topic(topic.clone());
+        self.topic_policies(&spec.policies, &target, exists).await?;
+        if let Some(schema) = &spec.schema {
+            let current = if exists { latest_schema(self.admin, topic).await? } else { None };
+            if current.as_ref() != Some(schema) {
+                self.push("topic", name.clone(), Some("schema"), current.as_ref().map(to_value), Some(to_value(schema)),
+                          Op::CreateSchema(topic.clone(), schema.clone()));
+            }
+        }
+        if let Some(permissions) = &spec.permissions {
+            self.permissions(&target, permissions, exists, inherited).await?;
+        }
+
+        if let Some(subscriptions) = &spec.subscriptions {
+            let current = if exists { self.admin.topics().subscriptions(topic).await? } else { Vec::new() };
+            for subscription in subscriptions {
+                if !current.contains(subscription) {
+                    self.push("subscription", format!("{} {}", name, subscription), None, None, None,
+                              Op::CreateSubscription(topic.clone(), subscription.clone()));
+                }
+            }
+            if self.prune {
+                for subscription in current {
+                    if !subscriptions.contains(&subscription) {
+                        self.delete("subscription", format!("{} {}", name, subscription),
+                                    Op::DeleteSubscription(topic.clone(), subscription));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes a namespace the manifest does not declare, with its functions, sinks and topics
+    /// first.
+    async fn prune_namespace(&mut self, namespace: &NamespaceName) -> Result<(), Error> {
+        for function in workers_list(self.admin.functions().list(namespace).await)? {
+            self.delete("function", format!("{}/{}", namespace, function),
+                        Op::DeleteFunction(namespace.clone(), function));
+        }
+        for sink in workers_list(self.admin.sinks().list(namespace).await)? {
+            self.delete("sink", format!("{}/{}", namespace, sink), Op::DeleteSink(namespace.clone(), sink));
+        }
+        for (topic, partitioned) in list_topics(self.admin, namespace).await? {
+            self.delete("topic", topic.to_string(), Op::DeleteTopic(topic, partitioned));
+        }
+        self.delete("namespace", namespace.to_string(), Op::DeleteNamespace(namespace.clone()));
+        Ok(())
+    }
+}
+
+/// The topics of a namespace, without partitions and system topics, and whether they are
+/// partitioned.
+pub(crate) async fn list_topics(admin: &PulsarAdmin, namespace: &NamespaceName) -> Result<Vec<(TopicName, bool)>, Error> {
+    let mut topics = BTreeMap::new();
+    for domain in [TopicDomain::Persistent, TopicDomain::NonPersistent] {
+        for name in admin.topics().list_partitioned(namespace, domain).await? {
+            topics.insert(name, true);
+        }
+        for name in admin.topics().list(namespace, domain).await? {
+            if !TopicName::parse(name.as_str())?.is_partitioned() {
+                topics.entry(name).or_insert(false);
+            }
+        }
+    }
+    let mut result = Vec::new();
+    for (name, partitioned) in topics {
+        let topic = TopicName::parse(name.as_str())?;
+        // e.g. `__change_events`, which holds topic policies
+        if !topic.local_name().starts_with("__") {
+            result.push((topic, partitioned));
+        }
+    }
+    Ok(result)
+}
+
+/// The latest schema of a topic, `None` if it has none.
+pub(crate) async fn latest_schema(admin: &PulsarAdmin, topic: &TopicName) -> Result<Option<PostSchemaPayload>, Error> {
+    match admin.schemas().get(topic).await {
+        Ok(info) => Ok(Some(info.into())),
+        Err(AdminError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Compares lists the broker keeps in no particular order.
+fn same_items(a: &[String], b: &[String]) -> bool {
+    a.iter().collect::<BTreeSet<_>>() == b.iter().collect::<BTreeSet<_>>()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+impl Render for Plan {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["ACTION", "KIND", "NAME", "FIELD", "FROM", "TO"]);
+        for change in &self.changes {
+            table.add_row(vec![
+                change.action.name().to_string(),
+                change.kind.to_string(),
+                change.name.clone(),
+                change.field.clone().unwrap_or_default(),
+                change.from.as_ref().map(cell).unwrap_or_default(),
+                change.to.as_ref().map(cell).unwrap_or_default(),
+            ]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for Vec<ChangeResult<'_>> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["ACTION", "KIND", "NAME", "FIELD", "FROM", "TO", "STATUS"]);
+        for r in self {
+            table.add_row(vec![
+                r.change.action.name().to_string(),
+                r.change.kind.to_string(),
+                r.change.name.clone(),
+                r.change.field.clone().unwrap_or_default(),
+                r.change.from.as_ref().map(cell).unwrap_or_default(),
+                r.change.to.as_ref().map(cell).unwrap_or_default(),
+                r.status.to_string(),
+            ]);
+        }
+        vec![table]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::admin::PulsarAdmin;
+    use crate::manifest::Manifest;
+    use crate::manifest::plan::Plan;
+    use crate::testing::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn plan_and_apply() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/tenants") => MockResponse::new(200, r#"["acme","public"]"#),
+            ("GET", "/admin/v2/tenants/acme") =>
+                MockResponse::new(200, r#"{"adminRoles":["admin"],"allowedClusters":["standalone"]}"#),
+            ("GET", "/admin/v2/namespaces/acme") => MockResponse::new(200, r#"["acme/orders","acme/stale"]"#),
+            ("GET", "/admin/v2/namespaces/acme/orders/messageTTL") => MockResponse::new(200, "60"),
+            ("GET", "/admin/v2/namespaces/acme/orders/permissions") =>
+                MockResponse::new(200, r#"{"app":["produce"],"old":["consume"]}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/partitioned") =>
+                MockResponse::new(200, r#"["persistent://acme/orders/in"]"#),
+            ("GET", "/admin/v2/persistent/acme/orders") => MockResponse::new(200,
+                r#"["persistent://acme/orders/in-partition-0","persistent://acme/orders/in-partition-1","persistent://acme/orders/legacy"]"#),
+            ("GET", "/admin/v2/persistent/acme/orders/in/partitions") => MockResponse::new(200, r#"{"partitions":2}"#),
+            ("GET", "/admin/v2/persistent/acme/orders/in/subscriptions") =>
+                MockResponse::new(200, r#"["billing","stale-sub"]"#),
+            ("GET", "/admin/v3/functions/acme/stale") => MockResponse::new(200, r#"["enricher"]"#),
+            ("GET", "/admin/v3/sinks/acme/stale") => MockResponse::new(404, r#"{"reason":"no worker"}"#),
+            ("GET", _) => MockResponse::new(200, "[]"),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let manifest = Manifest::parse(r#"
+tenants:
+  - name: acme
+    adminRoles: [admin, ops]
+namespaces:
+  - name: acme/orders
+    policies:
+      messageTtl: 3600
+    permissions:
+      app: [produce]
+    topics:
+      - name: in
+        partitions: 4
+        subscriptions: [billing]
+      - name: dlq
+        subscriptions: [retry]
+"#).unwrap();
+
+        let plan = Plan::compute(&admin, &manifest, true).await.unwrap();
+        let changes: Vec<String> = plan.changes.iter()
+            .map(|c| format!("{} {} {} {}", c.action.name(), c.kind, c.name, c.field.as_deref().unwrap_or("-")))
+            .collect();
+        assert_eq!(changes, vec![
+            "update tenant acme adminRoles",
+            "update namespace acme/orders messageTtl",
+            "delete namespace acme/orders permissions.old",
+            "update topic persistent://acme/orders/in partitions",
+            "create topic persistent://acme/orders/dlq -",
+            "create subscription persistent://acme/orders/dlq retry -",
+            "delete subscription persistent://acme/orders/in stale-sub -",
+            "delete topic persistent://acme/orders/legacy -",
+            "delete function acme/stale/enricher -",
+            "delete namespace acme/stale -",
+        ]);
+
+        let before = server.requests().len();
+        let (report, result) = plan.apply(&admin, false).await;
+        result.unwrap();
+        let statuses: Vec<&str> = report.iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![
+            "applied", "applied", "deleted", "applied", "applied", "applied", "deleted", "deleted", "deleted", "deleted",
+        ]);
+        let requests: Vec<String> = server.requests().into_iter().skip(before)
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(requests, vec![
+            "POST /admin/v2/tenants/acme",
+            "POST /admin/v2/namespaces/acme/orders/messageTTL",
+            "DELETE /admin/v2/namespaces/acme/orders/permissions/old",
+            "POST /admin/v2/persistent/acme/orders/in/partitions?updateLocalTopicOnly=false&force=false",
+            "PUT /admin/v2/persistent/acme/orders/dlq",
+            "PUT /admin/v2/persistent/acme/orders/dlq/subscription/retry",
+            "DELETE /admin/v2/persistent/acme/orders/in/subscription/stale-sub?force=false",
+            "DELETE /admin/v2/persistent/acme/orders/legacy?force=false&deleteSchema=true",
+            "DELETE /admin/v3/functions/acme/stale/enricher",
+            "DELETE /admin/v2/namespaces/acme/stale?force=false",
+        ]);
+
+        let unchanged = Manifest::parse("namespaces:\n  - name: acme/orders\n    policies:\n      messageTtl: 60\n")
+            .unwrap();
+        assert!(Plan::compute(&admin, &unchanged, false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn apply_reports_failure() {
+        let server = MockServer::start(|req, _| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/admin/v2/tenants") => MockResponse::new(200, r#"["acme"]"#),
+            ("GET", "/admin/v2/tenants/acme") =>
+                MockResponse::new(200, r#"{"adminRoles":["admin"],"allowedClusters":["standalone"]}"#),
+            ("GET", "/admin/v2/namespaces/acme") => MockResponse::new(200, r#"["acme/orders"]"#),
+            ("GET", _) => MockResponse::new(200, "[]"),
+            ("PUT", "/admin/v2/persistent/acme/orders/in") => MockResponse::new(500, r#"{"reason":"bookies down"}"#),
+            _ => MockResponse::new(204, ""),
+        }).await;
+        let admin = PulsarAdmin::builder(server.url().to_string()).build().unwrap();
+        let manifest = Manifest::parse(r#"
+tenants:
+  - name: acme
+    adminRoles: [ops]
+    allowedClusters: [us-east]
+namespaces:
+  - name: acme/orders
+    topics:
+      - name: in
+      - name: out
+"#).unwrap();
+
+        let plan = Plan::compute(&admin, &manifest, false).await.unwrap();
+        let before = server.requests().len();
+        let (report, result) = plan.apply(&admin, false).await;
+        assert!(result.is_err());
+        let statuses: Vec<String> = report.iter()
+            .map(|r| format!("{} {} {}", r.change.kind, r.change.name, r.status))
+            .collect();
+        assert_eq!(statuses, vec![
+            "tenant acme applied",
+            "topic persistent://acme/orders/in failed",
+            "topic persistent://acme/orders/out not applied",
+        ]);
+        let requests: Vec<String> = server.requests().into_iter().skip(before)
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(requests, vec!["POST /admin/v2/tenants/acme", "PUT /admin/v2/persistent/acme/orders/in"]);
+    }
+}
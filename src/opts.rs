@@ -1,19 +1,31 @@
 use clap::Parser;
 
+use crate::cmd::apply::{ApplyOpts, DiffOpts};
 use crate::cmd::auth::AuthOpts;
+use crate::cmd::cmd::AsyncCmd;
 use crate::cmd::clusters::ClustersOpts;
+use crate::cmd::config::ConfigOpts;
 use crate::cmd::consume::ConsumeOpts;
+use crate::cmd::export::ExportOpts;
 use crate::cmd::functions::FunctionOpts;
+use crate::cmd::migrate::MigrateOpts;
 use crate::cmd::namespaces::NamespacesOpts;
 use crate::cmd::produce::ProduceOpts;
+use crate::cmd::shell::ShellOpts;
 use crate::cmd::sinks::SinksOpts;
 use crate::cmd::tenants::TenantsOpts;
 use crate::cmd::topics::TopicsOpts;
-use crate::config::PulsarConfig;
+use crate::config::ConfigOverrides;
+use crate::output::OutputFormat;
 use crate::cmd::perf::PerfOpts;
 
 #[derive(Parser, Debug, Clone)]
 pub struct PulsarOpts {
+    /// Config file to use, or a list of files to merge separated like PATH [env: PULSAR_SMITH_CONFIG]
+    #[arg(long)]
+    pub config: Option<String>,
+
+    /// Context to use instead of the current one [env: PULSAR_SMITH_CONTEXT]
     #[arg(long)]
     pub context: Option<String>,
 
@@ -23,9 +35,12 @@ pub struct PulsarOpts {
     #[arg(long)]
     pub admin_url: Option<String>,
 
+    /// HTTP(S) proxy for admin requests [env: PULSAR_SMITH_PROXY_URL]
     #[arg(long)]
     pub proxy_url: Option<String>,
 
+    /// Auth method: token, oauth2, basic or tls. tls only works for admin commands, as the pulsar
+    /// client cannot present client certificates [env: PULSAR_SMITH_AUTH_NAME]
     #[arg(long)]
     pub auth_name: Option<String>,
 
@@ -38,19 +53,59 @@ pub struct PulsarOpts {
     #[arg(long)]
     pub tls_hostname_verification_enabled: Option<bool>,
 
+    /// PEM file of CA certificates to trust [env: PULSAR_SMITH_TLS_TRUST_CERTS_FILE_PATH]
+    #[arg(long)]
+    pub tls_trust_certs_file_path: Option<String>,
+
+    /// PEM client certificate for admin requests [env: PULSAR_SMITH_TLS_CERT_FILE_PATH]
+    #[arg(long)]
+    pub tls_cert_file_path: Option<String>,
+
+    /// PEM private key of the client certificate [env: PULSAR_SMITH_TLS_KEY_FILE_PATH]
+    #[arg(long)]
+    pub tls_key_file_path: Option<String>,
+
+    /// Timeout of admin requests in seconds [env: PULSAR_SMITH_REQUEST_TIMEOUT]
+    #[arg(long)]
+    pub request_timeout: Option<u64>,
+
+    /// Timeout of connecting to the admin service in seconds [env: PULSAR_SMITH_CONNECT_TIMEOUT]
+    #[arg(long)]
+    pub connect_timeout: Option<u64>,
+
+    /// Retries of failed admin requests, 0 to disable [env: PULSAR_SMITH_MAX_RETRIES]
+    #[arg(long)]
+    pub max_retries: Option<u32>,
+
+    /// Namespace for short topic and namespace names [env: PULSAR_SMITH_NAMESPACE]
+    #[arg(short = 'n', long)]
+    pub namespace: Option<String>,
+
+    /// Output format of command results
+    #[arg(short = 'o', long, value_enum, default_value = "table", global = true)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub cmd: Command,
 }
 
 impl PulsarOpts {
-    pub fn to_pulsar_config(&self) -> PulsarConfig {
-        PulsarConfig {
-            url: self.url.as_ref().unwrap_or(&String::from("pulsar://localhost:6650")).clone(),
-            admin_url: self.admin_url.as_ref().unwrap_or(&String::from("http://localhost:8080")).clone(),
+    pub fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            url: self.url.clone(),
+            admin_url: self.admin_url.clone(),
             auth_name: self.auth_name.clone(),
             auth_params: self.auth_params.clone(),
-            allow_insecure_connection: self.allow_insecure_connection.unwrap_or(false),
-            tls_hostname_verification_enabled: self.tls_hostname_verification_enabled.unwrap_or(true),
+            allow_insecure_connection: self.allow_insecure_connection,
+            tls_hostname_verification_enabled: self.tls_hostname_verification_enabled,
+            tls_trust_certs_file_path: self.tls_trust_certs_file_path.clone(),
+            tls_cert_file_path: self.tls_cert_file_path.clone(),
+            tls_key_file_path: self.tls_key_file_path.clone(),
+            proxy_url: self.proxy_url.clone(),
+            request_timeout: self.request_timeout,
+            connect_timeout: self.connect_timeout,
+            max_retries: self.max_retries,
+            namespace: self.namespace.clone(),
         }
     }
 }
@@ -66,7 +121,36 @@ pub enum Command {
     Auth(AuthOpts),
     Functions(FunctionOpts),
     Sinks(SinksOpts),
+    Apply(ApplyOpts),
+    Diff(DiffOpts),
+    Export(ExportOpts),
+    Migrate(MigrateOpts),
     Perf(PerfOpts),
+    Config(ConfigOpts),
+    Shell(ShellOpts),
+}
+
+impl Command {
+    /// The command to run with a context, `None` for `config`, `migrate` and `shell` which need
+    /// more.
+    pub fn as_async_cmd(&self) -> Option<&dyn AsyncCmd> {
+        match self {
+            Command::Produce(x) => Some(x),
+            Command::Consume(x) => Some(x),
+            Command::Clusters(x) => Some(x),
+            Command::Tenants(x) => Some(x),
+            Command::Namespaces(x) => Some(x),
+            Command::Topics(x) => Some(x),
+            Command::Auth(x) => Some(x),
+            Command::Functions(x) => Some(x),
+            Command::Sinks(x) => Some(x),
+            Command::Apply(x) => Some(x),
+            Command::Diff(x) => Some(x),
+            Command::Export(x) => Some(x),
+            Command::Perf(x) => Some(x),
+            Command::Config(_) | Command::Migrate(_) | Command::Shell(_) => None,
+        }
+    }
 }
 
 pub fn parse_opts() -> PulsarOpts {
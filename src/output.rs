@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Aligned columns for humans
+    #[default]
+    Table,
+    /// Like table, with additional columns
+    Wide,
+    /// Pretty printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Tab separated values without headers, for shell pipelines
+    Plain,
+}
+
+/// A human readable table, rendered with aligned columns.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Table {
+        Table {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Flattens a JSON document into `FIELD VALUE` rows, joining nested keys with `.`. A
+    /// scalar becomes a single `VALUE` row.
+    pub fn from_value(value: &Value) -> Table {
+        if !value.is_object() && !value.is_array() {
+            let mut table = Table::new(&["VALUE"]);
+            table.add_row(vec![cell(value)]);
+            return table;
+        }
+        let mut table = Table::new(&["FIELD", "VALUE"]);
+        let mut fields = BTreeMap::new();
+        flatten("", value, &mut fields);
+        for (key, value) in fields {
+            table.add_row(vec![key, value]);
+        }
+        table
+    }
+
+    fn render(&self, headers: bool) -> String {
+        let mut rows: Vec<&Vec<String>> = Vec::new();
+        if headers && !self.headers.is_empty() {
+            rows.push(&self.headers);
+        }
+        rows.extend(self.rows.iter());
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| rows.iter().filter_map(|r| r.get(i)).map(|c| c.chars().count()).max().unwrap_or(0))
+            .collect();
+        let mut out = String::new();
+        for row in rows {
+            let line: Vec<String> = row.iter().zip(widths.iter())
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            out.push_str(line.join("   ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render_plain(&self) -> String {
+        self.rows.iter()
+            .map(|r| r.join("\t") + "\n")
+            .collect()
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, fields: &mut BTreeMap<String, String>) {
+    let key = |k: &str| if prefix.is_empty() { k.to_string() } else { format!("{}.{}", prefix, k) };
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                flatten(key(k).as_str(), v, fields);
+            }
+        }
+        Value::Array(items) if items.iter().any(|i| i.is_object() || i.is_array()) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(key(i.to_string().as_str()).as_str(), v, fields);
+            }
+        }
+        _ => {
+            fields.insert(prefix.to_string(), cell(value));
+        }
+    }
+}
+
+/// Formats a JSON scalar or array of scalars as a table cell.
+pub fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<String>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Implemented by everything a command prints. JSON and YAML come from `Serialize`, tables
+/// and plain output from `tables`, which by default lists every field of the document.
+pub trait Render: Serialize {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        vec![Table::from_value(&serde_json::to_value(self).unwrap_or(Value::Null))]
+    }
+}
+
+impl Render for Value {}
+
+impl Render for Vec<String> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["NAME"]);
+        for name in self {
+            table.add_row(vec![name.clone()]);
+        }
+        vec![table]
+    }
+}
+
+impl Render for BTreeMap<String, Vec<String>> {
+    fn tables(&self, _wide: bool) -> Vec<Table> {
+        let mut table = Table::new(&["ROLE", "ACTIONS"]);
+        for (role, actions) in self {
+            table.add_row(vec![role.clone(), actions.join(",")]);
+        }
+        vec![table]
+    }
+}
+
+impl<T: Render> Render for Option<T> {
+    fn tables(&self, wide: bool) -> Vec<Table> {
+        match self {
+            Some(value) => value.tables(wide),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Output {
+    format: OutputFormat,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Output {
+        Output { format }
+    }
+
+    pub fn format(self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn to_string<T: Render + ?Sized>(self, value: &T) -> Result<String, Error> {
+        Ok(match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(value)? + "\n",
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            OutputFormat::Table | OutputFormat::Wide => value.tables(self.format == OutputFormat::Wide)
+                .iter()
+                .map(|t| t.render(true))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            OutputFormat::Plain => value.tables(false)
+                .iter()
+                .map(|t| t.render_plain())
+                .collect(),
+        })
+    }
+
+    pub fn render<T: Render + ?Sized>(self, value: &T) -> Result<(), Error> {
+        print!("{}", self.to_string(value)?);
+        Ok(())
+    }
+
+    /// Prints a status message for humans; machine readable formats stay silent on success.
+    pub fn message(self, message: &str) {
+        match self.format {
+            OutputFormat::Table | OutputFormat::Wide | OutputFormat::Plain => println!("{}", message),
+            OutputFormat::Json | OutputFormat::Yaml => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::output::{Output, OutputFormat, Table};
+
+    #[test]
+    fn aligned_table() {
+        let mut table = Table::new(&["NAME", "BACKLOG"]);
+        table.add_row(vec!["a-long-name".to_string(), "1".to_string()]);
+        table.add_row(vec!["b".to_string(), "200".to_string()]);
+        assert_eq!(table.render(true), "NAME          BACKLOG\na-long-name   1\nb             200\n");
+        assert_eq!(table.render_plain(), "a-long-name\t1\nb\t200\n");
+    }
+
+    #[test]
+    fn formats() {
+        let topics = vec!["persistent://public/default/a".to_string()];
+        assert_eq!(Output::new(OutputFormat::Plain).to_string(&topics).unwrap(), "persistent://public/default/a\n");
+        assert_eq!(Output::new(OutputFormat::Json).to_string(&topics).unwrap(),
+                   "[\n  \"persistent://public/default/a\"\n]\n");
+
+        let doc = json!({"b": {"c": [1, 2]}, "a": "x", "d": null});
+        assert_eq!(Output::new(OutputFormat::Table).to_string(&doc).unwrap(),
+                   "FIELD   VALUE\na       x\nb.c     1,2\nd\n");
+    }
+}